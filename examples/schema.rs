@@ -3,9 +3,17 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use warehouse_facility::contract_info::ContractInfo;
-use warehouse_facility::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use warehouse_facility::state::{Facility, Pledge};
+use warehouse_facility::contract_info::{ContractConfig, ContractInfo, ContractVersion};
+use warehouse_facility::msg::{
+    AvailableCreditResponse, CheckAssetsAvailableResponse, EscrowBalanceResponse,
+    EscrowedPledgesResponse, ExecuteMsg, FacilitySummary, InstantiateMsg, ListClosedPledgesResponse,
+    ListIdsResponse, ListPledgesResponse, MigrateMsg, MigrateResponse, PartyRoleResponse,
+    PaydownStateResponse, PledgeStateResponse, PledgesWithContextResponse, ProposePledgeResponse,
+    QueryMsg, SimulatePledgeAdvanceResponse, SudoMsg, ValidateInstantiateResponse,
+};
+use warehouse_facility::state::{
+    Asset, AssetTransition, Facility, MarkerDistribution, Paydown, Pledge,
+};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -13,11 +21,34 @@ fn main() {
     create_dir_all(&out_dir).unwrap();
     remove_schemas(&out_dir).unwrap();
 
+    export_schema(&schema_for!(Asset), &out_dir);
+    export_schema(&schema_for!(AssetTransition), &out_dir);
+    export_schema(&schema_for!(AvailableCreditResponse), &out_dir);
+    export_schema(&schema_for!(CheckAssetsAvailableResponse), &out_dir);
+    export_schema(&schema_for!(ContractConfig), &out_dir);
     export_schema(&schema_for!(ContractInfo), &out_dir);
+    export_schema(&schema_for!(ContractVersion), &out_dir);
+    export_schema(&schema_for!(EscrowBalanceResponse), &out_dir);
+    export_schema(&schema_for!(EscrowedPledgesResponse), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(Facility), &out_dir);
+    export_schema(&schema_for!(FacilitySummary), &out_dir);
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
+    export_schema(&schema_for!(ListClosedPledgesResponse), &out_dir);
+    export_schema(&schema_for!(ListIdsResponse), &out_dir);
+    export_schema(&schema_for!(ListPledgesResponse), &out_dir);
+    export_schema(&schema_for!(MarkerDistribution), &out_dir);
     export_schema(&schema_for!(MigrateMsg), &out_dir);
+    export_schema(&schema_for!(MigrateResponse), &out_dir);
+    export_schema(&schema_for!(PartyRoleResponse), &out_dir);
+    export_schema(&schema_for!(Paydown), &out_dir);
+    export_schema(&schema_for!(PaydownStateResponse), &out_dir);
     export_schema(&schema_for!(Pledge), &out_dir);
+    export_schema(&schema_for!(PledgeStateResponse), &out_dir);
+    export_schema(&schema_for!(PledgesWithContextResponse), &out_dir);
+    export_schema(&schema_for!(ProposePledgeResponse), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(SimulatePledgeAdvanceResponse), &out_dir);
+    export_schema(&schema_for!(SudoMsg), &out_dir);
+    export_schema(&schema_for!(ValidateInstantiateResponse), &out_dir);
 }
@@ -1,28 +1,58 @@
-use crate::contract_info::{get_contract_info, set_contract_info, ContractInfo};
+use crate::contract_info::{
+    get_contract_config, get_contract_info, get_contract_version, is_closed, is_frozen, is_paused,
+    migrate_contract_info, set_closed, set_contract_info, set_frozen, set_paused, ContractInfo,
+};
 use crate::error::ContractError;
-use crate::msg::{Authorize, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, Validate};
+use crate::msg::{
+    asset_key, Authorize, AvailableCreditResponse, CheckAssetsAvailableResponse, ClosedPledge,
+    EscrowBalance, EscrowBalanceResponse, EscrowedPledgesResponse, ExecuteMsg, FacilitySummary,
+    InstantiateMsg, ListClosedPledgesResponse, ListIdsResponse, ListPledgesResponse, MigrateMsg,
+    MigrateResponse, PartyRoleResponse, PaydownStateResponse, PledgeStateResponse,
+    PledgesWithContextResponse, ProposePledgeResponse, QueryMsg, SimulatePledgeAdvanceResponse,
+    SudoMsg, Validate, ValidateInstantiateResponse,
+};
 use crate::state::{
-    find_pledge_ids_with_assets, get_asset_ids, get_asset_ids_by_filter, get_assets,
-    get_paydown_ids, get_paydowns, get_pledge_ids, get_pledges, load_paydown, load_pledge,
-    remove_assets, save_paydown, save_pledge, set_assets_state, Asset, AssetState, ContractParty,
-    Facility, Paydown, PaydownKind, PaydownSaleInfo, PaydownState, Pledge, PledgeState,
+    find_paydowns_for_pledge, find_pledge_ids_with_assets, find_pledges_with_assets, get_asset_ids,
+    get_asset_ids_by_filter, get_assets, get_paydown_ids, get_paydowns, get_pledge_ids,
+    get_pledge_ids_by_states, get_pledges, get_pledges_by_states, get_pledges_page,
+    is_asset_marker_denom_used, load_asset_history, load_paydown, load_paydown_state, load_pledge,
+    load_pledge_raw, load_pledge_state, may_load_asset, may_load_paydown, may_load_pledge,
+    migrate_asset_marker_denoms, migrate_paydowns, migrate_pledges, register_asset_marker_denom,
+    remove_assets, save_paydown, save_pledge, set_assets_state, Asset, AssetState, AssetTransition,
+    ContractParty, Facility, MarkerDistribution, Paydown, PaydownKind, PaydownSaleInfo,
+    PaydownState, Pledge, PledgeState,
+};
+use crate::utils::{
+    marker_address_for_denom, normalize_asset_id, pledge_marker_denom, vec_contains, vec_has_any,
+    MetadataAddress,
 };
-use crate::utils::{vec_contains, vec_has_any};
 use cosmwasm_std::{
-    attr, coins, entry_point, to_binary, Addr, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo,
-    Response, StdResult, Storage,
+    attr, coin, coins, entry_point, to_binary, Addr, BankMsg, Binary, ContractResult, CosmosMsg,
+    Deps, DepsMut, Env, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage, SubMsg,
+    Timestamp, Uint128,
 };
 use provwasm_std::{
     activate_marker, bind_name, cancel_marker, create_marker, destroy_marker, finalize_marker,
-    grant_marker_access, transfer_marker_coins, withdraw_coins, AccessGrant, Marker, MarkerAccess,
-    MarkerType, NameBinding, ProvenanceMsg, ProvenanceQuerier,
+    grant_marker_access, transfer_marker_coins, unbind_name, withdraw_coins, AccessGrant, Marker,
+    MarkerAccess, MarkerType, NameBinding, ProvenanceMsg, ProvenanceQuerier,
 };
-use rust_decimal::prelude::{FromStr, ToPrimitive};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal::Decimal;
-use std::ops::{Div, Mul};
+use std::collections::BTreeMap;
+use uuid::Uuid;
 
 pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// Reply ids for the marker setup submessages, used to report which step failed.
+const REPLY_CREATE_MARKER: u64 = 1;
+const REPLY_GRANT_MARKER_ACCESS: u64 = 2;
+const REPLY_FINALIZE_MARKER: u64 = 3;
+const REPLY_ACTIVATE_MARKER: u64 = 4;
+
+// The default and maximum number of pledges returned by a single list pledges query.
+const DEFAULT_LIST_PLEDGES_LIMIT: u32 = 30;
+const MAX_LIST_PLEDGES_LIMIT: u32 = 100;
+
 fn marker_has_grant(marker: Marker, grant: AccessGrant) -> bool {
     let access = marker
         .permissions
@@ -37,6 +67,64 @@ fn marker_has_grant(marker: Marker, grant: AccessGrant) -> bool {
     has_grant
 }
 
+// derive the address of a marker account from its denom, using the same bech32 prefix as the
+// contract address.
+fn derive_marker_address(contract_address: &Addr, denom: &str) -> StdResult<Addr> {
+    let (hrp, _, _) = bech32::decode(contract_address.as_str())
+        .map_err(|e| StdError::generic_err(format!("Invalid contract address: {}", e)))?;
+    let address = marker_address_for_denom(&hrp, denom)
+        .map_err(|e| StdError::generic_err(format!("Invalid marker address: {}", e)))?;
+    Ok(Addr::unchecked(address))
+}
+
+// look up the escrow marker, ensuring that the contract has privs on it.
+fn load_escrow_marker(
+    deps: Deps,
+    contract_address: &Addr,
+    escrow_marker: &Addr,
+) -> Result<Marker, ContractError> {
+    let marker = ProvenanceQuerier::new(&deps.querier)
+        .get_marker_by_address(escrow_marker.clone())
+        .map_err(|_| ContractError::EscrowMarkerNotFound {
+            address: escrow_marker.to_string(),
+        })?;
+    if !marker_has_grant(
+        marker.clone(),
+        AccessGrant {
+            address: contract_address.clone(),
+            permissions: vec![MarkerAccess::Transfer, MarkerAccess::Withdraw],
+        },
+    ) {
+        return Err(ContractError::MissingEscrowMarkerGrant {});
+    }
+    Ok(marker)
+}
+
+// look up the address of a marker account from its denom.
+fn get_marker_address(deps: &Deps, denom: &str) -> Result<Addr, ContractError> {
+    ProvenanceQuerier::new(&deps.querier)
+        .get_marker_by_denom(denom)
+        .map(|marker| marker.address)
+        .map_err(|error| {
+            if is_marker_not_found(&error) {
+                ContractError::MarkerNotFound {
+                    denom: denom.into(),
+                }
+            } else {
+                error.into()
+            }
+        })
+}
+
+// Whether a querier error means that the queried marker doesn't exist.
+fn is_marker_not_found(error: &StdError) -> bool {
+    match error {
+        StdError::NotFound { .. } => true,
+        StdError::GenericErr { msg, .. } => msg.contains("not found"),
+        _ => false,
+    }
+}
+
 // check if all of the specified assets are in the inventory with the optionally specified state (None = any state).
 fn assets_in_inventory(
     storage: &dyn Storage,
@@ -44,7 +132,7 @@ fn assets_in_inventory(
     assets: &[String],
 ) -> bool {
     let inventory_assets = get_asset_ids(storage, state, None, None).unwrap();
-    vec_contains(&inventory_assets, &assets)
+    vec_contains(&inventory_assets, assets)
 }
 
 // check if any of the specified assets are in the inventory with the optionally specified state (None = any state).
@@ -54,7 +142,225 @@ fn any_assets_in_inventory(
     assets: &[String],
 ) -> bool {
     let inventory_assets = get_asset_ids(storage, state, None, None).unwrap();
-    vec_has_any(&inventory_assets, &assets)
+    vec_has_any(&inventory_assets, assets)
+}
+
+// The purpose of the funds required by a message, used to report missing or insufficient funds.
+enum RequiredFunds {
+    // The advance, escrowed from the warehouse when it accepts a pledge.
+    PledgeAdvance,
+
+    // The paydown, escrowed from the originator when it proposes a paydown.
+    Paydown,
+
+    // The purchase price, escrowed from the buyer when it accepts a paydown and sale.
+    Purchase,
+}
+
+// ensure that the sender sent at least the required amount of the denom, returning the surplus.
+// NOTE: Funds in other denoms are not counted towards the required amount, and are refunded
+//       along with the surplus (see refund_surplus).
+fn require_funds(
+    info: &MessageInfo,
+    denom: &str,
+    amount: u128,
+    purpose: RequiredFunds,
+) -> Result<u128, ContractError> {
+    if info.funds.is_empty() {
+        return Err(match purpose {
+            RequiredFunds::PledgeAdvance => ContractError::MissingPledgeAdvanceFunds {},
+            RequiredFunds::Paydown => ContractError::MissingPaydownFunds {},
+            RequiredFunds::Purchase => ContractError::MissingPurchaseFunds {},
+        });
+    }
+
+    let received: u128 = info
+        .funds
+        .iter()
+        .filter(|coin| coin.denom == denom)
+        .map(|coin| coin.amount.u128())
+        .sum();
+    if received < amount {
+        // report the first coin sent when none of the funds are in the required denom
+        let (received, received_denom) = match received {
+            0 => (info.funds[0].amount.u128(), info.funds[0].denom.clone()),
+            _ => (received, denom.to_string()),
+        };
+        let (need, need_denom) = (amount, denom.to_string());
+        return Err(match purpose {
+            RequiredFunds::PledgeAdvance => ContractError::InsufficientPledgeAdvanceFunds {
+                need,
+                need_denom,
+                received,
+                received_denom,
+            },
+            RequiredFunds::Paydown => ContractError::InsufficientPaydownFunds {
+                need,
+                need_denom,
+                received,
+                received_denom,
+            },
+            RequiredFunds::Purchase => ContractError::InsufficientPurchaseFunds {
+                need,
+                need_denom,
+                received,
+                received_denom,
+            },
+        });
+    }
+
+    Ok(received - amount)
+}
+
+// select the configured stablecoin denom that the sender paid in, so that the required funds are
+// checked (and any surplus refunded) in that denom.
+// NOTE: When funds were sent in more than one configured denom, the denom with the most funds is
+//       used; when none were, the first configured denom is reported as the required denom.
+fn select_stablecoin_denom(info: &MessageInfo, denoms: &[String]) -> String {
+    let received = |denom: &String| -> u128 {
+        info.funds
+            .iter()
+            .filter(|coin| &coin.denom == denom)
+            .map(|coin| coin.amount.u128())
+            .sum()
+    };
+
+    let mut selected = &denoms[0];
+    for denom in denoms {
+        if received(denom) > received(selected) {
+            selected = denom;
+        }
+    }
+    selected.clone()
+}
+
+// get the stablecoin denom recorded for funds held in escrow, falling back to the first configured
+// denom for pledges and paydowns that were stored before the denom was recorded.
+fn recorded_stablecoin_denom(facility: &Facility, recorded: &Option<String>) -> String {
+    recorded
+        .clone()
+        .unwrap_or_else(|| facility.stablecoin_denoms[0].clone())
+}
+
+// build a message refunding the surplus funds of the denom, along with any funds sent in other
+// denoms, to the sender.
+// NOTE: The refunded coins are combined by denom and sorted, as the bank module requires.
+fn refund_surplus(info: &MessageInfo, denom: &str, surplus: u128) -> Option<BankMsg> {
+    let mut refunds: BTreeMap<&str, u128> = BTreeMap::new();
+    for funds in info.funds.iter().filter(|funds| funds.denom != denom) {
+        *refunds.entry(funds.denom.as_str()).or_default() += funds.amount.u128();
+    }
+    if surplus > 0 {
+        refunds.insert(denom, surplus);
+    }
+
+    let amount: Vec<_> = refunds
+        .into_iter()
+        .filter(|(_, amount)| *amount > 0)
+        .map(|(denom, amount)| coin(amount, denom))
+        .collect();
+    if amount.is_empty() {
+        return None;
+    }
+    Some(BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount,
+    })
+}
+
+// calculate the total supply and distribution of the facility marker for the advance rate.
+// NOTE: The total supply is scaled to the precision of the advance rate, so the warehouse share is
+//       always a whole amount (whatever the rounding mode) and the remainder is assigned to the
+//       originator, so the distribution always adds up to the total supply.
+fn calculate_marker_distribution(facility: &Facility) -> Result<MarkerDistribution, ContractError> {
+    let invalid_advance_rate = || {
+        ContractError::invalid_field(
+            "facility.advance_rate",
+            "is too precise to represent as a facility marker supply",
+        )
+    };
+
+    // get the advance rate
+    let rate = facility.advance_rate_decimal()?;
+
+    let total_supply: u128 = 10u128
+        .checked_pow(rate.scale() + 2)
+        .ok_or_else(invalid_advance_rate)?;
+    let to_warehouse: u128 = rate
+        .checked_div(Decimal::from(100))
+        .zip(Decimal::from_u128(total_supply))
+        .and_then(|(rate, supply)| rate.checked_mul(supply))
+        .and_then(|amount| amount.to_u128())
+        .ok_or_else(invalid_advance_rate)?;
+    let to_originator: u128 = total_supply
+        .checked_sub(to_warehouse)
+        .ok_or_else(invalid_advance_rate)?;
+
+    Ok(MarkerDistribution {
+        total_supply: total_supply.into(),
+        to_warehouse: to_warehouse.into(),
+        to_originator: to_originator.into(),
+        advance_rate: facility.advance_rate.clone(),
+    })
+}
+
+// Split an asset value between the warehouse and originator in the same proportion as the
+// facility marker distribution for the advance rate.
+// NOTE: The warehouse share is rounded to a whole amount with the facility rounding mode (default:
+//       truncate) and the remainder is assigned to the originator.
+fn simulate_pledge_advance(
+    facility: &Facility,
+    asset_value: u64,
+) -> Result<SimulatePledgeAdvanceResponse, ContractError> {
+    // ensure the advance rate can be represented as a facility marker distribution
+    calculate_marker_distribution(facility)?;
+    let rate = facility.advance_rate_decimal()?;
+
+    let to_warehouse: u128 = Decimal::from_u64(asset_value)
+        .and_then(|value| value.checked_mul(rate))
+        .and_then(|amount| amount.checked_div(Decimal::from(100)))
+        .map(|share| facility.round_warehouse_share(share))
+        .and_then(|share| share.to_u128())
+        .ok_or_else(|| {
+            ContractError::invalid_field("asset_value", "is too large to split at the advance rate")
+        })?;
+    let to_warehouse = Uint128::from(to_warehouse);
+    let asset_value = Uint128::from(asset_value);
+    let to_originator = asset_value
+        .checked_sub(to_warehouse)
+        .map_err(StdError::from)?;
+
+    Ok(SimulatePledgeAdvanceResponse {
+        total_advance: to_warehouse,
+        marker_to_warehouse: to_warehouse,
+        marker_to_originator: to_originator,
+    })
+}
+
+// Build the submessages that create a restricted marker and make it usable by the contract.
+// NOTE: Each step replies on error, so that a failure is reported with the step that failed.
+fn marker_setup_messages(
+    supply: u128,
+    denom: String,
+    contract_address: Addr,
+    access: Vec<MarkerAccess>,
+) -> StdResult<Vec<SubMsg<ProvenanceMsg>>> {
+    Ok(vec![
+        // create the marker
+        SubMsg::reply_on_error(
+            create_marker(supply, denom.clone(), MarkerType::Restricted)?,
+            REPLY_CREATE_MARKER,
+        ),
+        // set privileges on the marker
+        SubMsg::reply_on_error(
+            grant_marker_access(denom.clone(), contract_address, access)?,
+            REPLY_GRANT_MARKER_ACCESS,
+        ),
+        // finalize the marker
+        SubMsg::reply_on_error(finalize_marker(denom.clone())?, REPLY_FINALIZE_MARKER),
+        // activate the marker
+        SubMsg::reply_on_error(activate_marker(denom)?, REPLY_ACTIVATE_MARKER),
+    ])
 }
 
 // smart contract initialization entrypoint
@@ -63,26 +369,27 @@ pub fn instantiate(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    msg: InstantiateMsg,
+    mut msg: InstantiateMsg,
 ) -> Result<Response<ProvenanceMsg>, ContractError> {
     // validate the message
     msg.validate()?;
 
-    // get the advance rate
-    let advance_rate = Decimal::from_str(&msg.facility.advance_rate).map_err(|_| {
-        ContractError::InvalidFields {
-            fields: vec![String::from("facility.advance_rate")],
-        }
-    })?;
+    // store the rates without any surrounding whitespace
+    msg.facility.advance_rate = msg.facility.advance_rate.trim().into();
+    msg.facility.paydown_rate = msg.facility.paydown_rate.trim().into();
+
+    // ensure the contract has privs on the escrow marker
+    load_escrow_marker(
+        deps.as_ref(),
+        &env.contract.address,
+        &msg.facility.escrow_marker,
+    )?;
 
     // calculate the total supply and distribution of facility marker
-    let facility_marker_supply: u128 = 10u128.pow(advance_rate.scale() + 2);
-    let facility_marker_to_warehouse: u128 = advance_rate
-        .div(Decimal::from(100))
-        .mul(Decimal::from(facility_marker_supply))
-        .to_u128()
-        .unwrap();
-    let facility_marker_to_originator: u128 = facility_marker_supply - facility_marker_to_warehouse;
+    let distribution = calculate_marker_distribution(&msg.facility)?;
+    let facility_marker_supply: u128 = distribution.total_supply.u128();
+    let facility_marker_to_warehouse: u128 = distribution.to_warehouse.u128();
+    let facility_marker_to_originator: u128 = distribution.to_originator.u128();
 
     // save contract info
     let contract_info = ContractInfo::new(
@@ -98,21 +405,15 @@ pub fn instantiate(
     let mut messages = Vec::new();
 
     // create name binding
-    messages.push(bind_name(
+    messages.push(SubMsg::new(bind_name(
         contract_info.bind_name,
         env.contract.address.clone(),
         NameBinding::Restricted,
-    )?);
+    )?));
 
-    // create facility marker
-    messages.push(create_marker(
+    // create, grant privileges on, finalize and activate the facility marker
+    messages.extend(marker_setup_messages(
         facility_marker_supply,
-        msg.facility.marker_denom.clone(),
-        MarkerType::Restricted,
-    )?);
-
-    // set privileges on the facility marker
-    messages.push(grant_marker_access(
         msg.facility.marker_denom.clone(),
         env.contract.address,
         vec![
@@ -124,31 +425,25 @@ pub fn instantiate(
         ],
     )?);
 
-    // finalize the facility marker
-    messages.push(finalize_marker(msg.facility.marker_denom.clone())?);
-
-    // activate the facility marker
-    messages.push(activate_marker(msg.facility.marker_denom.clone())?);
-
     // withdraw the facility marker to the warehouse address
-    messages.push(withdraw_coins(
+    messages.push(SubMsg::new(withdraw_coins(
         msg.facility.marker_denom.clone(),
         facility_marker_to_warehouse,
         msg.facility.marker_denom.clone(),
         Addr::unchecked(msg.facility.warehouse),
-    )?);
+    )?));
 
     // withdraw the facility marker to the originator address
-    messages.push(withdraw_coins(
+    messages.push(SubMsg::new(withdraw_coins(
         msg.facility.marker_denom.clone(),
         facility_marker_to_originator,
         msg.facility.marker_denom.clone(),
         Addr::unchecked(msg.facility.originator),
-    )?);
+    )?));
 
     // build response
     Ok(Response::new()
-        .add_messages(messages)
+        .add_submessages(messages)
         .add_attributes(vec![
             attr(
                 "contract_info",
@@ -172,6 +467,19 @@ pub fn execute(
     // authorize the sender
     let contract_info = get_contract_info(deps.storage)?;
     msg.authorize(contract_info.clone(), info.sender.clone())?;
+    if let ExecuteMsg::CancelPledge { id } = &msg {
+        authorize_cancel(deps.as_ref(), &contract_info, &info.sender, id)?;
+    }
+
+    // no activity is allowed once the facility has been closed
+    if is_closed(deps.storage)? {
+        return Err(ContractError::FacilityClosed {});
+    }
+
+    // no new proposals are allowed while the facility is paused by the admin
+    if msg.is_proposal() && is_paused(deps.storage)? {
+        return Err(ContractError::Paused {});
+    }
 
     match msg {
         ExecuteMsg::ProposePledge {
@@ -179,24 +487,72 @@ pub fn execute(
             assets,
             total_advance,
             asset_marker_denom,
+            valid_until,
+            document_hash,
         } => propose_pledge(
             deps,
             env,
             info,
             contract_info,
+            id,
+            canonical_assets(&assets)?,
+            total_advance,
+            asset_marker_denom,
+            valid_until,
+            document_hash,
+        ),
+        ExecuteMsg::ReproposePledge {
             id,
             assets,
             total_advance,
             asset_marker_denom,
+            valid_until,
+            document_hash,
+        } => repropose_pledge(
+            deps,
+            env,
+            info,
+            contract_info,
+            id,
+            canonical_assets(&assets)?,
+            total_advance,
+            asset_marker_denom,
+            valid_until,
+            document_hash,
+        ),
+        ExecuteMsg::AmendPledge {
+            id,
+            assets,
+            total_advance,
+        } => amend_pledge(
+            deps,
+            env,
+            info,
+            contract_info,
+            id,
+            assets.map(|assets| canonical_assets(&assets)).transpose()?,
+            total_advance,
         ),
         ExecuteMsg::AcceptPledge { id } => accept_pledge(deps, env, info, contract_info, id),
+        ExecuteMsg::AcceptPledges { ids } => accept_pledges(deps, env, info, contract_info, ids),
         ExecuteMsg::CancelPledge { id } => cancel_pledge(deps, env, info, contract_info, id),
+        ExecuteMsg::RejectPledge { id, reason } => {
+            reject_pledge(deps, env, info, contract_info, id, reason)
+        }
         ExecuteMsg::ExecutePledge { id } => execute_pledge(deps, env, info, contract_info, id),
         ExecuteMsg::ProposePaydown {
             id,
             assets,
             total_paydown,
-        } => propose_paydown(deps, env, info, contract_info, id, assets, total_paydown),
+        } => propose_paydown(
+            deps,
+            env,
+            info,
+            contract_info,
+            id,
+            canonical_assets(&assets)?,
+            total_paydown,
+        ),
         ExecuteMsg::ProposePaydownAndSell {
             id,
             assets,
@@ -209,7 +565,7 @@ pub fn execute(
             info,
             contract_info,
             id,
-            assets,
+            canonical_assets(&assets)?,
             total_paydown,
             buyer,
             purchase_price,
@@ -217,9 +573,42 @@ pub fn execute(
         ExecuteMsg::AcceptPaydown { id } => accept_paydown(deps, env, info, contract_info, id),
         ExecuteMsg::CancelPaydown { id } => cancel_paydown(deps, env, info, contract_info, id),
         ExecuteMsg::ExecutePaydown { id } => execute_paydown(deps, env, info, contract_info, id),
+        ExecuteMsg::RecordRepayment {
+            pledge_id,
+            reference,
+        } => record_repayment(deps, env, info, contract_info, pledge_id, reference),
+        ExecuteMsg::UpdateBinding { bind_name } => {
+            update_binding(deps, env, info, contract_info, bind_name)
+        }
+        ExecuteMsg::SetPaused { paused } => {
+            set_facility_paused(deps, env, info, contract_info, paused)
+        }
+        ExecuteMsg::SettleCancelledMarkers {} => {
+            settle_cancelled_markers(deps, env, info, contract_info)
+        }
+        ExecuteMsg::TransferOriginator { new_originator } => {
+            transfer_originator(deps, env, info, contract_info, new_originator)
+        }
+        ExecuteMsg::CloseFacility {} => close_facility(deps, env, info, contract_info),
     }
 }
 
+// Normalize the assets (scope UUIDs or bech32 scope addresses) to their canonical scope UUIDs,
+// sorted so that the same assets are stored the same way however (and in whatever order) they were
+// specified.
+// NOTE: Duplicate assets (in either form) are already rejected by the message validation.
+fn canonical_assets(assets: &[String]) -> Result<Vec<String>, ContractError> {
+    let mut canonical = assets
+        .iter()
+        .map(|asset| {
+            normalize_asset_id(asset)
+                .map_err(|e| ContractError::invalid_field("asset", e.to_string()))
+        })
+        .collect::<Result<Vec<String>, ContractError>>()?;
+    canonical.sort();
+    Ok(canonical)
+}
+
 #[allow(clippy::too_many_arguments)]
 fn propose_pledge(
     deps: DepsMut,
@@ -228,237 +617,666 @@ fn propose_pledge(
     contract_info: ContractInfo,
     id: String,
     assets: Vec<String>,
-    total_advance: u64,
+    total_advance: Uint128,
     asset_marker_denom: String,
+    valid_until: Option<Timestamp>,
+    document_hash: Option<String>,
 ) -> Result<Response<ProvenanceMsg>, ContractError> {
     // ensure that a pledge with the specified id doesn't already exist
-    let pledge = load_pledge(deps.storage, id.as_bytes());
-    if let Ok(v) = pledge {
-        return Err(ContractError::PledgeAlreadyExists { id: v.id });
-    }
-
-    // ensure that the assets are not in the inventory
-    if any_assets_in_inventory(deps.storage, None, &assets) {
-        return Err(ContractError::AssetsAlreadyPledged {});
-    }
-
-    // ensure the contract has privs on the escrow marker
-    let querier = ProvenanceQuerier::new(&deps.querier);
-    let escrow_marker =
-        querier.get_marker_by_address(contract_info.facility.escrow_marker.clone())?;
-    if !marker_has_grant(
-        escrow_marker,
-        AccessGrant {
-            address: env.contract.address.clone(),
-            permissions: vec![MarkerAccess::Transfer, MarkerAccess::Withdraw],
-        },
-    ) {
-        return Err(ContractError::MissingEscrowMarkerGrant {});
+    if may_load_pledge(deps.storage, id.as_bytes())?.is_some() {
+        return Err(ContractError::PledgeAlreadyExists { id });
     }
 
-    // create the pledge
-    let pledge = Pledge {
+    save_pledge_proposal(
+        deps,
+        env,
+        contract_info,
         id,
         assets,
         total_advance,
-        asset_marker_denom: asset_marker_denom.clone(),
-        state: PledgeState::Proposed,
-    };
-
-    // save the pledge
-    save_pledge(deps.storage, &pledge.id.as_bytes(), &pledge)?;
+        asset_marker_denom,
+        valid_until,
+        document_hash,
+        "propose_pledge",
+    )
+}
 
-    // update the asset(s) state in the facility inventory
-    set_assets_state(deps.storage, AssetState::PledgeProposed, &pledge.assets)?;
+#[allow(clippy::too_many_arguments)]
+fn repropose_pledge(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    contract_info: ContractInfo,
+    id: String,
+    assets: Vec<String>,
+    total_advance: Uint128,
+    asset_marker_denom: String,
+    valid_until: Option<Timestamp>,
+    document_hash: Option<String>,
+) -> Result<Response<ProvenanceMsg>, ContractError> {
+    // locate the pledge
+    let pledge = may_load_pledge(deps.storage, id.as_bytes())?
+        .ok_or(ContractError::PledgeNotFound { id: id.clone() })?;
 
-    // TODO: using metadata module, we need to lookup the assets by id and change the value owner
+    // only pledges that are in the "CANCELLED" or "REJECTED" states can be re-proposed
+    if pledge.state != PledgeState::Cancelled && pledge.state != PledgeState::Rejected {
+        return Err(ContractError::StateError {
+            error:
+                "Unable to re-propose pledge: Pledge is not in the 'cancelled' or 'rejected' state."
+                    .into(),
+        });
+    }
 
-    // messages to include in transaction
-    let messages = vec![
-        // create asset pool marker
-        create_marker(1, asset_marker_denom.clone(), MarkerType::Restricted)?,
-        // set privileges on the asset pool marker
-        grant_marker_access(
-            asset_marker_denom.clone(),
-            env.contract.address,
-            vec![
-                MarkerAccess::Admin,
-                MarkerAccess::Burn,
-                MarkerAccess::Delete,
-                MarkerAccess::Deposit,
-                MarkerAccess::Mint,
-                MarkerAccess::Transfer,
-                MarkerAccess::Withdraw,
-            ],
-        )?,
-        // finalize the asset pool marker
-        finalize_marker(asset_marker_denom.clone())?,
-        // activate the asset pool marker
-        activate_marker(asset_marker_denom.clone())?,
-        // withdraw the asset pool marker to the originator address
-        withdraw_coins(
-            asset_marker_denom.clone(),
-            1,
-            asset_marker_denom,
-            Addr::unchecked(contract_info.facility.originator),
-        )?,
-    ];
+    // ensure the asset marker of the cancelled or rejected pledge has been destroyed, since the
+    // re-proposal creates its asset marker again with the same denom
+    match get_marker_address(&deps.as_ref(), &pledge.asset_marker_denom) {
+        Err(ContractError::MarkerNotFound { .. }) => {}
+        Ok(_) => {
+            return Err(ContractError::AssetMarkerStillExists {
+                denom: pledge.asset_marker_denom,
+            })
+        }
+        Err(error) => return Err(error),
+    }
 
-    Ok(Response::new()
-        .add_messages(messages)
-        .add_attribute("action", "propose_pledge")
-        .set_data(to_binary(&pledge)?))
+    save_pledge_proposal(
+        deps,
+        env,
+        contract_info,
+        id,
+        assets,
+        total_advance,
+        asset_marker_denom,
+        valid_until,
+        document_hash,
+        "repropose_pledge",
+    )
 }
 
-fn accept_pledge(
+// Amend the assets and/or advance of a pledge proposal in place, keeping its asset pool marker.
+// NOTE: Assets removed from the proposal are removed from the inventory, and assets added to it
+//       must not already be in the inventory.
+fn amend_pledge(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
-    contract_info: ContractInfo,
+    _info: MessageInfo,
+    _contract_info: ContractInfo,
     id: String,
+    assets: Option<Vec<String>>,
+    total_advance: Option<Uint128>,
 ) -> Result<Response<ProvenanceMsg>, ContractError> {
     // locate the pledge
-    let mut pledge = load_pledge(deps.storage, id.as_bytes())?;
+    let mut pledge = may_load_pledge(deps.storage, id.as_bytes())?
+        .ok_or(ContractError::PledgeNotFound { id })?;
 
-    // only pledges that are in the "PROPOSED" state can be accepted
+    // only pledges that are in the "PROPOSED" state can be amended
     if pledge.state != PledgeState::Proposed {
         return Err(ContractError::StateError {
-            error: "Unable to accept pledge: Pledge is not in the 'proposed' state.".into(),
+            error: "Unable to amend pledge: Pledge is not in the 'proposed' state.".into(),
         });
     }
 
-    // ensure the contract has privs on the escrow marker
-    let querier = ProvenanceQuerier::new(&deps.querier);
-    let escrow_marker =
-        querier.get_marker_by_address(contract_info.facility.escrow_marker.clone())?;
-    if !marker_has_grant(
-        escrow_marker.clone(),
-        AccessGrant {
-            address: env.contract.address,
-            permissions: vec![MarkerAccess::Transfer, MarkerAccess::Withdraw],
-        },
-    ) {
-        return Err(ContractError::MissingEscrowMarkerGrant {});
-    }
+    if let Some(assets) = assets {
+        let removed: Vec<String> = pledge
+            .assets
+            .iter()
+            .filter(|asset| !assets.contains(asset))
+            .cloned()
+            .collect();
+        let added: Vec<String> = assets
+            .iter()
+            .filter(|asset| !pledge.assets.contains(asset))
+            .cloned()
+            .collect();
+
+        // ensure that the added assets are not in the inventory
+        if any_assets_in_inventory(deps.storage, None, &added) {
+            return Err(ContractError::AssetsAlreadyPledged {});
+        }
 
-    // make sure that the warehouse sent the appropriate stablecoin
-    let advance_funds = info
-        .funds
-        .get(0)
-        .ok_or(ContractError::MissingPledgeAdvanceFunds {})?;
-    if (advance_funds.denom != contract_info.facility.stablecoin_denom)
-        || (advance_funds.amount != pledge.total_advance.into())
-    {
-        return Err(ContractError::InsufficientPledgeAdvanceFunds {
-            need: pledge.total_advance.to_u128().unwrap(),
-            need_denom: contract_info.facility.stablecoin_denom,
-            received: advance_funds.amount.u128(),
-            received_denom: advance_funds.denom.clone(),
-        });
+        // free the removed asset(s) and claim the added asset(s) in the facility inventory
+        remove_assets(deps.storage, &removed, env.block.time)?;
+        set_assets_state(
+            deps.storage,
+            AssetState::PledgeProposed,
+            &added,
+            env.block.time,
+        )?;
+
+        pledge.assets = assets;
     }
 
-    // messages to include in transaction
-    let messages = vec![
-        // forward stablecoin to escrow marker account
-        BankMsg::Send {
-            to_address: escrow_marker.address.to_string(),
-            amount: coins(
-                pledge.total_advance.into(),
-                contract_info.facility.stablecoin_denom,
-            ),
-        },
-    ];
+    if let Some(total_advance) = total_advance {
+        pledge.total_advance = total_advance;
+    }
 
     // update the pledge
-    pledge.state = PledgeState::Accepted;
-    save_pledge(deps.storage, &pledge.id.as_bytes(), &pledge)?;
+    save_pledge(deps.storage, pledge.id.as_bytes(), &pledge)?;
 
     Ok(Response::new()
-        .add_messages(messages)
-        .add_attribute("action", "accept_pledge")
+        .add_attribute("action", "amend_pledge")
         .set_data(to_binary(&pledge)?))
 }
 
-fn cancel_pledge(
+// Save a new pledge proposal (replacing any existing pledge with the id) and set up its asset
+// pool marker.
+#[allow(clippy::too_many_arguments)]
+fn save_pledge_proposal(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
     contract_info: ContractInfo,
     id: String,
+    assets: Vec<String>,
+    total_advance: Uint128,
+    asset_marker_denom: String,
+    valid_until: Option<Timestamp>,
+    document_hash: Option<String>,
+    action: &str,
 ) -> Result<Response<ProvenanceMsg>, ContractError> {
-    // locate the pledge
-    let mut pledge = load_pledge(deps.storage, id.as_bytes())?;
-
-    // only pledges that are in the "PROPOSED" or "ACCEPTED" states can be cancelled
-    let remove_assets_from_escrow = true;
-    let mut remove_advance_from_escrow = false;
-    match pledge.state {
-        PledgeState::Proposed => {}
-        PledgeState::Accepted => {
-            remove_advance_from_escrow = true;
-        }
-        _ => {
-            return Err(ContractError::StateError {
-                error:
-                    "Unable to cancel pledge: Pledge is not in the 'proposed' or 'accepted' state."
-                        .into(),
-            })
+    // ensure the acceptance deadline (if any) hasn't already passed
+    if let Some(valid_until) = valid_until {
+        if valid_until < env.block.time {
+            return Err(ContractError::invalid_field(
+                "valid_until",
+                "must not be before the current block time",
+            ));
         }
     }
 
-    // ensure the contract has privs on the escrow marker
-    let querier = ProvenanceQuerier::new(&deps.querier);
-    let escrow_marker =
-        querier.get_marker_by_address(contract_info.facility.escrow_marker.clone())?;
-    if !marker_has_grant(
-        escrow_marker.clone(),
-        AccessGrant {
-            address: env.contract.address,
-            permissions: vec![MarkerAccess::Transfer, MarkerAccess::Withdraw],
-        },
-    ) {
-        return Err(ContractError::MissingEscrowMarkerGrant {});
+    // ensure that the assets are not in the inventory
+    if any_assets_in_inventory(deps.storage, None, &assets) {
+        return Err(ContractError::AssetsAlreadyPledged {});
     }
 
-    // messages to include in transaction
-    let mut messages = Vec::new();
+    // ensure the asset marker denom doesn't collide with the facility marker
+    if asset_marker_denom == contract_info.facility.marker_denom {
+        return Err(ContractError::AssetMarkerDenomInUse {
+            denom: asset_marker_denom,
+            used_by: "the facility marker".into(),
+        });
+    }
 
-    // remove the advance from escrow back to the warehouse account
-    if remove_advance_from_escrow {
-        // withdraw advance funds from the escrow marker account to the warehouse
-        messages.push(withdraw_coins(
-            escrow_marker.denom,
-            pledge.total_advance.into(),
-            contract_info.facility.stablecoin_denom.clone(),
-            contract_info.facility.warehouse,
-        )?);
+    // ensure the asset marker denom has never been used by another pledge (in any state)
+    // NOTE: A re-proposed pledge reuses the denom of the pledge it replaces.
+    let replaced_denom =
+        may_load_pledge(deps.storage, id.as_bytes())?.map(|pledge| pledge.asset_marker_denom);
+    if replaced_denom.as_deref() != Some(asset_marker_denom.as_str())
+        && is_asset_marker_denom_used(deps.storage, &asset_marker_denom)?
+    {
+        return Err(ContractError::DenomAlreadyUsed {
+            denom: asset_marker_denom,
+        });
     }
 
-    // remove the assets (asset marker) from escrow
+    // ensure the asset marker denom is the one derived for this pledge
+    let expected_denom = pledge_marker_denom(&contract_info.facility.marker_denom, &id);
+    if asset_marker_denom != expected_denom {
+        return Err(ContractError::InvalidAssetMarkerDenom {
+            expected: expected_denom,
+        });
+    }
+
+    // ensure the contract has privs on the escrow marker
+    load_escrow_marker(
+        deps.as_ref(),
+        &env.contract.address,
+        &contract_info.facility.escrow_marker,
+    )?;
+
+    // create the pledge
+    let pledge = Pledge {
+        id,
+        assets,
+        total_advance,
+        asset_marker_denom: asset_marker_denom.clone(),
+        state: PledgeState::Proposed,
+        proposed_at: env.block.time,
+        accepted_at: None,
+        executed_at: None,
+        advance_denom: None,
+        valid_until,
+        document_hash,
+        repayment_reference: None,
+    };
+
+    // save the pledge and reserve its asset marker denom
+    save_pledge(deps.storage, pledge.id.as_bytes(), &pledge)?;
+    register_asset_marker_denom(deps.storage, &pledge.asset_marker_denom)?;
+
+    // update the asset(s) state in the facility inventory
+    set_assets_state(
+        deps.storage,
+        AssetState::PledgeProposed,
+        &pledge.assets,
+        env.block.time,
+    )?;
+
+    // TODO: using metadata module, we need to lookup the assets by id and change the value owner
+
+    // derive the address of the asset pool marker (it can't be queried, since the marker
+    // isn't created until the messages in this transaction are processed)
+    let marker_address = derive_marker_address(&env.contract.address, &asset_marker_denom)?;
+
+    // create, grant privileges on, finalize and activate the asset pool marker
+    let mut messages = marker_setup_messages(
+        1,
+        asset_marker_denom.clone(),
+        env.contract.address,
+        vec![
+            MarkerAccess::Admin,
+            MarkerAccess::Burn,
+            MarkerAccess::Delete,
+            MarkerAccess::Deposit,
+            MarkerAccess::Mint,
+            MarkerAccess::Transfer,
+            MarkerAccess::Withdraw,
+        ],
+    )?;
+
+    // withdraw the asset pool marker to the originator address
+    messages.push(SubMsg::new(withdraw_coins(
+        asset_marker_denom.clone(),
+        1,
+        asset_marker_denom,
+        Addr::unchecked(contract_info.facility.originator),
+    )?));
+
+    let mut response = Response::new()
+        .add_submessages(messages)
+        .add_attribute("action", action);
+    if let Some(document_hash) = &pledge.document_hash {
+        response = response.add_attribute("document_hash", document_hash);
+    }
+
+    Ok(response.set_data(to_binary(&ProposePledgeResponse {
+        pledge,
+        marker_address,
+    })?))
+}
+
+// Ensure the pledge proposal's acceptance deadline (if any) hasn't passed. The proposal can still
+// be accepted at the deadline itself.
+fn require_unexpired(pledge: &Pledge, env: &Env) -> Result<(), ContractError> {
+    match pledge.valid_until {
+        Some(valid_until) if env.block.time > valid_until => Err(ContractError::ProposalExpired {
+            id: pledge.id.clone(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+// Ensure the pledge's assets are all still proposed for pledge, and haven't been claimed by another
+// accepted or executed pledge (that still holds them) since the pledge was proposed.
+fn require_assets_still_proposed(
+    storage: &dyn Storage,
+    pledge: &Pledge,
+) -> Result<(), ContractError> {
+    if !assets_in_inventory(storage, Some(AssetState::PledgeProposed), &pledge.assets) {
+        return Err(ContractError::AssetsAlreadyPledged {});
+    }
+    for state in [PledgeState::Accepted, PledgeState::Executed] {
+        let claimants = find_pledges_with_assets(storage, &pledge.assets, Some(state), None, None)?;
+        for claimant in claimants {
+            if vec_has_any(&assets_held_for_pledge(storage, &claimant)?, &pledge.assets) {
+                return Err(ContractError::AssetsAlreadyPledged {});
+            }
+        }
+    }
+    Ok(())
+}
+
+// The pledge's assets that are still held for it. An asset paid down from the pledge can be pledged
+// again, after which it's held for the open (proposed, accepted or executed) pledge claiming it.
+fn assets_held_for_pledge(storage: &dyn Storage, pledge: &Pledge) -> StdResult<Vec<String>> {
+    let paid_down: Vec<String> =
+        find_paydowns_for_pledge(storage, pledge, Some(PaydownState::Executed))?
+            .into_iter()
+            .flat_map(|paydown| paydown.assets)
+            .collect();
+    let claimed: Vec<String> = get_pledges_by_states(
+        storage,
+        vec![
+            PledgeState::Proposed,
+            PledgeState::Accepted,
+            PledgeState::Executed,
+        ],
+        None,
+        None,
+    )?
+    .into_iter()
+    .filter(|other| other.id != pledge.id)
+    .flat_map(|other| other.assets)
+    .collect();
+    Ok(pledge
+        .assets
+        .iter()
+        .filter(|asset| !(paid_down.contains(asset) && claimed.contains(asset)))
+        .cloned()
+        .collect())
+}
+
+// Find the executed pledges that still hold any of the assets.
+fn find_pledge_ids_holding_assets(
+    storage: &dyn Storage,
+    assets: &[String],
+) -> StdResult<Vec<String>> {
+    let mut pledge_ids = vec![];
+    for pledge in
+        find_pledges_with_assets(storage, assets, Some(PledgeState::Executed), None, None)?
+    {
+        if vec_has_any(&assets_held_for_pledge(storage, &pledge)?, assets) {
+            pledge_ids.push(pledge.id);
+        }
+    }
+    Ok(pledge_ids)
+}
+
+// The total advance outstanding: escrowed for accepted pledges or disbursed for executed pledges.
+fn outstanding_advance(storage: &dyn Storage) -> StdResult<u128> {
+    Ok(get_pledges_by_states(
+        storage,
+        vec![PledgeState::Accepted, PledgeState::Executed],
+        None,
+        None,
+    )?
+    .iter()
+    .map(|pledge| pledge.total_advance.u128())
+    .sum())
+}
+
+// Ensure that advancing the additional amount won't take the total advance outstanding past the
+// facility limit, if the facility has one.
+fn require_within_facility_limit(
+    storage: &dyn Storage,
+    facility: &Facility,
+    additional: Uint128,
+) -> Result<(), ContractError> {
+    if let Some(limit) = facility.max_total_advance {
+        let would_be = outstanding_advance(storage)?.saturating_add(additional.u128());
+        if would_be > limit.u128() {
+            return Err(ContractError::FacilityLimitExceeded {
+                limit: limit.u128(),
+                would_be,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn accept_pledge(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    contract_info: ContractInfo,
+    id: String,
+) -> Result<Response<ProvenanceMsg>, ContractError> {
+    // locate the pledge
+    let mut pledge = may_load_pledge(deps.storage, id.as_bytes())?
+        .ok_or(ContractError::PledgeNotFound { id })?;
+
+    // only pledges that are in the "PROPOSED" state can be accepted
+    if !pledge.state.can_transition_to(&PledgeState::Accepted) {
+        return Err(ContractError::StateError {
+            error: "Unable to accept pledge: Pledge is not in the 'proposed' state.".into(),
+        });
+    }
+
+    // the proposal can't be accepted after its acceptance deadline
+    require_unexpired(&pledge, &env)?;
+
+    // the assets can't have been claimed by another pledge in the meantime
+    require_assets_still_proposed(deps.storage, &pledge)?;
+
+    // the advance can't take the facility past its limit
+    require_within_facility_limit(deps.storage, &contract_info.facility, pledge.total_advance)?;
+
+    // ensure the contract has privs on the escrow marker
+    let escrow_marker = load_escrow_marker(
+        deps.as_ref(),
+        &env.contract.address,
+        &contract_info.facility.escrow_marker,
+    )?;
+
+    // make sure that the warehouse sent the appropriate stablecoin (in any of the facility denoms)
+    let denom = select_stablecoin_denom(&info, &contract_info.facility.stablecoin_denoms);
+    let surplus = require_funds(
+        &info,
+        &denom,
+        pledge.total_advance.into(),
+        RequiredFunds::PledgeAdvance,
+    )?;
+    let refund = refund_surplus(&info, &denom, surplus);
+
+    // messages to include in transaction
+    let mut messages = vec![
+        // forward stablecoin to escrow marker account
+        BankMsg::Send {
+            to_address: escrow_marker.address.to_string(),
+            amount: coins(pledge.total_advance.into(), &denom),
+        },
+    ];
+
+    // refund any overpayment (and any funds in other denoms) to the warehouse
+    if let Some(refund) = refund {
+        messages.push(refund);
+    }
+
+    // update the pledge
+    pledge.state = PledgeState::Accepted;
+    pledge.accepted_at = Some(env.block.time);
+    pledge.advance_denom = Some(denom);
+    save_pledge(deps.storage, pledge.id.as_bytes(), &pledge)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "accept_pledge")
+        .set_data(to_binary(&pledge)?))
+}
+
+// Accept multiple pledge proposals at once, escrowing their combined advance.
+// NOTE: All of the pledges must be in the "PROPOSED" state, otherwise none of them are accepted.
+fn accept_pledges(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    contract_info: ContractInfo,
+    ids: Vec<String>,
+) -> Result<Response<ProvenanceMsg>, ContractError> {
+    // locate the pledges, checking them all before any are updated
+    let mut pledges: Vec<Pledge> = vec![];
+    for id in ids {
+        let pledge = may_load_pledge(deps.storage, id.as_bytes())?
+            .ok_or(ContractError::PledgeNotFound { id })?;
+
+        // only pledges that are in the "PROPOSED" state can be accepted
+        if !pledge.state.can_transition_to(&PledgeState::Accepted) {
+            return Err(ContractError::StateError {
+                error: format!(
+                    "Unable to accept pledges: Pledge {} is not in the 'proposed' state.",
+                    pledge.id
+                ),
+            });
+        }
+
+        // none of the proposals can be accepted after their acceptance deadline
+        require_unexpired(&pledge, &env)?;
+
+        // none of the assets can have been claimed by another pledge in the meantime
+        require_assets_still_proposed(deps.storage, &pledge)?;
+        pledges.push(pledge);
+    }
+
+    // ensure the contract has privs on the escrow marker
+    let escrow_marker = load_escrow_marker(
+        deps.as_ref(),
+        &env.contract.address,
+        &contract_info.facility.escrow_marker,
+    )?;
+
+    // the combined advance can't take the facility past its limit
+    let total_advance: Uint128 = pledges.iter().map(|pledge| pledge.total_advance).sum();
+    require_within_facility_limit(deps.storage, &contract_info.facility, total_advance)?;
+
+    // make sure that the warehouse sent the combined advance (in any of the facility denoms)
+    let denom = select_stablecoin_denom(&info, &contract_info.facility.stablecoin_denoms);
+    let surplus = require_funds(
+        &info,
+        &denom,
+        total_advance.into(),
+        RequiredFunds::PledgeAdvance,
+    )?;
+    let refund = refund_surplus(&info, &denom, surplus);
+
+    // messages to include in transaction
+    let mut messages = vec![
+        // forward stablecoin to escrow marker account
+        BankMsg::Send {
+            to_address: escrow_marker.address.to_string(),
+            amount: coins(total_advance.into(), &denom),
+        },
+    ];
+
+    // refund any overpayment (and any funds in other denoms) to the warehouse
+    if let Some(refund) = refund {
+        messages.push(refund);
+    }
+
+    // update the pledges, each of which records its own advance held in escrow
+    for pledge in pledges.iter_mut() {
+        pledge.state = PledgeState::Accepted;
+        pledge.accepted_at = Some(env.block.time);
+        pledge.advance_denom = Some(denom.clone());
+        save_pledge(deps.storage, pledge.id.as_bytes(), pledge)?;
+    }
+
+    let ids: Vec<String> = pledges.iter().map(|pledge| pledge.id.clone()).collect();
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "accept_pledges")
+        .add_attribute("pledge_ids", ids.join(","))
+        .set_data(to_binary(&pledges)?))
+}
+
+// Build the messages that return a proposed or accepted pledge's escrowed advance and assets.
+fn unwind_pledge(
+    deps: Deps,
+    env: &Env,
+    contract_info: &ContractInfo,
+    pledge: &Pledge,
+) -> Result<Vec<CosmosMsg<ProvenanceMsg>>, ContractError> {
+    let remove_assets_from_escrow = true;
+    let remove_advance_from_escrow = pledge.state == PledgeState::Accepted;
+
+    // ensure the contract has privs on the escrow marker
+    let escrow_marker = load_escrow_marker(
+        deps,
+        &env.contract.address,
+        &contract_info.facility.escrow_marker,
+    )?;
+
+    // messages to include in transaction
+    let mut messages: Vec<CosmosMsg<ProvenanceMsg>> = Vec::new();
+
+    // remove the advance from escrow back to the warehouse account
+    if remove_advance_from_escrow {
+        // withdraw advance funds from the escrow marker account to the warehouse
+        messages.push(withdraw_coins(
+            escrow_marker.denom,
+            pledge.total_advance.into(),
+            recorded_stablecoin_denom(&contract_info.facility, &pledge.advance_denom),
+            contract_info.facility.warehouse.clone(),
+        )?);
+    }
+
+    // remove the assets (asset marker) from escrow
     if remove_assets_from_escrow {
-        let asset_marker = querier.get_marker_by_denom(pledge.asset_marker_denom.clone())?;
+        messages.extend(remove_asset_marker(
+            deps,
+            contract_info,
+            &pledge.asset_marker_denom,
+        )?);
+    }
+
+    Ok(messages)
+}
 
+// Build the messages that return an asset marker to its supply, then cancel and destroy it.
+// NOTE: If the asset marker can't be found (ex: it was already destroyed by a partially
+//       applied cancel), there is nothing left to unwind so no messages are built. Any other
+//       failure to look up the marker is returned.
+fn remove_asset_marker(
+    deps: Deps,
+    contract_info: &ContractInfo,
+    asset_marker_denom: &str,
+) -> Result<Vec<CosmosMsg<ProvenanceMsg>>, ContractError> {
+    let asset_marker_address = match get_marker_address(&deps, asset_marker_denom) {
+        Ok(address) => address,
+        Err(ContractError::MarkerNotFound { .. }) => return Ok(vec![]),
+        Err(error) => return Err(error),
+    };
+
+    Ok(vec![
         // transfer the asset marker back to the marker supply
-        messages.push(transfer_marker_coins(
+        transfer_marker_coins(
             1,
-            pledge.asset_marker_denom.clone(),
-            asset_marker.address,
-            contract_info.facility.originator,
-        )?);
-
+            asset_marker_denom,
+            asset_marker_address,
+            contract_info.facility.originator.clone(),
+        )?,
         // cancel the asset marker
-        messages.push(cancel_marker(pledge.asset_marker_denom.clone())?);
-
+        cancel_marker(asset_marker_denom)?,
         // destroy the asset marker
-        messages.push(destroy_marker(pledge.asset_marker_denom.clone())?);
+        destroy_marker(asset_marker_denom)?,
+    ])
+}
+
+// Authorize a pledge cancellation against the state of the stored pledge: the warehouse can only
+// cancel a stale proposal, since its advance is escrowed once accepted.
+// NOTE: A pledge that doesn't exist is left for the message handler to report.
+fn authorize_cancel(
+    deps: Deps,
+    contract_info: &ContractInfo,
+    sender: &Addr,
+    id: &str,
+) -> Result<(), ContractError> {
+    if contract_info.facility.warehouse != *sender {
+        return Ok(());
+    }
+
+    if let Some(pledge) = may_load_pledge(deps.storage, id.as_bytes())? {
+        if pledge.state != PledgeState::Proposed {
+            return Err(ContractError::Unauthorized {});
+        }
+    }
+    Ok(())
+}
+
+fn cancel_pledge(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    contract_info: ContractInfo,
+    id: String,
+) -> Result<Response<ProvenanceMsg>, ContractError> {
+    // locate the pledge
+    let mut pledge = may_load_pledge(deps.storage, id.as_bytes())?
+        .ok_or(ContractError::PledgeNotFound { id })?;
+
+    // only pledges that are in the "PROPOSED" or "ACCEPTED" states can be cancelled
+    if !pledge.state.can_transition_to(&PledgeState::Cancelled) {
+        return Err(ContractError::StateError {
+            error: "Unable to cancel pledge: Pledge is not in the 'proposed' or 'accepted' state."
+                .into(),
+        });
     }
+    let messages = unwind_pledge(deps.as_ref(), &env, &contract_info, &pledge)?;
 
     // update the pledge
     pledge.state = PledgeState::Cancelled;
-    save_pledge(deps.storage, &pledge.id.as_bytes(), &pledge)?;
+    save_pledge(deps.storage, pledge.id.as_bytes(), &pledge)?;
 
     // remove the assets from the inventory
-    remove_assets(deps.storage, &pledge.assets)?;
+    remove_assets(deps.storage, &pledge.assets, env.block.time)?;
 
     Ok(Response::new()
         .add_messages(messages)
@@ -466,6 +1284,44 @@ fn cancel_pledge(
         .set_data(to_binary(&pledge)?))
 }
 
+fn reject_pledge(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    contract_info: ContractInfo,
+    id: String,
+    reason: Option<String>,
+) -> Result<Response<ProvenanceMsg>, ContractError> {
+    // locate the pledge
+    let mut pledge = may_load_pledge(deps.storage, id.as_bytes())?
+        .ok_or(ContractError::PledgeNotFound { id })?;
+
+    // only pledges that are in the "PROPOSED" state can be rejected
+    if !pledge.state.can_transition_to(&PledgeState::Rejected) {
+        return Err(ContractError::StateError {
+            error: "Unable to reject pledge: Pledge is not in the 'proposed' state.".into(),
+        });
+    }
+
+    let messages = unwind_pledge(deps.as_ref(), &env, &contract_info, &pledge)?;
+
+    // update the pledge
+    pledge.state = PledgeState::Rejected;
+    save_pledge(deps.storage, pledge.id.as_bytes(), &pledge)?;
+
+    // remove the assets from the inventory
+    remove_assets(deps.storage, &pledge.assets, env.block.time)?;
+
+    let mut response = Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "reject_pledge");
+    if let Some(reason) = reason {
+        response = response.add_attribute("reason", reason);
+    }
+
+    Ok(response.set_data(to_binary(&pledge)?))
+}
+
 fn execute_pledge(
     deps: DepsMut,
     env: Env,
@@ -474,27 +1330,33 @@ fn execute_pledge(
     id: String,
 ) -> Result<Response<ProvenanceMsg>, ContractError> {
     // locate the pledge
-    let mut pledge = load_pledge(deps.storage, id.as_bytes())?;
+    let mut pledge = may_load_pledge(deps.storage, id.as_bytes())?
+        .ok_or(ContractError::PledgeNotFound { id })?;
 
     // only pledges that are in the "ACCEPTED" state can be executed
-    if pledge.state != PledgeState::Accepted {
+    if !pledge.state.can_transition_to(&PledgeState::Executed) {
         return Err(ContractError::StateError {
             error: "Unable to execute pledge: Pledge is not in the 'accepted' state.".into(),
         });
     }
 
     // ensure the contract has privs on the escrow marker
-    let querier = ProvenanceQuerier::new(&deps.querier);
-    let escrow_marker =
-        querier.get_marker_by_address(contract_info.facility.escrow_marker.clone())?;
-    if !marker_has_grant(
-        escrow_marker.clone(),
-        AccessGrant {
-            address: env.contract.address,
-            permissions: vec![MarkerAccess::Transfer, MarkerAccess::Withdraw],
-        },
-    ) {
-        return Err(ContractError::MissingEscrowMarkerGrant {});
+    let escrow_marker = load_escrow_marker(
+        deps.as_ref(),
+        &env.contract.address,
+        &contract_info.facility.escrow_marker,
+    )?;
+
+    // ensure the escrow marker actually holds the advance before disbursing it
+    let advance_denom = recorded_stablecoin_denom(&contract_info.facility, &pledge.advance_denom);
+    let escrowed: Uint128 = escrow_marker
+        .coins
+        .iter()
+        .filter(|coin| coin.denom == advance_denom)
+        .map(|coin| coin.amount)
+        .sum();
+    if escrowed < pledge.total_advance {
+        return Err(ContractError::InsufficientEscrow {});
     }
 
     // messages to include in transaction
@@ -503,17 +1365,23 @@ fn execute_pledge(
         withdraw_coins(
             escrow_marker.denom,
             pledge.total_advance.into(),
-            contract_info.facility.stablecoin_denom.clone(),
+            advance_denom,
             contract_info.facility.originator,
         )?,
     ];
 
     // update the pledge
     pledge.state = PledgeState::Executed;
-    save_pledge(deps.storage, &pledge.id.as_bytes(), &pledge)?;
+    pledge.executed_at = Some(env.block.time);
+    save_pledge(deps.storage, pledge.id.as_bytes(), &pledge)?;
 
     // update the asset(s) state in the facility inventory
-    set_assets_state(deps.storage, AssetState::Inventory, &pledge.assets)?;
+    set_assets_state(
+        deps.storage,
+        AssetState::Inventory,
+        &pledge.assets,
+        env.block.time,
+    )?;
 
     Ok(Response::new()
         .add_messages(messages)
@@ -527,12 +1395,11 @@ fn propose_paydown(
     contract_info: ContractInfo,
     id: String,
     assets: Vec<String>,
-    total_paydown: u64,
+    total_paydown: Uint128,
 ) -> Result<Response<ProvenanceMsg>, ContractError> {
     // ensure that a paydown with the specified id doesn't already exist
-    let paydown = load_paydown(deps.storage, id.as_bytes());
-    if let Ok(v) = paydown {
-        return Err(ContractError::PaydownAlreadyExists { id: v.id });
+    if may_load_paydown(deps.storage, id.as_bytes())?.is_some() {
+        return Err(ContractError::PaydownAlreadyExists { id });
     }
 
     // ensure that the included assets are in the inventory
@@ -540,20 +1407,25 @@ fn propose_paydown(
         return Err(ContractError::AssetsNotInInventory {});
     }
 
-    // ensure the contract has privs on the escrow marker
-    let querier = ProvenanceQuerier::new(&deps.querier);
-    let escrow_marker =
-        querier.get_marker_by_address(contract_info.facility.escrow_marker.clone())?;
-    if !marker_has_grant(
-        escrow_marker.clone(),
-        AccessGrant {
-            address: env.contract.address,
-            permissions: vec![MarkerAccess::Transfer, MarkerAccess::Withdraw],
-        },
-    ) {
-        return Err(ContractError::MissingEscrowMarkerGrant {});
+    // ensure that the included assets all belong to the same pledge
+    let pledge_ids = find_pledge_ids_holding_assets(deps.storage, &assets)?;
+    if pledge_ids.is_empty() {
+        return Err(ContractError::PaydownAssetsNotPledged {});
+    }
+    if pledge_ids.len() > 1 {
+        return Err(ContractError::PaydownSpansMultiplePledges {});
     }
 
+    // ensure the contract has privs on the escrow marker
+    let escrow_marker = load_escrow_marker(
+        deps.as_ref(),
+        &env.contract.address,
+        &contract_info.facility.escrow_marker,
+    )?;
+
+    // the originator may pay down in any of the facility stablecoin denoms
+    let denom = select_stablecoin_denom(&info, &contract_info.facility.stablecoin_denoms);
+
     // create the paydown
     let paydown = Paydown {
         id,
@@ -563,59 +1435,56 @@ fn propose_paydown(
         state: PaydownState::Proposed,
         parties_accepted: vec![],
         sale_info: None,
+        proposed_at: env.block.time,
+        accepted_at: None,
+        executed_at: None,
+        paydown_denom: Some(denom.clone()),
+        purchase_denom: None,
+        pledge_id: Some(pledge_ids[0].clone()),
     };
 
-    // make sure that the originator sent the appropriate stablecoin
-    let paydown_funds = info
-        .funds
-        .get(0)
-        .ok_or(ContractError::MissingPaydownFunds {})?;
-    if (paydown_funds.denom != contract_info.facility.stablecoin_denom)
-        || (paydown_funds.amount != paydown.total_paydown.into())
-    {
-        return Err(ContractError::InsufficientPaydownFunds {
-            need: paydown.total_paydown.to_u128().unwrap(),
-            need_denom: contract_info.facility.stablecoin_denom,
-            received: paydown_funds.amount.u128(),
-            received_denom: paydown_funds.denom.clone(),
-        });
-    }
+    // make sure that the originator sent the paydown, which is escrowed until the paydown is
+    // executed (or returned to the originator if it's cancelled)
+    // NOTE: For a paydown and sale, the buyer's purchase funds are escrowed separately, when the
+    //       buyer accepts the paydown.
+    let surplus = require_funds(
+        &info,
+        &denom,
+        paydown.total_paydown.into(),
+        RequiredFunds::Paydown,
+    )?;
+    let refund = refund_surplus(&info, &denom, surplus);
 
     // messages to include in transaction
-    let messages = vec![
+    let mut messages = vec![
         // forward stablecoin to escrow marker account
         BankMsg::Send {
             to_address: escrow_marker.address.to_string(),
-            amount: coins(
-                paydown.total_paydown.into(),
-                contract_info.facility.stablecoin_denom,
-            ),
+            amount: coins(paydown.total_paydown.into(), &denom),
         },
     ];
 
+    // refund any overpayment (and any funds in other denoms) to the originator
+    if let Some(refund) = refund {
+        messages.push(refund);
+    }
+
     // save the paydown
-    save_paydown(deps.storage, &paydown.id.as_bytes(), &paydown)?;
+    save_paydown(deps.storage, paydown.id.as_bytes(), &paydown)?;
 
     // update the asset(s) state in the facility inventory
-    set_assets_state(deps.storage, AssetState::PaydownProposed, &paydown.assets)?;
-
-    // get the pledges affected by this paydown
-    let affected_pledges = find_pledge_ids_with_assets(
+    set_assets_state(
         deps.storage,
-        paydown.assets,
-        Some(PledgeState::Executed),
-        None,
-        None,
+        AssetState::PaydownProposed,
+        &paydown.assets,
+        env.block.time,
     )?;
 
-    // TODO: Anything else to do at this state? How do we handle the asset marker(s) (assets being payed down
-    //       can come from multiple pledges). CoNfUsEd!
-
     Ok(Response::new()
         .add_messages(messages)
         .add_attributes(vec![
             attr("action", "propose_paydown"),
-            attr("affected_pledges", affected_pledges.join(",")),
+            attr("affected_pledges", pledge_ids.join(",")),
         ]))
 }
 
@@ -627,14 +1496,13 @@ fn propose_paydown_and_sell(
     contract_info: ContractInfo,
     id: String,
     assets: Vec<String>,
-    total_paydown: u64,
+    total_paydown: Uint128,
     buyer: Addr,
     purchase_price: u64,
 ) -> Result<Response<ProvenanceMsg>, ContractError> {
     // ensure that a paydown with the specified id doesn't already exist
-    let paydown = load_paydown(deps.storage, id.as_bytes());
-    if let Ok(v) = paydown {
-        return Err(ContractError::PaydownAlreadyExists { id: v.id });
+    if may_load_paydown(deps.storage, id.as_bytes())?.is_some() {
+        return Err(ContractError::PaydownAlreadyExists { id });
     }
 
     // ensure that the included assets are in the inventory
@@ -642,19 +1510,24 @@ fn propose_paydown_and_sell(
         return Err(ContractError::AssetsNotInInventory {});
     }
 
-    // ensure the contract has privs on the escrow marker
-    let querier = ProvenanceQuerier::new(&deps.querier);
-    let escrow_marker =
-        querier.get_marker_by_address(contract_info.facility.escrow_marker.clone())?;
-    if !marker_has_grant(
-        escrow_marker.clone(),
-        AccessGrant {
-            address: env.contract.address,
-            permissions: vec![MarkerAccess::Transfer, MarkerAccess::Withdraw],
-        },
-    ) {
-        return Err(ContractError::MissingEscrowMarkerGrant {});
+    // ensure that the included assets all belong to the same pledge
+    let pledge_ids = find_pledge_ids_holding_assets(deps.storage, &assets)?;
+    if pledge_ids.is_empty() {
+        return Err(ContractError::PaydownAssetsNotPledged {});
     }
+    if pledge_ids.len() > 1 {
+        return Err(ContractError::PaydownSpansMultiplePledges {});
+    }
+
+    // ensure the contract has privs on the escrow marker
+    let escrow_marker = load_escrow_marker(
+        deps.as_ref(),
+        &env.contract.address,
+        &contract_info.facility.escrow_marker,
+    )?;
+
+    // the originator may pay down in any of the facility stablecoin denoms
+    let denom = select_stablecoin_denom(&info, &contract_info.facility.stablecoin_denoms);
 
     // create the paydown
     let paydown = Paydown {
@@ -668,59 +1541,56 @@ fn propose_paydown_and_sell(
             buyer,
             price: purchase_price,
         }),
+        proposed_at: env.block.time,
+        accepted_at: None,
+        executed_at: None,
+        paydown_denom: Some(denom.clone()),
+        purchase_denom: None,
+        pledge_id: Some(pledge_ids[0].clone()),
     };
 
-    // make sure that the originator sent the appropriate stablecoin
-    let paydown_funds = info
-        .funds
-        .get(0)
-        .ok_or(ContractError::MissingPaydownFunds {})?;
-    if (paydown_funds.denom != contract_info.facility.stablecoin_denom)
-        || (paydown_funds.amount != paydown.total_paydown.into())
-    {
-        return Err(ContractError::InsufficientPaydownFunds {
-            need: paydown.total_paydown.to_u128().unwrap(),
-            need_denom: contract_info.facility.stablecoin_denom,
-            received: paydown_funds.amount.u128(),
-            received_denom: paydown_funds.denom.clone(),
-        });
-    }
+    // make sure that the originator sent the paydown, which is escrowed until the paydown is
+    // executed (or returned to the originator if it's cancelled)
+    // NOTE: For a paydown and sale, the buyer's purchase funds are escrowed separately, when the
+    //       buyer accepts the paydown.
+    let surplus = require_funds(
+        &info,
+        &denom,
+        paydown.total_paydown.into(),
+        RequiredFunds::Paydown,
+    )?;
+    let refund = refund_surplus(&info, &denom, surplus);
 
     // messages to include in transaction
-    let messages = vec![
+    let mut messages = vec![
         // forward stablecoin to escrow marker account
         BankMsg::Send {
             to_address: escrow_marker.address.to_string(),
-            amount: coins(
-                paydown.total_paydown.into(),
-                contract_info.facility.stablecoin_denom,
-            ),
+            amount: coins(paydown.total_paydown.into(), &denom),
         },
     ];
 
+    // refund any overpayment (and any funds in other denoms) to the originator
+    if let Some(refund) = refund {
+        messages.push(refund);
+    }
+
     // save the paydown
-    save_paydown(deps.storage, &paydown.id.as_bytes(), &paydown)?;
+    save_paydown(deps.storage, paydown.id.as_bytes(), &paydown)?;
 
     // update the asset(s) state in the facility inventory
-    set_assets_state(deps.storage, AssetState::PaydownProposed, &paydown.assets)?;
-
-    // get the pledges affected by this paydown
-    let affected_pledges = find_pledge_ids_with_assets(
+    set_assets_state(
         deps.storage,
-        paydown.assets,
-        Some(PledgeState::Executed),
-        None,
-        None,
+        AssetState::PaydownProposed,
+        &paydown.assets,
+        env.block.time,
     )?;
 
-    // TODO: Anything else to do at this state? How do we handle the asset marker(s) (assets being payed down
-    //       can come from multiple pledges). CoNfUsEd!
-
     Ok(Response::new()
         .add_messages(messages)
         .add_attributes(vec![
             attr("action", "propose_paydown_and_sell"),
-            attr("affected_pledges", affected_pledges.join(",")),
+            attr("affected_pledges", pledge_ids.join(",")),
         ]))
 }
 
@@ -732,7 +1602,8 @@ fn accept_paydown(
     id: String,
 ) -> Result<Response<ProvenanceMsg>, ContractError> {
     // locate the paydown
-    let mut paydown = load_paydown(deps.storage, id.as_bytes())?;
+    let mut paydown = may_load_paydown(deps.storage, id.as_bytes())?
+        .ok_or(ContractError::PaydownNotFound { id })?;
 
     // extract the sale info
     let sale_info = paydown.sale_info.as_ref();
@@ -760,68 +1631,56 @@ fn accept_paydown(
     }
 
     // ensure that the accepting party hasn't already accepted
-    if paydown
-        .parties_accepted
-        .clone()
-        .into_iter()
-        .find(|x| x == &accepting_party)
-        != None
-    {
+    if paydown.parties_accepted.contains(&accepting_party) {
         return Err(ContractError::PaydownPartyAlreadyAccepted {
             party: accepting_party,
         });
     }
 
     // only paydowns that are in the "PROPOSED" state can be accepted
-    if paydown.state != PaydownState::Proposed {
+    if !paydown.state.can_transition_to(&PaydownState::Accepted) {
         return Err(ContractError::StateError {
             error: "Unable to accept paydown: Paydown is not in the 'proposed' state.".into(),
         });
     }
 
     // ensure the contract has privs on the escrow marker
-    let querier = ProvenanceQuerier::new(&deps.querier);
-    let escrow_marker =
-        querier.get_marker_by_address(contract_info.facility.escrow_marker.clone())?;
-    if !marker_has_grant(
-        escrow_marker.clone(),
-        AccessGrant {
-            address: env.contract.address,
-            permissions: vec![MarkerAccess::Transfer, MarkerAccess::Withdraw],
-        },
-    ) {
-        return Err(ContractError::MissingEscrowMarkerGrant {});
-    }
+    let escrow_marker = load_escrow_marker(
+        deps.as_ref(),
+        &env.contract.address,
+        &contract_info.facility.escrow_marker,
+    )?;
 
     let mut messages = vec![];
 
+    // only the buyer pays when accepting (the originator already escrowed the paydown when it was
+    // proposed, and the warehouse pays nothing to accept)
     if accepting_party == ContractParty::Buyer {
-        // make sure that the buyer sent the appropriate stablecoin
-        let paydown_funds = info
-            .funds
-            .get(0)
-            .ok_or(ContractError::MissingPurchaseFunds {})?;
-        if (paydown_funds.denom != contract_info.facility.stablecoin_denom)
-            || (paydown_funds.amount != sale_info.unwrap().price.into())
-        {
-            return Err(ContractError::InsufficientPurchaseFunds {
-                need: sale_info.unwrap().price.to_u128().unwrap(),
-                need_denom: contract_info.facility.stablecoin_denom,
-                received: paydown_funds.amount.u128(),
-                received_denom: paydown_funds.denom.clone(),
-            });
-        }
+        // make sure that the buyer sent the appropriate stablecoin (in any of the facility denoms)
+        let denom = select_stablecoin_denom(&info, &contract_info.facility.stablecoin_denoms);
+        let surplus = require_funds(
+            &info,
+            &denom,
+            sale_info.unwrap().price.into(),
+            RequiredFunds::Purchase,
+        )?;
+        let refund = refund_surplus(&info, &denom, surplus);
 
         // forward stablecoin to escrow marker account
         messages.push(
             BankMsg::Send {
                 to_address: escrow_marker.address.to_string(),
-                amount: coins(
-                    sale_info.unwrap().price.into(),
-                    contract_info.facility.stablecoin_denom,
-                ),
+                amount: coins(sale_info.unwrap().price.into(), &denom),
             },
         );
+
+        // refund any overpayment (and any funds in other denoms) to the buyer
+        if let Some(refund) = refund {
+            messages.push(refund);
+        }
+
+        // record the denom to disburse or return the purchase funds in
+        paydown.purchase_denom = Some(denom);
     }
 
     // update the paydown
@@ -831,6 +1690,7 @@ fn accept_paydown(
             // for regular paydowns, only the warehouse needs to accept
             if vec_contains(&paydown.parties_accepted, &[ContractParty::Warehouse]) {
                 paydown.state = PaydownState::Accepted;
+                paydown.accepted_at = Some(env.block.time);
             }
         }
 
@@ -841,10 +1701,21 @@ fn accept_paydown(
                 &[ContractParty::Warehouse, ContractParty::Buyer],
             ) {
                 paydown.state = PaydownState::Accepted;
+                paydown.accepted_at = Some(env.block.time);
             }
         }
     }
-    save_paydown(deps.storage, &paydown.id.as_bytes(), &paydown)?;
+    save_paydown(deps.storage, paydown.id.as_bytes(), &paydown)?;
+
+    // update the asset(s) state in the facility inventory once the paydown is accepted
+    if paydown.state == PaydownState::Accepted {
+        set_assets_state(
+            deps.storage,
+            AssetState::PaydownAccepted,
+            &paydown.assets,
+            env.block.time,
+        )?;
+    }
 
     Ok(Response::new()
         .add_messages(messages)
@@ -860,32 +1731,24 @@ fn cancel_paydown(
     id: String,
 ) -> Result<Response<ProvenanceMsg>, ContractError> {
     // locate the paydown
-    let mut paydown = load_paydown(deps.storage, id.as_bytes())?;
+    let mut paydown = may_load_paydown(deps.storage, id.as_bytes())?
+        .ok_or(ContractError::PaydownNotFound { id })?;
 
     // only paydowns that are in the "PROPOSED" or "ACCEPTED" states can be cancelled=
-    match paydown.state {
-        PaydownState::Proposed => {}
-        PaydownState::Accepted => {}
-        _ => return Err(ContractError::StateError {
+    if !paydown.state.can_transition_to(&PaydownState::Cancelled) {
+        return Err(ContractError::StateError {
             error:
                 "Unable to cancel paydown: Paydown is not in the 'proposed' or 'accepted' state."
                     .into(),
-        }),
+        });
     }
 
     // ensure the contract has privs on the escrow marker
-    let querier = ProvenanceQuerier::new(&deps.querier);
-    let escrow_marker =
-        querier.get_marker_by_address(contract_info.facility.escrow_marker.clone())?;
-    if !marker_has_grant(
-        escrow_marker.clone(),
-        AccessGrant {
-            address: env.contract.address,
-            permissions: vec![MarkerAccess::Transfer, MarkerAccess::Withdraw],
-        },
-    ) {
-        return Err(ContractError::MissingEscrowMarkerGrant {});
-    }
+    let escrow_marker = load_escrow_marker(
+        deps.as_ref(),
+        &env.contract.address,
+        &contract_info.facility.escrow_marker,
+    )?;
 
     // messages to include in transaction
     let mut messages = vec![
@@ -893,8 +1756,8 @@ fn cancel_paydown(
         withdraw_coins(
             escrow_marker.clone().denom,
             paydown.total_paydown.into(),
-            contract_info.facility.stablecoin_denom.clone(),
-            contract_info.facility.originator,
+            recorded_stablecoin_denom(&contract_info.facility, &paydown.paydown_denom),
+            contract_info.facility.originator.clone(),
         )?,
     ];
 
@@ -908,7 +1771,7 @@ fn cancel_paydown(
         messages.push(withdraw_coins(
             escrow_marker.denom,
             sale_info.unwrap().price.into(),
-            contract_info.facility.stablecoin_denom,
+            recorded_stablecoin_denom(&contract_info.facility, &paydown.purchase_denom),
             sale_info.unwrap().clone().buyer,
         )?);
     }
@@ -917,10 +1780,15 @@ fn cancel_paydown(
 
     // update the paydown
     paydown.state = PaydownState::Cancelled;
-    save_paydown(deps.storage, &paydown.id.as_bytes(), &paydown)?;
+    save_paydown(deps.storage, paydown.id.as_bytes(), &paydown)?;
 
     // update the asset(s) state in the facility inventory
-    set_assets_state(deps.storage, AssetState::Inventory, &paydown.assets)?;
+    set_assets_state(
+        deps.storage,
+        AssetState::Inventory,
+        &paydown.assets,
+        env.block.time,
+    )?;
 
     Ok(Response::new()
         .add_messages(messages)
@@ -936,28 +1804,22 @@ fn execute_paydown(
     id: String,
 ) -> Result<Response<ProvenanceMsg>, ContractError> {
     // locate the paydown
-    let mut paydown = load_paydown(deps.storage, id.as_bytes())?;
+    let mut paydown = may_load_paydown(deps.storage, id.as_bytes())?
+        .ok_or(ContractError::PaydownNotFound { id })?;
 
     // only paydowns that are in the "ACCEPTED" state can be executed
-    if paydown.state != PaydownState::Accepted {
+    if !paydown.state.can_transition_to(&PaydownState::Executed) {
         return Err(ContractError::StateError {
             error: "Unable to execute paydown: Paydown is not in the 'accepted' state.".into(),
         });
     }
 
     // ensure the contract has privs on the escrow marker
-    let querier = ProvenanceQuerier::new(&deps.querier);
-    let escrow_marker =
-        querier.get_marker_by_address(contract_info.facility.escrow_marker.clone())?;
-    if !marker_has_grant(
-        escrow_marker.clone(),
-        AccessGrant {
-            address: env.contract.address,
-            permissions: vec![MarkerAccess::Transfer, MarkerAccess::Withdraw],
-        },
-    ) {
-        return Err(ContractError::MissingEscrowMarkerGrant {});
-    }
+    let escrow_marker = load_escrow_marker(
+        deps.as_ref(),
+        &env.contract.address,
+        &contract_info.facility.escrow_marker,
+    )?;
 
     // messages to include in transaction
     let mut messages = vec![
@@ -965,8 +1827,8 @@ fn execute_paydown(
         withdraw_coins(
             escrow_marker.clone().denom,
             paydown.total_paydown.into(),
-            contract_info.facility.stablecoin_denom.clone(),
-            contract_info.facility.warehouse,
+            recorded_stablecoin_denom(&contract_info.facility, &paydown.paydown_denom),
+            contract_info.facility.warehouse.clone(),
         )?,
     ];
 
@@ -975,7 +1837,7 @@ fn execute_paydown(
         messages.push(withdraw_coins(
             escrow_marker.denom,
             paydown.sale_info.as_ref().unwrap().price.into(),
-            contract_info.facility.stablecoin_denom.clone(),
+            recorded_stablecoin_denom(&contract_info.facility, &paydown.purchase_denom),
             contract_info.facility.originator.clone(),
         )?);
     }
@@ -984,10 +1846,11 @@ fn execute_paydown(
 
     // update the paydown
     paydown.state = PaydownState::Executed;
-    save_paydown(deps.storage, &paydown.id.as_bytes(), &paydown)?;
+    paydown.executed_at = Some(env.block.time);
+    save_paydown(deps.storage, paydown.id.as_bytes(), &paydown)?;
 
     // remove the assets from the facility inventory
-    remove_assets(deps.storage, &paydown.assets)?;
+    remove_assets(deps.storage, &paydown.assets, env.block.time)?;
 
     // get the current inventory
     let inventory = list_inventory(deps.storage)?;
@@ -995,49 +1858,48 @@ fn execute_paydown(
     // get the pledges affected by this paydown
     let affected_pledges = find_pledge_ids_with_assets(
         deps.storage,
-        paydown.assets,
+        &paydown.assets,
         Some(PledgeState::Executed),
         None,
         None,
     )?;
 
-    // get the pledges that are closed by this paydown
-    let closed_pledges: Vec<String> = affected_pledges
+    // get the pledges that are closed by this paydown (none of their assets remain in inventory)
+    let mut closed_pledges: Vec<Pledge> = vec![];
+    for pledge_id in &affected_pledges {
+        let pledge = may_load_pledge(deps.storage, pledge_id.as_bytes())?.ok_or(
+            ContractError::PledgeNotFound {
+                id: pledge_id.clone(),
+            },
+        )?;
+        if !vec_has_any(&inventory, &pledge.assets) {
+            closed_pledges.push(pledge);
+        }
+    }
+    let closed_pledge_ids: Vec<String> = closed_pledges
         .iter()
-        .filter(|id| {
-            !vec_has_any(
-                &inventory,
-                &load_pledge(deps.storage, id.as_bytes()).unwrap().assets,
-            )
-        })
-        .map(String::from)
+        .map(|pledge| pledge.id.clone())
         .collect();
 
     // update the state on the closed pledges
-    for pledge_id in &closed_pledges {
-        // load the pledge
-        let mut pledge = get_pledge(deps.storage, String::from(pledge_id))?;
+    for mut pledge in closed_pledges {
+        // only pledges that are in the "EXECUTED" state can be closed
+        if !pledge.state.can_transition_to(&PledgeState::Closed) {
+            return Err(ContractError::StateError {
+                error: "Unable to close pledge: Pledge is not in the 'executed' state.".into(),
+            });
+        }
 
-        // get the asset marker for the pledge
-        let asset_marker = querier.get_marker_by_denom(pledge.asset_marker_denom.clone())?;
+        // remove the asset marker
+        messages.extend(remove_asset_marker(
+            deps.as_ref(),
+            &contract_info,
+            &pledge.asset_marker_denom,
+        )?);
 
         // update the pledge
         pledge.state = PledgeState::Closed;
-        save_pledge(deps.storage, &pledge.id.as_bytes(), &pledge)?;
-
-        // transfer the asset marker back to the marker supply
-        messages.push(transfer_marker_coins(
-            1,
-            pledge.asset_marker_denom.clone(),
-            asset_marker.address,
-            contract_info.facility.originator.clone(),
-        )?);
-
-        // cancel the asset marker
-        messages.push(cancel_marker(pledge.asset_marker_denom.clone())?);
-
-        // destroy the asset marker
-        messages.push(destroy_marker(pledge.asset_marker_denom.clone())?);
+        save_pledge(deps.storage, pledge.id.as_bytes(), &pledge)?;
     }
 
     Ok(Response::new()
@@ -1045,89 +1907,5424 @@ fn execute_paydown(
         .add_attributes(vec![
             attr("action", "execute_paydown"),
             attr("affected_pledges", affected_pledges.join(",")),
-            attr("closed_pledges", closed_pledges.join(",")),
+            attr("closed_pledges", closed_pledge_ids.join(",")),
         ]))
 }
 
+// Close an executed pledge whose advance was repaid outside of the facility (ex: by wire transfer),
+// recording the external reference of the repayment.
+// NOTE: No funds are moved by the contract. The remaining assets are removed from the inventory
+//       and the asset marker is destroyed, as for a pledge closed by a paydown.
+fn record_repayment(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    contract_info: ContractInfo,
+    pledge_id: String,
+    reference: String,
+) -> Result<Response<ProvenanceMsg>, ContractError> {
+    // locate the pledge
+    let mut pledge = may_load_pledge(deps.storage, pledge_id.as_bytes())?
+        .ok_or(ContractError::PledgeNotFound { id: pledge_id })?;
+
+    // only pledges that are in the "EXECUTED" state can be closed
+    if !pledge.state.can_transition_to(&PledgeState::Closed) {
+        return Err(ContractError::StateError {
+            error: "Unable to record repayment: Pledge is not in the 'executed' state.".into(),
+        });
+    }
+
+    // only the assets still held for the pledge are closed with it, since an asset paid down from
+    // the pledge may since have been pledged again
+    let assets = assets_held_for_pledge(deps.storage, &pledge)?;
+
+    // the pledge can't be closed while any of its assets are in a pending paydown
+    if any_assets_in_inventory(deps.storage, Some(AssetState::PaydownProposed), &assets)
+        || any_assets_in_inventory(deps.storage, Some(AssetState::PaydownAccepted), &assets)
+    {
+        return Err(ContractError::StateError {
+            error: "Unable to record repayment: Pledge assets are in a pending paydown.".into(),
+        });
+    }
+
+    let messages = remove_asset_marker(deps.as_ref(), &contract_info, &pledge.asset_marker_denom)?;
+
+    // update the pledge
+    pledge.state = PledgeState::Closed;
+    pledge.repayment_reference = Some(reference.clone());
+    save_pledge(deps.storage, pledge.id.as_bytes(), &pledge)?;
+
+    // remove the remaining assets from the inventory
+    remove_assets(deps.storage, &assets, env.block.time)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "record_repayment"),
+            attr("pledge_id", pledge.id.clone()),
+            attr("reference", reference),
+        ])
+        .set_data(to_binary(&pledge)?))
+}
+
+// Get the role the address plays in the facility, after validating the address.
+fn get_party_role(deps: Deps, address: String) -> StdResult<PartyRoleResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let contract_info = get_contract_info(deps.storage)?;
+    Ok(PartyRoleResponse {
+        role: contract_info.party(&address).into(),
+    })
+}
+
 fn get_facility_info(store: &dyn Storage) -> StdResult<Facility> {
     let contract_info = get_contract_info(store)?;
     Ok(contract_info.facility)
 }
 
+fn get_facility_summary(store: &dyn Storage) -> StdResult<FacilitySummary> {
+    let total_outstanding_advance = Uint128::new(outstanding_advance(store)?);
+    let open_pledge_proposals = get_pledge_ids(
+        store,
+        Some(PledgeState::Proposed),
+        None,
+        None,
+        Order::Ascending,
+    )?
+    .len();
+    let open_paydown_proposals = get_paydown_ids(
+        store,
+        Some(PaydownState::Proposed),
+        None,
+        None,
+        Order::Ascending,
+    )?
+    .len();
+
+    Ok(FacilitySummary {
+        facility: get_facility_info(store)?,
+        inventory_count: list_inventory(store)?.len() as u64,
+        total_outstanding_advance,
+        open_proposal_count: (open_pledge_proposals + open_paydown_proposals) as u64,
+    })
+}
+
+fn get_marker_distribution(store: &dyn Storage) -> StdResult<MarkerDistribution> {
+    let contract_info = get_contract_info(store)?;
+    Ok(calculate_marker_distribution(&contract_info.facility)?)
+}
+
+fn get_simulated_pledge_advance(
+    store: &dyn Storage,
+    asset_value: u64,
+) -> StdResult<SimulatePledgeAdvanceResponse> {
+    let contract_info = get_contract_info(store)?;
+    Ok(simulate_pledge_advance(
+        &contract_info.facility,
+        asset_value,
+    )?)
+}
+
 fn get_pledge(store: &dyn Storage, id: String) -> StdResult<Pledge> {
     load_pledge(store, id.as_bytes())
 }
 
-fn list_pledge_ids(store: &dyn Storage) -> StdResult<Vec<String>> {
-    get_pledge_ids(store, None, None, None)
+fn get_pledge_state(store: &dyn Storage, id: String) -> StdResult<PledgeStateResponse> {
+    let state = load_pledge_state(store, id.as_bytes())?;
+    Ok(PledgeStateResponse { id, state })
+}
+
+fn get_pledge_raw(store: &dyn Storage, id: String) -> StdResult<Binary> {
+    Ok(Binary(load_pledge_raw(store, id.as_bytes())?))
+}
+
+// Get the live pledge that includes the asset, comparing the canonical scope UUIDs of the
+// specified and stored assets, so that either form of an asset matches the other.
+fn get_pledge_by_asset(store: &dyn Storage, asset: String) -> StdResult<Pledge> {
+    let key = asset_key(&asset);
+    get_pledges_by_states(
+        store,
+        vec![
+            PledgeState::Proposed,
+            PledgeState::Accepted,
+            PledgeState::Executed,
+        ],
+        None,
+        None,
+    )?
+    .into_iter()
+    .find(|pledge| {
+        pledge
+            .assets
+            .iter()
+            .any(|pledged| asset_key(pledged) == key)
+    })
+    .ok_or_else(|| StdError::not_found(format!("Pledge with asset {}", asset)))
+}
+
+// Find the pledges that include any of the assets (specified as scope UUIDs or bech32 scope
+// addresses), so that reconciliation doesn't need to list every pledge.
+fn find_pledges_by_assets(
+    store: &dyn Storage,
+    assets: Vec<String>,
+    state: Option<PledgeState>,
+) -> StdResult<Vec<Pledge>> {
+    let assets: Vec<String> = assets.iter().map(|asset| asset_key(asset)).collect();
+    find_pledges_with_assets(store, &assets, state, None, None)
+}
+
+// Get the facility credit limit (if any), the advance outstanding against it and the remaining
+// credit available.
+fn get_available_credit(store: &dyn Storage) -> StdResult<AvailableCreditResponse> {
+    let limit = get_contract_info(store)?.facility.max_total_advance;
+    let outstanding = Uint128::new(outstanding_advance(store)?);
+    Ok(AvailableCreditResponse {
+        limit,
+        outstanding,
+        available: limit.map(|limit| limit.saturating_sub(outstanding)),
+    })
+}
+
+// Get the storage iteration order for a list query (ascending by default).
+fn list_order(descending: Option<bool>) -> Order {
+    match descending {
+        Some(true) => Order::Descending,
+        _ => Order::Ascending,
+    }
+}
+
+fn list_pledge_ids(store: &dyn Storage, descending: Option<bool>) -> StdResult<ListIdsResponse> {
+    let ids = get_pledge_ids(store, None, None, None, list_order(descending))?;
+    Ok(ListIdsResponse {
+        total: ids.len() as u64,
+        ids,
+    })
+}
+
+// List a page of pledges, capped so the response stays within the query size limit.
+fn list_pledges(
+    store: &dyn Storage,
+    descending: Option<bool>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListPledgesResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_LIST_PLEDGES_LIMIT)
+        .min(MAX_LIST_PLEDGES_LIMIT) as usize;
+    let (pledges, next_start_after) =
+        get_pledges_page(store, None, start_after, limit, list_order(descending))?;
+    Ok(ListPledgesResponse {
+        pledges,
+        next_start_after,
+    })
+}
+
+// List a page of closed pledges, each joined with the executed paydown(s) that closed it.
+fn list_closed_pledges(
+    store: &dyn Storage,
+    descending: Option<bool>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListClosedPledgesResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_LIST_PLEDGES_LIMIT)
+        .min(MAX_LIST_PLEDGES_LIMIT) as usize;
+    let (pledges, next_start_after) = get_pledges_page(
+        store,
+        Some(PledgeState::Closed),
+        start_after,
+        limit,
+        list_order(descending),
+    )?;
+    let pledges = pledges
+        .into_iter()
+        .map(|pledge| {
+            let paydowns = find_paydowns_for_pledge(store, &pledge, Some(PaydownState::Executed))?;
+            Ok(ClosedPledge { pledge, paydowns })
+        })
+        .collect::<StdResult<Vec<ClosedPledge>>>()?;
+    Ok(ListClosedPledgesResponse {
+        pledges,
+        next_start_after,
+    })
 }
 
-fn list_pledges(store: &dyn Storage) -> StdResult<Vec<Pledge>> {
-    get_pledges(store, None, None, None)
+fn list_pledge_proposals(store: &dyn Storage, descending: Option<bool>) -> StdResult<Vec<Pledge>> {
+    get_pledges(
+        store,
+        Some(PledgeState::Proposed),
+        None,
+        None,
+        list_order(descending),
+    )
+}
+
+fn list_pledges_by_states(store: &dyn Storage, states: Vec<PledgeState>) -> StdResult<Vec<Pledge>> {
+    get_pledges_by_states(store, states, None, None)
+}
+
+// List all pledges along with the facility parties (ex: for reporting tools).
+fn get_pledges_with_context(store: &dyn Storage) -> StdResult<PledgesWithContextResponse> {
+    let contract_info = get_contract_info(store)?;
+    let pledges = get_pledges(store, None, None, None, Order::Ascending)?;
+    Ok(PledgesWithContextResponse {
+        originator: contract_info.facility.originator,
+        warehouse: contract_info.facility.warehouse,
+        pledges,
+    })
+}
+
+// List the accepted pledges, which have their advance held in escrow until executed.
+fn list_escrowed_pledges(store: &dyn Storage) -> StdResult<EscrowedPledgesResponse> {
+    let pledges = get_pledges(
+        store,
+        Some(PledgeState::Accepted),
+        None,
+        None,
+        Order::Ascending,
+    )?;
+    let total_escrowed = pledges.iter().map(|pledge| pledge.total_advance).sum();
+    Ok(EscrowedPledgesResponse {
+        pledges,
+        total_escrowed,
+    })
 }
 
-fn list_pledge_proposals(store: &dyn Storage) -> StdResult<Vec<Pledge>> {
-    get_pledges(store, Some(PledgeState::Proposed), None, None)
+// Get the stablecoin balance held by the contract for each stablecoin denom, alongside the advance
+// escrowed for accepted pledges in the denom, so that a mismatch is visible.
+fn get_escrow_balance(deps: Deps, env: Env) -> StdResult<EscrowBalanceResponse> {
+    let contract_info = get_contract_info(deps.storage)?;
+    let pledges = get_pledges(
+        deps.storage,
+        Some(PledgeState::Accepted),
+        None,
+        None,
+        Order::Ascending,
+    )?;
+
+    // the escrowed advances are forwarded to the escrow marker account, which holds the balance
+    let escrow_marker = load_escrow_marker(
+        deps,
+        &env.contract.address,
+        &contract_info.facility.escrow_marker,
+    )?;
+
+    let balances = contract_info
+        .facility
+        .stablecoin_denoms
+        .iter()
+        .map(|denom| {
+            let balance = escrow_marker
+                .coins
+                .iter()
+                .filter(|coin| coin.denom == *denom)
+                .map(|coin| coin.amount)
+                .sum();
+            let total_escrowed = pledges
+                .iter()
+                .filter(|pledge| {
+                    recorded_stablecoin_denom(&contract_info.facility, &pledge.advance_denom)
+                        == *denom
+                })
+                .map(|pledge| pledge.total_advance)
+                .sum();
+            EscrowBalance {
+                denom: denom.clone(),
+                balance,
+                total_escrowed,
+            }
+        })
+        .collect();
+
+    Ok(EscrowBalanceResponse { balances })
 }
 
-fn list_paydown_ids(store: &dyn Storage) -> StdResult<Vec<String>> {
-    get_paydown_ids(store, None, None, None)
+fn list_paydown_ids(store: &dyn Storage, descending: Option<bool>) -> StdResult<ListIdsResponse> {
+    let ids = get_paydown_ids(store, None, None, None, list_order(descending))?;
+    Ok(ListIdsResponse {
+        total: ids.len() as u64,
+        ids,
+    })
 }
 
-fn list_paydowns(store: &dyn Storage) -> StdResult<Vec<Paydown>> {
-    get_paydowns(store, None, None, None)
+fn list_paydowns(store: &dyn Storage, descending: Option<bool>) -> StdResult<Vec<Paydown>> {
+    get_paydowns(store, None, None, None, list_order(descending))
 }
 
-fn list_paydown_proposals(store: &dyn Storage) -> StdResult<Vec<Paydown>> {
-    get_paydowns(store, Some(PaydownState::Proposed), None, None)
+fn list_paydown_proposals(
+    store: &dyn Storage,
+    descending: Option<bool>,
+) -> StdResult<Vec<Paydown>> {
+    get_paydowns(
+        store,
+        Some(PaydownState::Proposed),
+        None,
+        None,
+        list_order(descending),
+    )
 }
 
 fn get_paydown(store: &dyn Storage, id: String) -> StdResult<Paydown> {
     load_paydown(store, id.as_bytes())
 }
 
+fn get_paydown_state(store: &dyn Storage, id: String) -> StdResult<PaydownStateResponse> {
+    let state = load_paydown_state(store, id.as_bytes())?;
+    Ok(PaydownStateResponse { id, state })
+}
+
 fn list_assets(store: &dyn Storage) -> StdResult<Vec<Asset>> {
     get_assets(store, None, None, None)
 }
 
+fn list_assets_by_state(store: &dyn Storage, state: AssetState) -> StdResult<Vec<Asset>> {
+    get_assets(store, Some(state), None, None)
+}
+
+fn get_asset_history(store: &dyn Storage, id: String) -> StdResult<Vec<AssetTransition>> {
+    load_asset_history(store, id.as_bytes())
+}
+
 // Get a list of the assets ids in the inventory.
-// NOTE: An asset proposed for paydown is still technically in the inventory, so we include
-// them in the filter.
+// NOTE: An asset proposed for (or accepted for) paydown is still technically in the inventory
+// until the paydown is executed, so we include them in the filter.
 fn list_inventory(store: &dyn Storage) -> StdResult<Vec<String>> {
     get_asset_ids_by_filter(
         store,
-        vec![AssetState::Inventory, AssetState::PaydownProposed],
+        vec![
+            AssetState::Inventory,
+            AssetState::PaydownProposed,
+            AssetState::PaydownAccepted,
+        ],
         None,
         None,
     )
 }
 
-// smart contract query entrypoint
-#[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetContractInfo {} => to_binary(&get_contract_info(deps.storage)?),
-        QueryMsg::GetFacilityInfo {} => to_binary(&get_facility_info(deps.storage)?),
-        QueryMsg::GetPaydown { id } => to_binary(&get_paydown(deps.storage, id)?),
-        QueryMsg::GetPledge { id } => to_binary(&get_pledge(deps.storage, id)?),
-        QueryMsg::ListAssets {} => to_binary(&list_assets(deps.storage)?),
-        QueryMsg::ListInventory {} => to_binary(&list_inventory(deps.storage)?),
-        QueryMsg::ListPledgeIds {} => to_binary(&list_pledge_ids(deps.storage)?),
-        QueryMsg::ListPledgeProposals {} => to_binary(&list_pledge_proposals(deps.storage)?),
-        QueryMsg::ListPledges {} => to_binary(&list_pledges(deps.storage)?),
-        QueryMsg::ListPaydownIds {} => to_binary(&list_paydown_ids(deps.storage)?),
-        QueryMsg::ListPaydownProposals {} => to_binary(&list_paydown_proposals(deps.storage)?),
-        QueryMsg::ListPaydowns {} => to_binary(&list_paydowns(deps.storage)?),
-    }
+// List the distinct scopes of every asset the facility has tracked (the current assets and the
+// assets of all pledges and paydowns, whatever their state) as sorted bech32 scope addresses.
+// NOTE: Asset ids that aren't scopes (ex: ids stored before assets were normalized) are skipped.
+fn list_all_scopes(store: &dyn Storage) -> StdResult<Vec<String>> {
+    let pledges = get_pledges(store, None, None, None, Order::Ascending)?;
+    let paydowns = get_paydowns(store, None, None, None, Order::Ascending)?;
+    let mut scopes = get_asset_ids(store, None, None, None)?
+        .into_iter()
+        .chain(pledges.into_iter().flat_map(|pledge| pledge.assets))
+        .chain(paydowns.into_iter().flat_map(|paydown| paydown.assets))
+        .filter_map(|asset| normalize_asset_id(&asset).ok())
+        .filter_map(|id| Uuid::parse_str(&id).ok())
+        .map(|uuid| MetadataAddress::for_scope(uuid).to_string())
+        .collect::<Vec<String>>();
+    scopes.sort();
+    scopes.dedup();
+    Ok(scopes)
 }
 
-// smart contract migrate/upgrade entrypoint
-#[entry_point]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    // always update version info
-    let mut contract_info = get_contract_info(deps.storage)?;
-    contract_info.version = CONTRACT_VERSION.into();
+fn update_binding(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    mut contract_info: ContractInfo,
+    name: String,
+) -> Result<Response<ProvenanceMsg>, ContractError> {
+    // messages to include in transaction
+    let messages = vec![
+        // remove the existing name binding
+        unbind_name(contract_info.bind_name.clone())?,
+        // bind the new name to the contract
+        bind_name(name.clone(), env.contract.address, NameBinding::Restricted)?,
+    ];
+
+    // update the contract info
+    contract_info.bind_name = name;
     set_contract_info(deps.storage, &contract_info)?;
 
-    Ok(Response::default())
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "update_binding")
+        .add_attribute("bind_name", contract_info.bind_name))
+}
+
+// Pause or resume new proposals, while the existing pledges and paydowns can still be settled.
+fn set_facility_paused(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _contract_info: ContractInfo,
+    paused: bool,
+) -> Result<Response<ProvenanceMsg>, ContractError> {
+    // the pause is held by governance while the facility is frozen
+    if is_frozen(deps.storage)? {
+        return Err(ContractError::FacilityFrozen {});
+    }
+
+    set_paused(deps.storage, paused)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_paused")
+        .add_attribute("paused", paused.to_string()))
+}
+
+// Remove the asset markers left behind by cancelled or rejected pledges (ex: when the marker
+// couldn't be found while the pledge was unwound), skipping the markers that no longer exist.
+fn settle_cancelled_markers(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    contract_info: ContractInfo,
+) -> Result<Response<ProvenanceMsg>, ContractError> {
+    let pledges = get_pledges_by_states(
+        deps.storage,
+        vec![PledgeState::Cancelled, PledgeState::Rejected],
+        None,
+        None,
+    )?;
+
+    // messages to include in transaction
+    let mut messages: Vec<CosmosMsg<ProvenanceMsg>> = Vec::new();
+    let mut settled_pledge_ids: Vec<String> = Vec::new();
+    for pledge in pledges {
+        let marker_messages =
+            remove_asset_marker(deps.as_ref(), &contract_info, &pledge.asset_marker_denom)?;
+        if !marker_messages.is_empty() {
+            messages.extend(marker_messages);
+            settled_pledge_ids.push(pledge.id);
+        }
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "settle_cancelled_markers"),
+        attr("settled_pledges", settled_pledge_ids.join(",")),
+    ]))
+}
+
+// Transfer the originator role to a new address, so that all further originator messages must be
+// sent by it.
+// NOTE: Asset markers and funds already withdrawn to the previous originator are not moved, the
+//       previous originator is expected to transfer them as part of the sale of its loan book.
+fn transfer_originator(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    mut contract_info: ContractInfo,
+    new_originator: String,
+) -> Result<Response<ProvenanceMsg>, ContractError> {
+    let new_originator = deps
+        .api
+        .addr_validate(&new_originator)
+        .map_err(|_| ContractError::invalid_field("new_originator", "must be a valid address"))?;
+
+    // one address can't act on both sides of the facility's pledges
+    if new_originator == contract_info.facility.warehouse {
+        return Err(ContractError::invalid_field(
+            "new_originator",
+            "must not be the warehouse",
+        ));
+    }
+
+    let previous_originator = contract_info.facility.originator.clone();
+    contract_info.facility.originator = new_originator;
+    set_contract_info(deps.storage, &contract_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "transfer_originator"),
+        attr("previous_originator", previous_originator),
+        attr("new_originator", contract_info.facility.originator),
+    ]))
+}
+
+// Close the facility, cancelling and destroying the facility marker.
+// NOTE: Only a facility without live (proposed, accepted or executed) pledges can be closed,
+//       so there are no escrowed advances or asset markers left to unwind.
+fn close_facility(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    contract_info: ContractInfo,
+) -> Result<Response<ProvenanceMsg>, ContractError> {
+    let live_pledges = get_pledge_ids_by_states(
+        deps.storage,
+        vec![
+            PledgeState::Proposed,
+            PledgeState::Accepted,
+            PledgeState::Executed,
+        ],
+        None,
+        None,
+    )?;
+    if !live_pledges.is_empty() {
+        return Err(ContractError::StateError {
+            error: format!(
+                "Unable to close facility: Pledges are still live: {}",
+                live_pledges.join(",")
+            ),
+        });
+    }
+
+    // messages to include in transaction
+    let messages = vec![
+        // cancel the facility marker
+        cancel_marker(contract_info.facility.marker_denom.clone())?,
+        // destroy the facility marker
+        destroy_marker(contract_info.facility.marker_denom.clone())?,
+    ];
+
+    // block all further activity on the facility
+    set_closed(deps.storage)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "close_facility")
+        .add_attribute("marker_denom", contract_info.facility.marker_denom))
+}
+
+// Validate an instantiate message, returning the invalid fields rather than an error.
+// NOTE: In addition to the message validation, the facility addresses are validated
+//       against the chain's address format.
+fn validate_instantiate(deps: Deps, msg: InstantiateMsg) -> StdResult<ValidateInstantiateResponse> {
+    let mut invalid_fields = match msg.validate() {
+        Ok(()) => vec![],
+        Err(ContractError::InvalidFields { errors }) => {
+            errors.into_iter().map(|error| error.field).collect()
+        }
+        Err(e) => return Err(StdError::generic_err(e.to_string())),
+    };
+
+    let addresses = [
+        ("facility.originator", &msg.facility.originator),
+        ("facility.warehouse", &msg.facility.warehouse),
+        ("facility.escrow_marker", &msg.facility.escrow_marker),
+    ];
+    for (field, address) in addresses {
+        if !invalid_fields.iter().any(|invalid| invalid == field)
+            && deps.api.addr_validate(address.as_str()).is_err()
+        {
+            invalid_fields.push(field.into());
+        }
+    }
+
+    Ok(ValidateInstantiateResponse { invalid_fields })
+}
+
+// Split the specified assets into those that are free to be pledged and those that are
+// already tracked by the facility (proposed for pledge, in the inventory or proposed for paydown).
+fn check_assets_available(
+    store: &dyn Storage,
+    assets: Vec<String>,
+) -> StdResult<CheckAssetsAvailableResponse> {
+    let mut response = CheckAssetsAvailableResponse {
+        available: vec![],
+        unavailable: vec![],
+    };
+    for asset in assets {
+        // look up the asset by its canonical id, but report it as specified
+        let key = normalize_asset_id(&asset).unwrap_or_else(|_| asset.clone());
+        match may_load_asset(store, key.as_bytes())? {
+            None => response.available.push(asset),
+            Some(_) => response.unavailable.push(asset),
+        }
+    }
+    Ok(response)
+}
+
+// Count the asset pool markers for pledges that have not been cancelled, rejected or closed.
+// NOTE: Each marker is cross-checked against the marker module so that markers which
+// no longer exist on chain are not counted.
+fn count_active_markers(deps: Deps) -> StdResult<u64> {
+    let querier = ProvenanceQuerier::new(&deps.querier);
+    let count = get_pledges_by_states(
+        deps.storage,
+        vec![
+            PledgeState::Proposed,
+            PledgeState::Accepted,
+            PledgeState::Executed,
+        ],
+        None,
+        None,
+    )?
+    .iter()
+    .filter(|pledge| {
+        querier
+            .get_marker_by_denom(pledge.asset_marker_denom.clone())
+            .is_ok()
+    })
+    .count();
+    Ok(count as u64)
+}
+
+// smart contract query entrypoint
+#[entry_point]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetContractInfo {} => to_binary(&get_contract_info(deps.storage)?),
+        QueryMsg::GetConfig {} => to_binary(&get_contract_config(deps.storage)?),
+        QueryMsg::GetVersion {} => to_binary(&get_contract_version(deps.storage)?),
+        QueryMsg::GetPartyRole { address } => to_binary(&get_party_role(deps, address)?),
+        QueryMsg::GetFacilityInfo {} => to_binary(&get_facility_info(deps.storage)?),
+        QueryMsg::GetAvailableCredit {} => to_binary(&get_available_credit(deps.storage)?),
+        QueryMsg::GetFacilitySummary {} => to_binary(&get_facility_summary(deps.storage)?),
+        QueryMsg::GetMarkerDistribution {} => to_binary(&get_marker_distribution(deps.storage)?),
+        QueryMsg::SimulatePledgeAdvance { asset_value } => {
+            to_binary(&get_simulated_pledge_advance(deps.storage, asset_value)?)
+        }
+        QueryMsg::GetPaydown { id } => to_binary(&get_paydown(deps.storage, id)?),
+        QueryMsg::GetPaydownState { id } => to_binary(&get_paydown_state(deps.storage, id)?),
+        QueryMsg::GetPledge { id } => to_binary(&get_pledge(deps.storage, id)?),
+        QueryMsg::GetPledgeState { id } => to_binary(&get_pledge_state(deps.storage, id)?),
+        QueryMsg::GetPledgeRaw { id } => to_binary(&get_pledge_raw(deps.storage, id)?),
+        QueryMsg::GetPledgeByAsset { asset } => {
+            to_binary(&get_pledge_by_asset(deps.storage, asset)?)
+        }
+        QueryMsg::FindPledgesByAssets { assets, state } => {
+            to_binary(&find_pledges_by_assets(deps.storage, assets, state)?)
+        }
+        QueryMsg::ListAssets {} => to_binary(&list_assets(deps.storage)?),
+        QueryMsg::ListAssetsByState { state } => {
+            to_binary(&list_assets_by_state(deps.storage, state)?)
+        }
+        QueryMsg::GetAssetHistory { id } => to_binary(&get_asset_history(deps.storage, id)?),
+        QueryMsg::ListInventory {} => to_binary(&list_inventory(deps.storage)?),
+        QueryMsg::ListAllScopes {} => to_binary(&list_all_scopes(deps.storage)?),
+        QueryMsg::CountActiveMarkers {} => to_binary(&count_active_markers(deps)?),
+        QueryMsg::CheckAssetsAvailable { assets } => {
+            to_binary(&check_assets_available(deps.storage, assets)?)
+        }
+        QueryMsg::ListEscrowedPledges {} => to_binary(&list_escrowed_pledges(deps.storage)?),
+        QueryMsg::GetEscrowBalance {} => to_binary(&get_escrow_balance(deps, env)?),
+        QueryMsg::ValidateInstantiate { msg } => to_binary(&validate_instantiate(deps, msg)?),
+        QueryMsg::ListPledgeIds { descending } => {
+            to_binary(&list_pledge_ids(deps.storage, descending)?)
+        }
+        QueryMsg::ListPledgeProposals { descending } => {
+            to_binary(&list_pledge_proposals(deps.storage, descending)?)
+        }
+        QueryMsg::ListPledges {
+            descending,
+            start_after,
+            limit,
+        } => to_binary(&list_pledges(deps.storage, descending, start_after, limit)?),
+        QueryMsg::ListClosedPledges {
+            descending,
+            start_after,
+            limit,
+        } => to_binary(&list_closed_pledges(
+            deps.storage,
+            descending,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ListPledgesByStates { states } => {
+            to_binary(&list_pledges_by_states(deps.storage, states)?)
+        }
+        QueryMsg::GetPledgesWithContext {} => to_binary(&get_pledges_with_context(deps.storage)?),
+        QueryMsg::ListPaydownIds { descending } => {
+            to_binary(&list_paydown_ids(deps.storage, descending)?)
+        }
+        QueryMsg::ListPaydownProposals { descending } => {
+            to_binary(&list_paydown_proposals(deps.storage, descending)?)
+        }
+        QueryMsg::ListPaydowns { descending } => {
+            to_binary(&list_paydowns(deps.storage, descending)?)
+        }
+    }
+}
+
+// smart contract migrate/upgrade entrypoint
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    // wrap the single stablecoin denom of a legacy facility
+    let migrated_contract_info = migrate_contract_info(deps.storage)?;
+
+    // always update version info
+    let mut contract_info = get_contract_info(deps.storage)?;
+    let from_version = contract_info.version;
+    contract_info.version = CONTRACT_VERSION.into();
+    set_contract_info(deps.storage, &contract_info)?;
+
+    // rewrite pledges and paydowns stored with u64 totals
+    let migrated_pledges = migrate_pledges(deps.storage)?;
+    let migrated_paydowns = migrate_paydowns(deps.storage)?;
+
+    // reserve the asset marker denoms of pledges proposed before denoms were registered
+    let migrated_asset_marker_denoms = migrate_asset_marker_denoms(deps.storage)?;
+
+    let migrated = from_version != contract_info.version
+        || migrated_contract_info
+        || migrated_pledges > 0
+        || migrated_paydowns > 0
+        || migrated_asset_marker_denoms > 0;
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("migrated_contract_info", migrated_contract_info.to_string())
+        .add_attribute("migrated_pledges", migrated_pledges.to_string())
+        .add_attribute("migrated_paydowns", migrated_paydowns.to_string())
+        .add_attribute(
+            "migrated_asset_marker_denoms",
+            migrated_asset_marker_denoms.to_string(),
+        )
+        .set_data(to_binary(&MigrateResponse {
+            from_version,
+            to_version: contract_info.version,
+            migrated,
+        })?))
+}
+
+// smart contract reply entrypoint (invoked when a marker setup submessage fails)
+#[entry_point]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let step = match msg.id {
+        REPLY_CREATE_MARKER => "create_marker",
+        REPLY_GRANT_MARKER_ACCESS => "grant_marker_access",
+        REPLY_FINALIZE_MARKER => "finalize_marker",
+        REPLY_ACTIVATE_MARKER => "activate_marker",
+        id => {
+            return Err(ContractError::StateError {
+                error: format!("Unknown reply id: {}", id),
+            })
+        }
+    };
+
+    match msg.result {
+        ContractResult::Ok(_) => Ok(Response::default()),
+        ContractResult::Err(error) => Err(ContractError::MarkerSetupFailed {
+            step: step.into(),
+            error,
+        }),
+    }
+}
+
+// smart contract sudo entrypoint (only invoked by the chain)
+// NOTE: A freeze pauses the facility (as SetPaused does) and locks the pause until it is lifted.
+#[entry_point]
+pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    let frozen = match msg {
+        SudoMsg::Freeze {} => true,
+        SudoMsg::Unfreeze {} => false,
+    };
+    set_paused(deps.storage, frozen)?;
+    set_frozen(deps.storage, frozen)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sudo")
+        .add_attribute("frozen", frozen.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contract::{
+        calculate_marker_distribution, execute, get_marker_address, instantiate,
+        is_marker_not_found, migrate, query, refund_surplus, reply, require_funds, sudo,
+        RequiredFunds, CONTRACT_VERSION, MAX_LIST_PLEDGES_LIMIT, REPLY_ACTIVATE_MARKER,
+        REPLY_CREATE_MARKER, REPLY_FINALIZE_MARKER, REPLY_GRANT_MARKER_ACCESS,
+    };
+    use crate::contract_info::{
+        get_contract_info, set_contract_info, ContractVersion, CONTRACT_TYPE,
+    };
+    use crate::error::{ContractError, FieldError};
+    use crate::msg::{
+        AvailableCreditResponse, CheckAssetsAvailableResponse, ClosedPledge, EscrowBalance,
+        EscrowBalanceResponse, EscrowedPledgesResponse, ExecuteMsg, FacilitySummary,
+        InstantiateMsg, ListClosedPledgesResponse, ListIdsResponse, ListPledgesResponse,
+        MigrateMsg, MigrateResponse, PartyRole, PartyRoleResponse, PaydownStateResponse,
+        PledgeStateResponse, PledgesWithContextResponse, ProposePledgeResponse, QueryMsg,
+        SimulatePledgeAdvanceResponse, SudoMsg, ValidateInstantiateResponse,
+    };
+    use crate::state::{
+        save_pledge, set_assets_state, Asset, AssetState, AssetTransition, Facility,
+        MarkerDistribution, Paydown, PaydownState, Pledge, PledgeState, RoundingMode,
+    };
+    use crate::testing::*;
+    use crate::utils::MetadataAddress;
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{
+        attr, coin, coins, from_binary, Addr, BankMsg, Binary, ContractResult, CosmosMsg, Deps,
+        DepsMut, Reply, ReplyOn, Response, StdError, StdResult, Storage, Timestamp, Uint128,
+    };
+    use provwasm_mocks::mock_dependencies;
+    use provwasm_std::{
+        bind_name, cancel_marker, destroy_marker, transfer_marker_coins, unbind_name,
+        withdraw_coins, AccessGrant, MarkerAccess, NameBinding, ProvenanceMsg,
+    };
+    use uuid::Uuid;
+
+    #[test]
+    pub fn count_active_markers_excludes_cancelled_pledges() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.with_markers(vec![
+            marker(ESCROW_MARKER, ESCROW_DENOM),
+            marker("asset_marker_1", &asset_marker_denom(PLEDGE_ID_1)),
+            marker("asset_marker_2", &asset_marker_denom(PLEDGE_ID_2)),
+        ]);
+        instantiate_test_contract(deps.as_mut());
+
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 200);
+
+        let count: u64 = from_binary(
+            &query(deps.as_ref(), test_env(), QueryMsg::CountActiveMarkers {}).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(count, 2);
+
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::CancelPledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+
+        let count: u64 = from_binary(
+            &query(deps.as_ref(), test_env(), QueryMsg::CountActiveMarkers {}).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    pub fn pledge_and_paydown_full_flow() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        // propose -> accept -> execute the pledge
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Executed
+        );
+
+        // propose -> accept -> execute a paydown of the pledged asset
+        let steps = vec![
+            (
+                ORIGINATOR,
+                coins(100, STABLECOIN_DENOM),
+                ExecuteMsg::ProposePaydown {
+                    id: PAYDOWN_ID_1.into(),
+                    assets: vec![ASSET_ID_1.into()],
+                    total_paydown: Uint128::new(100),
+                },
+            ),
+            (
+                WAREHOUSE,
+                vec![],
+                ExecuteMsg::AcceptPaydown {
+                    id: PAYDOWN_ID_1.into(),
+                },
+            ),
+            (
+                ORIGINATOR,
+                vec![],
+                ExecuteMsg::ExecutePaydown {
+                    id: PAYDOWN_ID_1.into(),
+                },
+            ),
+        ];
+        for (sender, funds, msg) in steps {
+            execute(deps.as_mut(), test_env(), mock_info(sender, &funds), msg).unwrap();
+        }
+
+        // paying down the only asset closes the pledge
+        assert_eq!(
+            get_paydown(deps.as_ref(), PAYDOWN_ID_1).state,
+            PaydownState::Executed
+        );
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Closed
+        );
+    }
+
+    #[test]
+    pub fn instantiate_with_escrow_marker_grant() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        let result = instantiate(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ADMIN, &[]),
+            instantiate_msg(),
+        );
+        match result {
+            Ok(response) => assert_eq!(response.messages.len(), 7),
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn instantiate_replies_on_marker_setup_errors() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        let response = instantiate(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ADMIN, &[]),
+            instantiate_msg(),
+        )
+        .unwrap();
+
+        let replies: Vec<(ReplyOn, u64)> = response
+            .messages
+            .iter()
+            .map(|msg| (msg.reply_on.clone(), msg.id))
+            .collect();
+        assert_eq!(
+            replies,
+            vec![
+                (ReplyOn::Never, 0),
+                (ReplyOn::Error, REPLY_CREATE_MARKER),
+                (ReplyOn::Error, REPLY_GRANT_MARKER_ACCESS),
+                (ReplyOn::Error, REPLY_FINALIZE_MARKER),
+                (ReplyOn::Error, REPLY_ACTIVATE_MARKER),
+                (ReplyOn::Never, 0),
+                (ReplyOn::Never, 0),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn reply_with_failed_grant() {
+        let mut deps = mock_dependencies(&[]);
+        let result = reply(
+            deps.as_mut(),
+            test_env(),
+            Reply {
+                id: REPLY_GRANT_MARKER_ACCESS,
+                result: ContractResult::Err("marker not found".into()),
+            },
+        );
+        match result {
+            Err(ContractError::MarkerSetupFailed { step, error }) => {
+                assert_eq!(step, "grant_marker_access");
+                assert_eq!(error, "marker not found");
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn migrate_from_old_version() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let mut contract_info = get_contract_info(&deps.storage).unwrap();
+        contract_info.version = "0.0.1".into();
+        set_contract_info(&mut deps.storage, &contract_info).unwrap();
+
+        let response = migrate(deps.as_mut(), test_env(), MigrateMsg::Migrate {}).unwrap();
+        assert_eq!(response.attributes[0], attr("action", "migrate"));
+        let data: MigrateResponse = from_binary(&response.data.unwrap()).unwrap();
+        assert_eq!(
+            data,
+            MigrateResponse {
+                from_version: "0.0.1".into(),
+                to_version: CONTRACT_VERSION.into(),
+                migrated: true,
+            }
+        );
+        assert_eq!(
+            get_contract_info(&deps.storage).unwrap().version,
+            CONTRACT_VERSION
+        );
+
+        // migrating again leaves the contract as it is
+        let response = migrate(deps.as_mut(), test_env(), MigrateMsg::Migrate {}).unwrap();
+        let data: MigrateResponse = from_binary(&response.data.unwrap()).unwrap();
+        assert_eq!(data.from_version, CONTRACT_VERSION);
+        assert!(!data.migrated);
+    }
+
+    #[test]
+    pub fn reply_with_unknown_id() {
+        let mut deps = mock_dependencies(&[]);
+        let result = reply(
+            deps.as_mut(),
+            test_env(),
+            Reply {
+                id: 99,
+                result: ContractResult::Err("failed".into()),
+            },
+        );
+        match result {
+            Err(ContractError::StateError { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn get_party_role_for_each_role() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        for (address, role) in [
+            (ORIGINATOR, PartyRole::Originator),
+            (WAREHOUSE, PartyRole::Warehouse),
+            (ADMIN, PartyRole::None),
+            (BUYER, PartyRole::None),
+        ] {
+            let response: PartyRoleResponse = from_binary(
+                &query(
+                    deps.as_ref(),
+                    test_env(),
+                    QueryMsg::GetPartyRole {
+                        address: address.into(),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(response.role, role, "address {}", address);
+        }
+    }
+
+    #[test]
+    pub fn get_party_role_with_invalid_address() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let result = query(
+            deps.as_ref(),
+            test_env(),
+            QueryMsg::GetPartyRole {
+                address: "ab".into(),
+            },
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    pub fn instantiate_without_escrow_marker() {
+        let mut deps = mock_dependencies(&[]);
+        let result = instantiate(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ADMIN, &[]),
+            instantiate_msg(),
+        );
+        match result {
+            Err(ContractError::EscrowMarkerNotFound { address }) => {
+                assert_eq!(address, ESCROW_MARKER)
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn handlers_without_escrow_marker() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+
+        // point the facility at an escrow marker that doesn't exist
+        let mut contract_info = get_contract_info(deps.as_ref().storage).unwrap();
+        contract_info.facility.escrow_marker = Addr::unchecked("unknown-escrow-marker");
+        set_contract_info(deps.as_mut().storage, &contract_info).unwrap();
+
+        let messages = vec![
+            (
+                ORIGINATOR,
+                vec![],
+                ExecuteMsg::ProposePledge {
+                    id: PLEDGE_ID_2.into(),
+                    assets: vec![ASSET_ID_2.into()],
+                    total_advance: Uint128::new(100),
+                    asset_marker_denom: asset_marker_denom(PLEDGE_ID_2),
+                    valid_until: None,
+                    document_hash: None,
+                },
+            ),
+            (
+                WAREHOUSE,
+                coins(100, STABLECOIN_DENOM),
+                ExecuteMsg::AcceptPledge {
+                    id: PLEDGE_ID_1.into(),
+                },
+            ),
+            (
+                ORIGINATOR,
+                vec![],
+                ExecuteMsg::CancelPledge {
+                    id: PLEDGE_ID_1.into(),
+                },
+            ),
+        ];
+        for (sender, funds, msg) in messages {
+            let result = execute(deps.as_mut(), test_env(), mock_info(sender, &funds), msg);
+            match result {
+                Err(ContractError::EscrowMarkerNotFound { address }) => {
+                    assert_eq!(address, "unknown-escrow-marker")
+                }
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    pub fn propose_pledge_with_existing_id() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ProposePledge {
+                id: PLEDGE_ID_1.into(),
+                assets: vec![ASSET_ID_2.into()],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(PLEDGE_ID_1),
+                valid_until: None,
+                document_hash: None,
+            },
+        );
+        match result {
+            Err(ContractError::PledgeAlreadyExists { id }) => assert_eq!(id, PLEDGE_ID_1),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn propose_pledge_with_document_hash() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        let hash = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+        let response = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ProposePledge {
+                id: PLEDGE_ID_1.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(PLEDGE_ID_1),
+                valid_until: None,
+                document_hash: Some(hash.into()),
+            },
+        )
+        .unwrap();
+        assert!(response.attributes.contains(&attr("document_hash", hash)));
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).document_hash,
+            Some(hash.into())
+        );
+
+        // a pledge proposed without a document has no hash
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 100);
+        assert_eq!(get_pledge(deps.as_ref(), PLEDGE_ID_2).document_hash, None);
+    }
+
+    #[test]
+    pub fn propose_paydown_with_existing_id() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_sale(deps.as_mut(), 50);
+
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &coins(100, STABLECOIN_DENOM)),
+            ExecuteMsg::ProposePaydown {
+                id: PAYDOWN_ID_1.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_paydown: Uint128::new(100),
+            },
+        );
+        match result {
+            Err(ContractError::PaydownAlreadyExists { id }) => assert_eq!(id, PAYDOWN_ID_1),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn instantiate_without_escrow_marker_grant() {
+        let mut deps = mock_dependencies(&[]);
+        let mut escrow_marker = marker(ESCROW_MARKER, ESCROW_DENOM);
+        escrow_marker.permissions = vec![AccessGrant {
+            address: Addr::unchecked(CONTRACT_ADDRESS),
+            permissions: vec![MarkerAccess::Transfer],
+        }];
+        deps.querier.with_markers(vec![escrow_marker]);
+        let result = instantiate(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ADMIN, &[]),
+            instantiate_msg(),
+        );
+        match result {
+            Err(ContractError::MissingEscrowMarkerGrant {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn cancel_pledge_with_missing_asset_marker() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.with_markers(vec![
+            marker(ESCROW_MARKER, ESCROW_DENOM),
+            marker("asset_marker_1", &asset_marker_denom(PLEDGE_ID_1)),
+        ]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+
+        // the asset marker no longer exists on chain
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::CancelPledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        );
+        match result {
+            Ok(response) => {
+                assert!(response.messages.is_empty());
+                let pledge: Pledge = from_binary(&response.data.unwrap()).unwrap();
+                assert_eq!(pledge.state, PledgeState::Cancelled);
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn get_marker_address_by_denom() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.with_markers(vec![
+            marker(ESCROW_MARKER, ESCROW_DENOM),
+            marker("asset_marker_1", &asset_marker_denom(PLEDGE_ID_1)),
+        ]);
+
+        match get_marker_address(&deps.as_ref(), &asset_marker_denom(PLEDGE_ID_1)) {
+            Ok(address) => assert_eq!(address, Addr::unchecked("asset_marker_1")),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        match get_marker_address(&deps.as_ref(), &asset_marker_denom(PLEDGE_ID_2)) {
+            Err(ContractError::MarkerNotFound { denom }) => {
+                assert_eq!(denom, asset_marker_denom(PLEDGE_ID_2));
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn marker_not_found_errors() {
+        assert!(is_marker_not_found(&StdError::not_found("marker")));
+        assert!(is_marker_not_found(&StdError::generic_err(
+            "Querier system error: marker not found"
+        )));
+        assert!(!is_marker_not_found(&StdError::generic_err(
+            "Querier system error: Cannot parse request"
+        )));
+        assert!(!is_marker_not_found(&StdError::parse_err("Marker", "bad")));
+    }
+
+    #[test]
+    pub fn get_marker_distribution() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        let mut msg = instantiate_msg();
+        msg.facility.advance_rate = "75.125".into();
+        instantiate(deps.as_mut(), test_env(), mock_info(ADMIN, &[]), msg).unwrap();
+
+        let distribution: MarkerDistribution = from_binary(
+            &query(
+                deps.as_ref(),
+                test_env(),
+                QueryMsg::GetMarkerDistribution {},
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(distribution.total_supply, Uint128::from(100000u128));
+        assert_eq!(distribution.to_warehouse, Uint128::from(75125u128));
+        assert_eq!(distribution.to_originator, Uint128::from(24875u128));
+        assert_eq!(distribution.advance_rate, "75.125");
+    }
+
+    #[test]
+    pub fn instantiate_with_padded_rates() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        let mut msg = instantiate_msg();
+        msg.facility.advance_rate = " 75.125 ".into();
+        msg.facility.paydown_rate = "102.25 ".into();
+        assert!(validate_instantiate(msg.clone()).is_empty());
+        instantiate(deps.as_mut(), test_env(), mock_info(ADMIN, &[]), msg).unwrap();
+
+        // the rates are stored without the whitespace
+        let facility: Facility =
+            from_binary(&query(deps.as_ref(), test_env(), QueryMsg::GetFacilityInfo {}).unwrap())
+                .unwrap();
+        assert_eq!(facility.advance_rate, "75.125");
+        assert_eq!(facility.paydown_rate, "102.25");
+
+        // malformed rates are still rejected
+        let mut msg = instantiate_msg();
+        msg.facility.advance_rate = "75. 125".into();
+        assert_eq!(
+            validate_instantiate(msg),
+            vec!["facility.advance_rate".to_string()]
+        );
+    }
+
+    fn simulate_pledge_advance(
+        advance_rate: &str,
+        asset_value: u64,
+    ) -> SimulatePledgeAdvanceResponse {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        let mut msg = instantiate_msg();
+        msg.facility.advance_rate = advance_rate.into();
+        instantiate(deps.as_mut(), test_env(), mock_info(ADMIN, &[]), msg).unwrap();
+
+        from_binary(
+            &query(
+                deps.as_ref(),
+                test_env(),
+                QueryMsg::SimulatePledgeAdvance { asset_value },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    pub fn simulate_pledge_advance_across_rates() {
+        // (advance rate, asset value, to warehouse, to originator)
+        let cases: [(&str, u64, u128, u128); 7] = [
+            ("75", 1000, 750, 250),
+            ("75.125", 1000, 751, 249),
+            ("75.125", 200000, 150250, 49750),
+            ("50", 999, 499, 500),
+            ("100", 1234, 1234, 0),
+            ("0.5", 1234, 6, 1228),
+            ("33.333", 3, 0, 3),
+        ];
+
+        for &(advance_rate, asset_value, to_warehouse, to_originator) in cases.iter() {
+            let simulation = simulate_pledge_advance(advance_rate, asset_value);
+            assert_eq!(
+                simulation,
+                SimulatePledgeAdvanceResponse {
+                    total_advance: Uint128::from(to_warehouse),
+                    marker_to_warehouse: Uint128::from(to_warehouse),
+                    marker_to_originator: Uint128::from(to_originator),
+                },
+                "advance rate {}",
+                advance_rate
+            );
+        }
+    }
+
+    #[test]
+    pub fn simulate_pledge_advance_with_rounding_modes() {
+        // at a 33.333% advance rate, 1500 splits to 499.995, 50000 to 16666.5 and 150000 to 49999.5
+        // (rounding mode, asset value, to warehouse)
+        let cases = [
+            (None, 1500, 499),
+            (Some(RoundingMode::Truncate), 1500, 499),
+            (Some(RoundingMode::HalfUp), 1500, 500),
+            (Some(RoundingMode::HalfEven), 1500, 500),
+            (None, 50000, 16666),
+            (Some(RoundingMode::Truncate), 50000, 16666),
+            (Some(RoundingMode::HalfUp), 50000, 16667),
+            (Some(RoundingMode::HalfEven), 50000, 16666),
+            (Some(RoundingMode::Truncate), 150000, 49999),
+            (Some(RoundingMode::HalfUp), 150000, 50000),
+            (Some(RoundingMode::HalfEven), 150000, 50000),
+        ];
+
+        for (rounding, asset_value, to_warehouse) in cases.iter() {
+            let mut deps = mock_dependencies(&[]);
+            deps.querier
+                .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+            let mut msg = instantiate_msg();
+            msg.facility.advance_rate = "33.333".into();
+            msg.facility.rounding = rounding.clone();
+            instantiate(deps.as_mut(), test_env(), mock_info(ADMIN, &[]), msg).unwrap();
+
+            let simulation: SimulatePledgeAdvanceResponse = from_binary(
+                &query(
+                    deps.as_ref(),
+                    test_env(),
+                    QueryMsg::SimulatePledgeAdvance {
+                        asset_value: *asset_value,
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(
+                simulation.marker_to_warehouse,
+                Uint128::from(*to_warehouse as u128),
+                "rounding {:?}, asset value {}",
+                rounding,
+                asset_value
+            );
+            assert_eq!(
+                simulation.marker_to_originator,
+                Uint128::from((*asset_value - *to_warehouse) as u128)
+            );
+
+            // the facility marker distribution is exact, so it doesn't depend on the mode
+            let distribution: MarkerDistribution = from_binary(
+                &query(
+                    deps.as_ref(),
+                    test_env(),
+                    QueryMsg::GetMarkerDistribution {},
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(distribution.to_warehouse, Uint128::from(33333u128));
+        }
+    }
+
+    #[test]
+    pub fn simulate_pledge_advance_with_zero_asset_value() {
+        let simulation = simulate_pledge_advance("75.125", 0);
+        assert_eq!(simulation.total_advance, Uint128::zero());
+        assert_eq!(simulation.marker_to_warehouse, Uint128::zero());
+        assert_eq!(simulation.marker_to_originator, Uint128::zero());
+    }
+
+    #[test]
+    pub fn propose_pledge_returns_asset_marker_address() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ProposePledge {
+                id: PLEDGE_ID_1.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(PLEDGE_ID_1),
+                valid_until: None,
+                document_hash: None,
+            },
+        );
+        match result {
+            Ok(response) => {
+                let data: ProposePledgeResponse = from_binary(&response.data.unwrap()).unwrap();
+                assert_eq!(data.pledge.id, PLEDGE_ID_1);
+                assert_eq!(data.pledge.state, PledgeState::Proposed);
+                assert_eq!(
+                    data.marker_address,
+                    Addr::unchecked("tp12565mnvxzyjtu2ulpnn5cd7xcpw98q249aqtvu")
+                );
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn pledge_handlers_with_unknown_id() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let messages = vec![
+            (
+                WAREHOUSE,
+                ExecuteMsg::AcceptPledge {
+                    id: PLEDGE_ID_1.into(),
+                },
+            ),
+            (
+                ORIGINATOR,
+                ExecuteMsg::CancelPledge {
+                    id: PLEDGE_ID_1.into(),
+                },
+            ),
+            (
+                ORIGINATOR,
+                ExecuteMsg::ExecutePledge {
+                    id: PLEDGE_ID_1.into(),
+                },
+            ),
+        ];
+        for (sender, msg) in messages {
+            let result = execute(deps.as_mut(), test_env(), mock_info(sender, &[]), msg);
+            match result {
+                Err(ContractError::PledgeNotFound { id }) => assert_eq!(id, PLEDGE_ID_1),
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    pub fn paydown_handlers_with_unknown_id() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let messages = vec![
+            (
+                WAREHOUSE,
+                ExecuteMsg::AcceptPaydown {
+                    id: PAYDOWN_ID_1.into(),
+                },
+            ),
+            (
+                ORIGINATOR,
+                ExecuteMsg::CancelPaydown {
+                    id: PAYDOWN_ID_1.into(),
+                },
+            ),
+            (
+                ORIGINATOR,
+                ExecuteMsg::ExecutePaydown {
+                    id: PAYDOWN_ID_1.into(),
+                },
+            ),
+        ];
+        for (sender, msg) in messages {
+            let result = execute(deps.as_mut(), test_env(), mock_info(sender, &[]), msg);
+            match result {
+                Err(ContractError::PaydownNotFound { id }) => assert_eq!(id, PAYDOWN_ID_1),
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    pub fn check_assets_available_with_mixed_assets() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1, ASSET_ID_2], 100);
+
+        let response: CheckAssetsAvailableResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                test_env(),
+                QueryMsg::CheckAssetsAvailable {
+                    assets: vec![ASSET_ID_3.into(), ASSET_ID_1.into(), ASSET_ID_2.into()],
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(response.available, vec![ASSET_ID_3.to_string()]);
+        assert_eq!(
+            response.unavailable,
+            vec![ASSET_ID_1.to_string(), ASSET_ID_2.to_string()]
+        );
+    }
+
+    #[test]
+    pub fn propose_pledge_stores_assets_in_canonical_order() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        // the same assets, specified in different orders
+        propose_test_pledge(
+            deps.as_mut(),
+            PLEDGE_ID_1,
+            &[ASSET_ID_3, ASSET_ID_1, ASSET_ID_2],
+            100,
+        );
+        let first = get_pledge(deps.as_ref(), PLEDGE_ID_1);
+        cancel_pledge(deps.as_mut(), ORIGINATOR).unwrap();
+        propose_test_pledge(
+            deps.as_mut(),
+            PLEDGE_ID_2,
+            &[ASSET_ID_2, ASSET_ID_3, ASSET_ID_1],
+            100,
+        );
+        let second = get_pledge(deps.as_ref(), PLEDGE_ID_2);
+
+        assert_eq!(
+            first.assets,
+            vec![
+                ASSET_ID_1.to_string(),
+                ASSET_ID_2.to_string(),
+                ASSET_ID_3.to_string()
+            ]
+        );
+        assert_eq!(second.assets, first.assets);
+    }
+
+    #[test]
+    pub fn check_assets_available_with_no_pledges() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let response: CheckAssetsAvailableResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                test_env(),
+                QueryMsg::CheckAssetsAvailable {
+                    assets: vec![ASSET_ID_1.into(), ASSET_ID_2.into()],
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            response.available,
+            vec![ASSET_ID_1.to_string(), ASSET_ID_2.to_string()]
+        );
+        assert!(response.unavailable.is_empty());
+    }
+
+    fn scope_address(asset: &str) -> String {
+        MetadataAddress::for_scope(Uuid::parse_str(asset).unwrap()).to_string()
+    }
+
+    fn get_pledge_by_asset(deps: Deps, asset: &str) -> StdResult<Pledge> {
+        query(
+            deps,
+            test_env(),
+            QueryMsg::GetPledgeByAsset {
+                asset: asset.into(),
+            },
+        )
+        .and_then(|binary| from_binary(&binary))
+    }
+
+    #[test]
+    pub fn get_pledge_by_asset_in_either_form() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        // an asset stored as a UUID is found by its scope address
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        for asset in [ASSET_ID_1.to_string(), scope_address(ASSET_ID_1)] {
+            assert_eq!(
+                get_pledge_by_asset(deps.as_ref(), &asset).unwrap().id,
+                PLEDGE_ID_1
+            );
+        }
+
+        // an asset stored as a scope address is found by its UUID
+        let pledge = Pledge {
+            id: PLEDGE_ID_2.into(),
+            assets: vec![scope_address(ASSET_ID_2)],
+            total_advance: Uint128::new(100),
+            asset_marker_denom: asset_marker_denom(PLEDGE_ID_2),
+            state: PledgeState::Executed,
+            proposed_at: Timestamp::from_seconds(1000),
+            accepted_at: None,
+            executed_at: None,
+            advance_denom: None,
+            valid_until: None,
+            document_hash: None,
+            repayment_reference: None,
+        };
+        save_pledge(deps.as_mut().storage, PLEDGE_ID_2.as_bytes(), &pledge).unwrap();
+        assert_eq!(
+            get_pledge_by_asset(deps.as_ref(), ASSET_ID_2).unwrap().id,
+            PLEDGE_ID_2
+        );
+
+        // an asset that isn't pledged isn't found
+        match get_pledge_by_asset(deps.as_ref(), &scope_address(ASSET_ID_3)) {
+            Err(StdError::NotFound { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    fn find_pledges_by_assets(
+        deps: Deps,
+        assets: &[&str],
+        state: Option<PledgeState>,
+    ) -> Vec<String> {
+        let pledges: Vec<Pledge> = from_binary(
+            &query(
+                deps,
+                test_env(),
+                QueryMsg::FindPledgesByAssets {
+                    assets: assets.iter().map(|asset| asset.to_string()).collect(),
+                    state,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        pledges.into_iter().map(|pledge| pledge.id).collect()
+    }
+
+    #[test]
+    pub fn find_pledges_by_overlapping_and_disjoint_assets() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1, ASSET_ID_2], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_3], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_2, 100).unwrap();
+
+        // assets overlapping both pledges, in either form
+        let asset_3 = scope_address(ASSET_ID_3);
+        let ids = find_pledges_by_assets(deps.as_ref(), &[ASSET_ID_2, asset_3.as_str()], None);
+        assert_eq!(ids, vec![PLEDGE_ID_1, PLEDGE_ID_2]);
+
+        // only the pledges in the state
+        assert_eq!(
+            find_pledges_by_assets(
+                deps.as_ref(),
+                &[ASSET_ID_2, asset_3.as_str()],
+                Some(PledgeState::Accepted)
+            ),
+            vec![PLEDGE_ID_2]
+        );
+
+        // assets disjoint from every pledge
+        let unknown = "0f4c5b2a-8d3e-4a1f-9b6c-7e2d1a0c3b44";
+        assert!(find_pledges_by_assets(deps.as_ref(), &[unknown], None).is_empty());
+        assert!(find_pledges_by_assets(deps.as_ref(), &[], None).is_empty());
+    }
+
+    #[test]
+    pub fn propose_pledge_with_scope_address_assets() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        // mix a bare UUID with a scope address (stored as the scope UUID)
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ProposePledge {
+                id: PLEDGE_ID_1.into(),
+                assets: vec![ASSET_ID_1.to_uppercase(), scope_address(ASSET_ID_2)],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(PLEDGE_ID_1),
+                valid_until: None,
+                document_hash: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).assets,
+            vec![ASSET_ID_1.to_string(), ASSET_ID_2.to_string()]
+        );
+
+        // the same asset can't be pledged again in its other form
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ProposePledge {
+                id: PLEDGE_ID_2.into(),
+                assets: vec![ASSET_ID_2.into()],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(PLEDGE_ID_2),
+                valid_until: None,
+                document_hash: None,
+            },
+        );
+        match result {
+            Err(ContractError::AssetsAlreadyPledged {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // availability is checked by scope UUID, but reported as specified
+        let response: CheckAssetsAvailableResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                test_env(),
+                QueryMsg::CheckAssetsAvailable {
+                    assets: vec![scope_address(ASSET_ID_1), scope_address(ASSET_ID_3)],
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(response.available, vec![scope_address(ASSET_ID_3)]);
+        assert_eq!(response.unavailable, vec![scope_address(ASSET_ID_1)]);
+    }
+
+    #[test]
+    pub fn list_pledge_ids_includes_total() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 100);
+
+        let response: ListIdsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                test_env(),
+                QueryMsg::ListPledgeIds {
+                    descending: Some(true),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            response.ids,
+            vec![PLEDGE_ID_2.to_string(), PLEDGE_ID_1.to_string()]
+        );
+        assert_eq!(response.total, 2);
+    }
+
+    fn list_pledges(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> ListPledgesResponse {
+        from_binary(
+            &query(
+                deps,
+                test_env(),
+                QueryMsg::ListPledges {
+                    descending: None,
+                    start_after,
+                    limit,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    pub fn list_pledges_pages_past_the_max_limit() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        // store more pledges than can be listed in a single query
+        let count = MAX_LIST_PLEDGES_LIMIT as usize + 5;
+        for i in 0..count {
+            let id = format!("pledge-{:03}", i);
+            let pledge = Pledge {
+                id: id.clone(),
+                assets: vec![],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(&id),
+                state: PledgeState::Proposed,
+                proposed_at: Timestamp::from_seconds(1000),
+                accepted_at: None,
+                executed_at: None,
+                advance_denom: None,
+                valid_until: None,
+                document_hash: None,
+                repayment_reference: None,
+            };
+            save_pledge(deps.as_mut().storage, id.as_bytes(), &pledge).unwrap();
+        }
+
+        // the requested limit is capped at the max limit
+        let first_page = list_pledges(deps.as_ref(), None, Some(1000));
+        assert_eq!(first_page.pledges.len(), MAX_LIST_PLEDGES_LIMIT as usize);
+        assert_eq!(first_page.pledges[0].id, "pledge-000");
+        assert_eq!(first_page.next_start_after, Some("pledge-099".into()));
+
+        // the last page has the remaining pledges and no cursor
+        let last_page = list_pledges(deps.as_ref(), first_page.next_start_after, Some(1000));
+        assert_eq!(last_page.pledges.len(), 5);
+        assert_eq!(last_page.pledges[0].id, "pledge-100");
+        assert_eq!(last_page.next_start_after, None);
+
+        // streaming with the cursor visits every pledge exactly once
+        let mut ids: Vec<String> = vec![];
+        let mut start_after = None;
+        loop {
+            let page = list_pledges(deps.as_ref(), start_after, Some(7));
+            ids.extend(page.pledges.into_iter().map(|pledge| pledge.id));
+            match page.next_start_after {
+                Some(next) => start_after = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(ids.len(), count);
+        assert_eq!(ids[count - 1], format!("pledge-{:03}", count - 1));
+    }
+
+    #[test]
+    pub fn list_pledges_with_exact_limit_has_no_cursor() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 100);
+
+        let response = list_pledges(deps.as_ref(), None, Some(2));
+        assert_eq!(response.pledges.len(), 2);
+        assert_eq!(response.next_start_after, None);
+
+        let response = list_pledges(deps.as_ref(), None, Some(1));
+        assert_eq!(response.pledges[0].id, PLEDGE_ID_1);
+        assert_eq!(response.next_start_after, Some(PLEDGE_ID_1.into()));
+    }
+
+    #[test]
+    pub fn list_closed_pledges_with_paydowns() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        // execute two pledges, then pay down the assets of the first to close it
+        for (id, asset) in [(PLEDGE_ID_1, ASSET_ID_1), (PLEDGE_ID_2, ASSET_ID_2)] {
+            propose_test_pledge(deps.as_mut(), id, &[asset], 100);
+            accept_pledge(deps.as_mut(), id, 100).unwrap();
+            execute(
+                deps.as_mut(),
+                test_env(),
+                mock_info(ORIGINATOR, &[]),
+                ExecuteMsg::ExecutePledge { id: id.into() },
+            )
+            .unwrap();
+        }
+        let steps = vec![
+            (
+                ORIGINATOR,
+                coins(100, STABLECOIN_DENOM),
+                ExecuteMsg::ProposePaydown {
+                    id: PAYDOWN_ID_1.into(),
+                    assets: vec![ASSET_ID_1.into()],
+                    total_paydown: Uint128::new(100),
+                },
+            ),
+            (
+                WAREHOUSE,
+                vec![],
+                ExecuteMsg::AcceptPaydown {
+                    id: PAYDOWN_ID_1.into(),
+                },
+            ),
+            (
+                ORIGINATOR,
+                vec![],
+                ExecuteMsg::ExecutePaydown {
+                    id: PAYDOWN_ID_1.into(),
+                },
+            ),
+        ];
+        for (sender, funds, msg) in steps {
+            execute(deps.as_mut(), test_env(), mock_info(sender, &funds), msg).unwrap();
+        }
+
+        let response: ListClosedPledgesResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                test_env(),
+                QueryMsg::ListClosedPledges {
+                    descending: None,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        // only the closed pledge is listed, joined with the paydown that closed it
+        let paydown = get_paydown(deps.as_ref(), PAYDOWN_ID_1);
+        assert_eq!(paydown.pledge_id, Some(PLEDGE_ID_1.into()));
+        assert_eq!(
+            response,
+            ListClosedPledgesResponse {
+                pledges: vec![ClosedPledge {
+                    pledge: get_pledge(deps.as_ref(), PLEDGE_ID_1),
+                    paydowns: vec![paydown],
+                }],
+                next_start_after: None,
+            }
+        );
+        assert_eq!(response.pledges[0].pledge.state, PledgeState::Closed);
+    }
+
+    #[test]
+    pub fn list_paydown_ids_without_paydowns() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let response: ListIdsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                test_env(),
+                QueryMsg::ListPaydownIds { descending: None },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(response.ids.is_empty());
+        assert_eq!(response.total, 0);
+    }
+
+    #[test]
+    pub fn update_binding_rebinds_contract_name() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let response = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ADMIN, &[]),
+            ExecuteMsg::UpdateBinding {
+                bind_name: "new-bind-name".into(),
+            },
+        )
+        .unwrap();
+        let messages: Vec<_> = response.messages.into_iter().map(|msg| msg.msg).collect();
+        assert_eq!(
+            messages,
+            vec![
+                unbind_name("contract-bind-name").unwrap(),
+                bind_name(
+                    "new-bind-name",
+                    Addr::unchecked(CONTRACT_ADDRESS),
+                    NameBinding::Restricted
+                )
+                .unwrap(),
+            ]
+        );
+
+        let contract_info = get_contract_info(&deps.storage).unwrap();
+        assert_eq!(contract_info.bind_name, "new-bind-name");
+    }
+
+    #[test]
+    pub fn update_binding_requires_admin() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::UpdateBinding {
+                bind_name: "new-bind-name".into(),
+            },
+        );
+        match result {
+            Err(ContractError::Unauthorized {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    fn validate_instantiate(msg: InstantiateMsg) -> Vec<String> {
+        let deps = mock_dependencies(&[]);
+        let response: ValidateInstantiateResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                test_env(),
+                QueryMsg::ValidateInstantiate { msg },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        response.invalid_fields
+    }
+
+    #[test]
+    pub fn validate_instantiate_with_valid_msg() {
+        assert!(validate_instantiate(instantiate_msg()).is_empty());
+    }
+
+    #[test]
+    pub fn validate_instantiate_with_invalid_fields() {
+        let mut msg = instantiate_msg();
+        msg.bind_name = "".into();
+        msg.facility.advance_rate = "101".into();
+        assert_eq!(
+            validate_instantiate(msg),
+            vec!["bind_name", "facility.advance_rate"]
+        );
+
+        let mut msg = instantiate_msg();
+        msg.facility.stablecoin_denoms = vec![MARKER_DENOM.into()];
+        msg.facility.paydown_rate = "abc".into();
+        assert_eq!(
+            validate_instantiate(msg),
+            vec!["facility.stablecoin_denoms", "facility.paydown_rate"]
+        );
+    }
+
+    #[test]
+    pub fn validate_instantiate_with_invalid_addresses() {
+        let mut msg = instantiate_msg();
+        msg.facility.originator = Addr::unchecked("");
+        msg.facility.warehouse = Addr::unchecked("ab");
+        assert_eq!(
+            validate_instantiate(msg),
+            vec!["facility.originator", "facility.warehouse"]
+        );
+    }
+
+    fn send(to_address: &str, amount: u128) -> CosmosMsg<ProvenanceMsg> {
+        BankMsg::Send {
+            to_address: to_address.into(),
+            amount: coins(amount, STABLECOIN_DENOM),
+        }
+        .into()
+    }
+
+    fn messages(response: Response<ProvenanceMsg>) -> Vec<CosmosMsg<ProvenanceMsg>> {
+        response.messages.into_iter().map(|msg| msg.msg).collect()
+    }
+
+    fn accept_pledge(
+        deps: DepsMut,
+        id: &str,
+        amount: u128,
+    ) -> Result<Response<ProvenanceMsg>, ContractError> {
+        execute(
+            deps,
+            test_env(),
+            mock_info(WAREHOUSE, &coins(amount, STABLECOIN_DENOM)),
+            ExecuteMsg::AcceptPledge { id: id.into() },
+        )
+    }
+
+    // Set up a facility with an executed pledge whose asset is proposed for sale to the buyer.
+    fn propose_sale(deps: DepsMut, purchase_price: u64) {
+        let mut deps = deps;
+        propose_test_pledge(deps.branch(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        accept_pledge(deps.branch(), PLEDGE_ID_1, 100).unwrap();
+        execute(
+            deps.branch(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+        execute(
+            deps,
+            test_env(),
+            mock_info(ORIGINATOR, &coins(100, STABLECOIN_DENOM)),
+            ExecuteMsg::ProposePaydownAndSell {
+                id: PAYDOWN_ID_1.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_paydown: Uint128::new(100),
+                buyer: Addr::unchecked(BUYER),
+                purchase_price,
+            },
+        )
+        .unwrap();
+    }
+
+    fn accept_sale(deps: DepsMut, amount: u128) -> Result<Response<ProvenanceMsg>, ContractError> {
+        execute(
+            deps,
+            test_env(),
+            mock_info(BUYER, &coins(amount, STABLECOIN_DENOM)),
+            ExecuteMsg::AcceptPaydown {
+                id: PAYDOWN_ID_1.into(),
+            },
+        )
+    }
+
+    #[test]
+    pub fn accept_pledge_up_to_facility_limit() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        let mut msg = instantiate_msg();
+        msg.facility.max_total_advance = Some(Uint128::new(250));
+        instantiate(deps.as_mut(), test_env(), mock_info(ADMIN, &[]), msg).unwrap();
+
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 150);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_3, &[ASSET_ID_3], 1);
+
+        // executed pledges still count towards the limit
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+
+        // right at the limit
+        accept_pledge(deps.as_mut(), PLEDGE_ID_2, 150).unwrap();
+
+        // past the limit
+        match accept_pledge(deps.as_mut(), PLEDGE_ID_3, 1) {
+            Err(ContractError::FacilityLimitExceeded { limit, would_be }) => {
+                assert_eq!(limit, 250);
+                assert_eq!(would_be, 251);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_3).state,
+            PledgeState::Proposed
+        );
+    }
+
+    fn available_credit(deps: Deps) -> AvailableCreditResponse {
+        from_binary(&query(deps, test_env(), QueryMsg::GetAvailableCredit {}).unwrap()).unwrap()
+    }
+
+    #[test]
+    pub fn available_credit_with_facility_limit() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        let mut msg = instantiate_msg();
+        msg.facility.max_total_advance = Some(Uint128::new(250));
+        instantiate(deps.as_mut(), test_env(), mock_info(ADMIN, &[]), msg).unwrap();
+
+        // proposals don't use any of the credit until they're accepted
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        assert_eq!(
+            available_credit(deps.as_ref()),
+            AvailableCreditResponse {
+                limit: Some(Uint128::new(250)),
+                outstanding: Uint128::zero(),
+                available: Some(Uint128::new(250)),
+            }
+        );
+
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        assert_eq!(
+            available_credit(deps.as_ref()),
+            AvailableCreditResponse {
+                limit: Some(Uint128::new(250)),
+                outstanding: Uint128::new(100),
+                available: Some(Uint128::new(150)),
+            }
+        );
+    }
+
+    #[test]
+    pub fn available_credit_without_facility_limit() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+
+        assert_eq!(
+            available_credit(deps.as_ref()),
+            AvailableCreditResponse {
+                limit: None,
+                outstanding: Uint128::new(100),
+                available: None,
+            }
+        );
+    }
+
+    #[test]
+    pub fn accept_pledges_past_facility_limit() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        let mut msg = instantiate_msg();
+        msg.facility.max_total_advance = Some(Uint128::new(250));
+        instantiate(deps.as_mut(), test_env(), mock_info(ADMIN, &[]), msg).unwrap();
+
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 151);
+        match execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(WAREHOUSE, &coins(251, STABLECOIN_DENOM)),
+            ExecuteMsg::AcceptPledges {
+                ids: vec![PLEDGE_ID_1.into(), PLEDGE_ID_2.into()],
+            },
+        ) {
+            Err(ContractError::FacilityLimitExceeded { limit, would_be }) => {
+                assert_eq!(limit, 250);
+                assert_eq!(would_be, 251);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Proposed
+        );
+    }
+
+    #[test]
+    pub fn accept_pledge_with_insufficient_funds() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+
+        match accept_pledge(deps.as_mut(), PLEDGE_ID_1, 99) {
+            Err(ContractError::InsufficientPledgeAdvanceFunds { need, received, .. }) => {
+                assert_eq!(need, 100);
+                assert_eq!(received, 99);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn accept_pledge_with_exact_funds() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+
+        let response = accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        assert_eq!(messages(response), vec![send(ESCROW_MARKER, 100)]);
+    }
+
+    #[test]
+    pub fn accept_pledge_refunds_surplus_funds() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+
+        let response = accept_pledge(deps.as_mut(), PLEDGE_ID_1, 150).unwrap();
+        assert_eq!(
+            messages(response),
+            vec![send(ESCROW_MARKER, 100), send(WAREHOUSE, 50)]
+        );
+    }
+
+    #[test]
+    pub fn accept_pledge_refunds_funds_in_other_denoms() {
+        let mut deps = instantiated_test_deps();
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+
+        let response = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(
+                WAREHOUSE,
+                &[coin(100, STABLECOIN_DENOM), coin(40, STABLECOIN_DENOM_2)],
+            ),
+            ExecuteMsg::AcceptPledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            messages(response),
+            vec![
+                send(ESCROW_MARKER, 100),
+                BankMsg::Send {
+                    to_address: WAREHOUSE.into(),
+                    amount: coins(40, STABLECOIN_DENOM_2),
+                }
+                .into()
+            ]
+        );
+    }
+
+    fn accept_pledges(
+        deps: DepsMut,
+        ids: &[&str],
+        amount: u128,
+    ) -> Result<Response<ProvenanceMsg>, ContractError> {
+        execute(
+            deps,
+            test_env_at(2000),
+            mock_info(WAREHOUSE, &coins(amount, STABLECOIN_DENOM)),
+            ExecuteMsg::AcceptPledges {
+                ids: ids.iter().map(|id| id.to_string()).collect(),
+            },
+        )
+    }
+
+    #[test]
+    pub fn accept_pledges_escrows_combined_advance() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 250);
+
+        let response = accept_pledges(deps.as_mut(), &[PLEDGE_ID_1, PLEDGE_ID_2], 400).unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", "accept_pledges"),
+                attr("pledge_ids", format!("{},{}", PLEDGE_ID_1, PLEDGE_ID_2)),
+            ]
+        );
+        assert_eq!(
+            messages(response),
+            vec![send(ESCROW_MARKER, 350), send(WAREHOUSE, 50)]
+        );
+
+        // each pledge records its own advance in escrow
+        for (id, total_advance) in [(PLEDGE_ID_1, 100u128), (PLEDGE_ID_2, 250u128)].iter() {
+            let pledge = get_pledge(deps.as_ref(), id);
+            assert_eq!(pledge.state, PledgeState::Accepted);
+            assert_eq!(pledge.total_advance, Uint128::new(*total_advance));
+            assert_eq!(pledge.accepted_at, Some(Timestamp::from_seconds(2000)));
+            assert_eq!(pledge.advance_denom, Some(STABLECOIN_DENOM.into()));
+        }
+    }
+
+    #[test]
+    pub fn accept_pledges_with_insufficient_funds() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 250);
+
+        match accept_pledges(deps.as_mut(), &[PLEDGE_ID_1, PLEDGE_ID_2], 349) {
+            Err(ContractError::InsufficientPledgeAdvanceFunds { need, received, .. }) => {
+                assert_eq!(need, 350);
+                assert_eq!(received, 349);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Proposed
+        );
+    }
+
+    #[test]
+    pub fn accept_pledges_with_pledge_not_proposed_mid_batch() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 250);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_3, &[ASSET_ID_3], 400);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_2, 250).unwrap();
+
+        match accept_pledges(deps.as_mut(), &[PLEDGE_ID_1, PLEDGE_ID_2, PLEDGE_ID_3], 500) {
+            Err(ContractError::StateError { error }) => {
+                assert!(error.contains(PLEDGE_ID_2));
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // none of the pledges in the batch were accepted
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Proposed
+        );
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_3).state,
+            PledgeState::Proposed
+        );
+    }
+
+    #[test]
+    pub fn accept_pledges_requires_warehouse() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &coins(100, STABLECOIN_DENOM)),
+            ExecuteMsg::AcceptPledges {
+                ids: vec![PLEDGE_ID_1.into()],
+            },
+        );
+        match result {
+            Err(ContractError::Unauthorized {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    // Propose a pledge at 1000s that can be accepted until the deadline.
+    fn propose_pledge_valid_until(
+        deps: DepsMut,
+        id: &str,
+        asset: &str,
+        valid_until: u64,
+    ) -> Result<Response<ProvenanceMsg>, ContractError> {
+        execute(
+            deps,
+            test_env_at(1000),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ProposePledge {
+                id: id.into(),
+                assets: vec![asset.into()],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(id),
+                valid_until: Some(Timestamp::from_seconds(valid_until)),
+                document_hash: None,
+            },
+        )
+    }
+
+    #[test]
+    pub fn accept_pledge_until_deadline() {
+        for (seconds, expired) in [(1999, false), (2000, false), (2001, true)] {
+            let mut deps = mock_dependencies(&[]);
+            deps.querier
+                .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+            instantiate_test_contract(deps.as_mut());
+            propose_pledge_valid_until(deps.as_mut(), PLEDGE_ID_1, ASSET_ID_1, 2000).unwrap();
+            assert_eq!(
+                get_pledge(deps.as_ref(), PLEDGE_ID_1).valid_until,
+                Some(Timestamp::from_seconds(2000))
+            );
+
+            let result = execute(
+                deps.as_mut(),
+                test_env_at(seconds),
+                mock_info(WAREHOUSE, &coins(100, STABLECOIN_DENOM)),
+                ExecuteMsg::AcceptPledge {
+                    id: PLEDGE_ID_1.into(),
+                },
+            );
+            match result {
+                Ok(_) if !expired => {}
+                Err(ContractError::ProposalExpired { id }) if expired => {
+                    assert_eq!(id, PLEDGE_ID_1);
+                }
+                result => panic!("unexpected result at {}s: {:?}", seconds, result),
+            }
+        }
+    }
+
+    #[test]
+    pub fn cancel_expired_pledge_proposal() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_pledge_valid_until(deps.as_mut(), PLEDGE_ID_1, ASSET_ID_1, 1500).unwrap();
+
+        // the originator can still cancel the proposal once it can no longer be accepted
+        execute(
+            deps.as_mut(),
+            test_env_at(2000),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::CancelPledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Cancelled
+        );
+    }
+
+    #[test]
+    pub fn accept_pledges_with_expired_proposal() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_pledge_valid_until(deps.as_mut(), PLEDGE_ID_1, ASSET_ID_1, 3000).unwrap();
+        propose_pledge_valid_until(deps.as_mut(), PLEDGE_ID_2, ASSET_ID_2, 1500).unwrap();
+
+        // the batch is accepted at 2000s, after the second proposal's deadline
+        match accept_pledges(deps.as_mut(), &[PLEDGE_ID_1, PLEDGE_ID_2], 200) {
+            Err(ContractError::ProposalExpired { id }) => assert_eq!(id, PLEDGE_ID_2),
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Proposed
+        );
+    }
+
+    #[test]
+    pub fn accept_pledge_with_assets_no_longer_proposed() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_3, &[ASSET_ID_3], 100);
+
+        // another pledge has already been accepted with the same asset
+        let pledge = Pledge {
+            id: PLEDGE_ID_2.into(),
+            assets: vec![ASSET_ID_1.into()],
+            total_advance: Uint128::new(100),
+            asset_marker_denom: asset_marker_denom(PLEDGE_ID_2),
+            state: PledgeState::Accepted,
+            proposed_at: Timestamp::from_seconds(1000),
+            accepted_at: Some(Timestamp::from_seconds(1000)),
+            executed_at: None,
+            advance_denom: None,
+            valid_until: None,
+            document_hash: None,
+            repayment_reference: None,
+        };
+        save_pledge(deps.as_mut().storage, PLEDGE_ID_2.as_bytes(), &pledge).unwrap();
+        match accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100) {
+            Err(ContractError::AssetsAlreadyPledged {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Proposed
+        );
+
+        // the asset has already moved into the inventory
+        set_assets_state(
+            deps.as_mut().storage,
+            AssetState::Inventory,
+            &[ASSET_ID_3.into()],
+            Timestamp::from_seconds(1000),
+        )
+        .unwrap();
+        match accept_pledges(deps.as_mut(), &[PLEDGE_ID_3], 100) {
+            Err(ContractError::AssetsAlreadyPledged {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_3).state,
+            PledgeState::Proposed
+        );
+    }
+
+    #[test]
+    pub fn propose_pledge_with_past_deadline() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        match propose_pledge_valid_until(deps.as_mut(), PLEDGE_ID_1, ASSET_ID_1, 999) {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new(
+                        "valid_until",
+                        "must not be before the current block time"
+                    )]
+                );
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn accept_pledge_with_advance_above_u64_max() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let total_advance = u64::MAX as u128 + 1;
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], total_advance);
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).total_advance,
+            Uint128::new(total_advance)
+        );
+
+        // the insufficient funds error reports the full advance
+        match accept_pledge(deps.as_mut(), PLEDGE_ID_1, total_advance - 1) {
+            Err(ContractError::InsufficientPledgeAdvanceFunds { need, received, .. }) => {
+                assert_eq!(need, total_advance);
+                assert_eq!(received, total_advance - 1);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let response = accept_pledge(deps.as_mut(), PLEDGE_ID_1, total_advance).unwrap();
+        assert_eq!(messages(response), vec![send(ESCROW_MARKER, total_advance)]);
+    }
+
+    #[test]
+    pub fn paydown_and_sell_escrows_paydown_then_purchase_funds() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+
+        // the originator escrows the paydown when proposing
+        let propose = |deps: DepsMut, amount: u128| {
+            let funds = match amount {
+                0 => vec![],
+                amount => coins(amount, STABLECOIN_DENOM),
+            };
+            execute(
+                deps,
+                test_env(),
+                mock_info(ORIGINATOR, &funds),
+                ExecuteMsg::ProposePaydownAndSell {
+                    id: PAYDOWN_ID_1.into(),
+                    assets: vec![ASSET_ID_1.into()],
+                    total_paydown: Uint128::new(100),
+                    buyer: Addr::unchecked(BUYER),
+                    purchase_price: 200,
+                },
+            )
+        };
+        match propose(deps.as_mut(), 0) {
+            Err(ContractError::MissingPaydownFunds {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match propose(deps.as_mut(), 99) {
+            Err(ContractError::InsufficientPaydownFunds { need, received, .. }) => {
+                assert_eq!(need, 100);
+                assert_eq!(received, 99);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        let response = propose(deps.as_mut(), 100).unwrap();
+        assert_eq!(messages(response), vec![send(ESCROW_MARKER, 100)]);
+
+        // the warehouse accepts without sending funds
+        let response = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(WAREHOUSE, &[]),
+            ExecuteMsg::AcceptPaydown {
+                id: PAYDOWN_ID_1.into(),
+            },
+        )
+        .unwrap();
+        assert!(messages(response).is_empty());
+
+        // the buyer escrows the purchase price when accepting
+        match execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(BUYER, &[]),
+            ExecuteMsg::AcceptPaydown {
+                id: PAYDOWN_ID_1.into(),
+            },
+        ) {
+            Err(ContractError::MissingPurchaseFunds {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        let response = accept_sale(deps.as_mut(), 200).unwrap();
+        assert_eq!(messages(response), vec![send(ESCROW_MARKER, 200)]);
+
+        // both escrows are released on execution
+        let response = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePaydown {
+                id: PAYDOWN_ID_1.into(),
+            },
+        )
+        .unwrap();
+        let released = messages(response);
+        assert_eq!(
+            released[..2],
+            [
+                withdraw(100, STABLECOIN_DENOM, WAREHOUSE),
+                withdraw(200, STABLECOIN_DENOM, ORIGINATOR)
+            ]
+        );
+    }
+
+    #[test]
+    pub fn accept_paydown_with_insufficient_purchase_funds() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_sale(deps.as_mut(), 200);
+
+        match accept_sale(deps.as_mut(), 199) {
+            Err(ContractError::InsufficientPurchaseFunds { need, received, .. }) => {
+                assert_eq!(need, 200);
+                assert_eq!(received, 199);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn accept_paydown_with_exact_purchase_funds() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_sale(deps.as_mut(), 200);
+
+        let response = accept_sale(deps.as_mut(), 200).unwrap();
+        assert_eq!(messages(response), vec![send(ESCROW_MARKER, 200)]);
+    }
+
+    #[test]
+    pub fn accept_paydown_refunds_surplus_purchase_funds() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_sale(deps.as_mut(), 200);
+
+        let response = accept_sale(deps.as_mut(), 250).unwrap();
+        assert_eq!(
+            messages(response),
+            vec![send(ESCROW_MARKER, 200), send(BUYER, 50)]
+        );
+    }
+
+    // Set up a facility that accepts funds in two stablecoin denoms.
+    fn instantiate_facility_with_two_stablecoins(deps: DepsMut) {
+        let mut msg = instantiate_msg();
+        msg.facility.stablecoin_denoms = vec![STABLECOIN_DENOM.into(), STABLECOIN_DENOM_2.into()];
+        instantiate(deps, test_env(), mock_info(ADMIN, &[]), msg).unwrap();
+    }
+
+    fn send_in(to_address: &str, amount: u128, denom: &str) -> CosmosMsg<ProvenanceMsg> {
+        BankMsg::Send {
+            to_address: to_address.into(),
+            amount: coins(amount, denom),
+        }
+        .into()
+    }
+
+    fn withdraw(amount: u128, denom: &str, recipient: &str) -> CosmosMsg<ProvenanceMsg> {
+        withdraw_coins(ESCROW_DENOM, amount, denom, Addr::unchecked(recipient)).unwrap()
+    }
+
+    #[test]
+    pub fn handlers_report_action_attributes_and_funds() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let response = try_propose_pledge(deps.as_mut()).unwrap();
+        assert_eq!(response.attributes, vec![attr("action", "propose_pledge")]);
+
+        let response = accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        assert_eq!(response.attributes, vec![attr("action", "accept_pledge")]);
+        assert_eq!(messages(response), vec![send(ESCROW_MARKER, 100)]);
+
+        let response = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(response.attributes, vec![attr("action", "execute_pledge")]);
+        assert_eq!(
+            messages(response),
+            vec![withdraw(100, STABLECOIN_DENOM, ORIGINATOR)]
+        );
+
+        let response = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &coins(100, STABLECOIN_DENOM)),
+            ExecuteMsg::ProposePaydown {
+                id: PAYDOWN_ID_1.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_paydown: Uint128::new(100),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", "propose_paydown"),
+                attr("affected_pledges", PLEDGE_ID_1),
+            ]
+        );
+        assert_eq!(messages(response), vec![send(ESCROW_MARKER, 100)]);
+
+        let response = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(WAREHOUSE, &[]),
+            ExecuteMsg::AcceptPaydown {
+                id: PAYDOWN_ID_1.into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(response.attributes, vec![attr("action", "accept_paydown")]);
+
+        let response = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePaydown {
+                id: PAYDOWN_ID_1.into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", "execute_paydown"),
+                attr("affected_pledges", PLEDGE_ID_1),
+                attr("closed_pledges", PLEDGE_ID_1),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn accept_pledge_in_either_stablecoin_denom() {
+        for denom in [STABLECOIN_DENOM, STABLECOIN_DENOM_2] {
+            let mut deps = mock_dependencies(&[]);
+            deps.querier
+                .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+            instantiate_facility_with_two_stablecoins(deps.as_mut());
+            propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+
+            let response = execute(
+                deps.as_mut(),
+                test_env(),
+                mock_info(WAREHOUSE, &coins(150, denom)),
+                ExecuteMsg::AcceptPledge {
+                    id: PLEDGE_ID_1.into(),
+                },
+            )
+            .unwrap();
+            assert_eq!(
+                messages(response),
+                vec![
+                    send_in(ESCROW_MARKER, 100, denom),
+                    send_in(WAREHOUSE, 50, denom)
+                ]
+            );
+            assert_eq!(
+                get_pledge(deps.as_ref(), PLEDGE_ID_1).advance_denom,
+                Some(denom.to_string())
+            );
+
+            // the advance is disbursed to the originator in the same denom
+            let response = execute(
+                deps.as_mut(),
+                test_env(),
+                mock_info(ORIGINATOR, &[]),
+                ExecuteMsg::ExecutePledge {
+                    id: PLEDGE_ID_1.into(),
+                },
+            )
+            .unwrap();
+            assert_eq!(messages(response), vec![withdraw(100, denom, ORIGINATOR)]);
+        }
+    }
+
+    #[test]
+    pub fn execute_pledge_with_insufficient_escrow() {
+        let mut deps = mock_dependencies(&[]);
+        let mut escrow_marker = marker(ESCROW_MARKER, ESCROW_DENOM);
+        escrow_marker.coins = coins(99, STABLECOIN_DENOM);
+        deps.querier.with_markers(vec![escrow_marker]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+
+        let error = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(error, ContractError::InsufficientEscrow {}));
+
+        // the pledge is left accepted
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Accepted
+        );
+    }
+
+    #[test]
+    pub fn execute_pledge_disburses_advance_once() {
+        let mut deps = instantiated_test_deps();
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+
+        let response = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+
+        // the advance leaves escrow exactly once, withdrawn from the escrow marker to the
+        // originator, and no bank send from the contract's own balance duplicates it
+        let disbursements = messages(response);
+        assert_eq!(
+            disbursements,
+            vec![withdraw(
+                get_pledge(deps.as_ref(), PLEDGE_ID_1).total_advance.u128(),
+                STABLECOIN_DENOM,
+                ORIGINATOR
+            )]
+        );
+        assert!(!disbursements
+            .iter()
+            .any(|message| matches!(message, CosmosMsg::Bank(_))));
+    }
+
+    #[test]
+    pub fn cancel_accepted_pledge_refunds_advance_denom() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_facility_with_two_stablecoins(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(WAREHOUSE, &coins(100, STABLECOIN_DENOM_2)),
+            ExecuteMsg::AcceptPledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+
+        let response = cancel_pledge(deps.as_mut(), ORIGINATOR).unwrap();
+        assert_eq!(
+            messages(response),
+            vec![withdraw(100, STABLECOIN_DENOM_2, WAREHOUSE)]
+        );
+    }
+
+    #[test]
+    pub fn accept_pledge_with_unconfigured_stablecoin_denom() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_facility_with_two_stablecoins(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(WAREHOUSE, &coins(100, "test.denom.other")),
+            ExecuteMsg::AcceptPledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        );
+        match result {
+            Err(ContractError::InsufficientPledgeAdvanceFunds {
+                need_denom,
+                received_denom,
+                ..
+            }) => {
+                assert_eq!(need_denom, STABLECOIN_DENOM);
+                assert_eq!(received_denom, "test.denom.other");
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn paydown_and_sale_in_different_stablecoin_denoms() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.with_markers(vec![
+            marker(ESCROW_MARKER, ESCROW_DENOM),
+            marker("asset_marker_1", &asset_marker_denom(PLEDGE_ID_1)),
+        ]);
+        instantiate_facility_with_two_stablecoins(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+
+        // the originator pays down in the second denom
+        let response = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &coins(100, STABLECOIN_DENOM_2)),
+            ExecuteMsg::ProposePaydownAndSell {
+                id: PAYDOWN_ID_1.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_paydown: Uint128::new(100),
+                buyer: Addr::unchecked(BUYER),
+                purchase_price: 200,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            messages(response),
+            vec![send_in(ESCROW_MARKER, 100, STABLECOIN_DENOM_2)]
+        );
+
+        // the buyer purchases in the first denom
+        accept_sale_by_warehouse(deps.as_mut());
+        let response = accept_sale(deps.as_mut(), 200).unwrap();
+        assert_eq!(
+            messages(response),
+            vec![send_in(ESCROW_MARKER, 200, STABLECOIN_DENOM)]
+        );
+
+        let paydown = get_paydown(deps.as_ref(), PAYDOWN_ID_1);
+        assert_eq!(paydown.paydown_denom, Some(STABLECOIN_DENOM_2.to_string()));
+        assert_eq!(paydown.purchase_denom, Some(STABLECOIN_DENOM.to_string()));
+
+        // each amount is disbursed in the denom it was paid in
+        let response = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePaydown {
+                id: PAYDOWN_ID_1.into(),
+            },
+        )
+        .unwrap();
+        let messages = messages(response);
+        assert_eq!(messages[0], withdraw(100, STABLECOIN_DENOM_2, WAREHOUSE));
+        assert_eq!(messages[1], withdraw(200, STABLECOIN_DENOM, ORIGINATOR));
+    }
+
+    #[test]
+    pub fn list_escrowed_pledges_with_mixed_states() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        // executed pledge
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+
+        // accepted pledge
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 250);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_2, 250).unwrap();
+
+        // proposed pledge
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_3, &[ASSET_ID_3], 400);
+
+        let response: EscrowedPledgesResponse = from_binary(
+            &query(deps.as_ref(), test_env(), QueryMsg::ListEscrowedPledges {}).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(response.pledges.len(), 1);
+        assert_eq!(response.pledges[0].id, PLEDGE_ID_2);
+        assert_eq!(response.total_escrowed, Uint128::from(250u128));
+    }
+
+    #[test]
+    pub fn get_escrow_balance_compares_balance_to_pledges() {
+        let mut deps = mock_dependencies(&[]);
+        let mut escrow_marker = marker(ESCROW_MARKER, ESCROW_DENOM);
+        escrow_marker.coins = vec![coin(300, STABLECOIN_DENOM), coin(50, STABLECOIN_DENOM_2)];
+        deps.querier.with_markers(vec![escrow_marker]);
+        instantiate_facility_with_two_stablecoins(deps.as_mut());
+
+        // accepted pledges in each denom
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 250);
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(WAREHOUSE, &coins(250, STABLECOIN_DENOM_2)),
+            ExecuteMsg::AcceptPledge {
+                id: PLEDGE_ID_2.into(),
+            },
+        )
+        .unwrap();
+
+        // proposed pledge (nothing escrowed)
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_3, &[ASSET_ID_3], 400);
+
+        // the balance is held by the escrow marker account, not the contract
+        let response: EscrowBalanceResponse =
+            from_binary(&query(deps.as_ref(), test_env(), QueryMsg::GetEscrowBalance {}).unwrap())
+                .unwrap();
+        assert_eq!(
+            response.balances,
+            vec![
+                EscrowBalance {
+                    denom: STABLECOIN_DENOM.into(),
+                    balance: Uint128::new(300),
+                    total_escrowed: Uint128::new(100),
+                },
+                EscrowBalance {
+                    denom: STABLECOIN_DENOM_2.into(),
+                    balance: Uint128::new(50),
+                    total_escrowed: Uint128::new(250),
+                },
+            ]
+        );
+    }
+
+    fn get_pledge(deps: Deps, id: &str) -> Pledge {
+        from_binary(&query(deps, test_env(), QueryMsg::GetPledge { id: id.into() }).unwrap())
+            .unwrap()
+    }
+
+    fn get_paydown(deps: Deps, id: &str) -> Paydown {
+        from_binary(&query(deps, test_env(), QueryMsg::GetPaydown { id: id.into() }).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    pub fn get_pledges_with_context() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_2, 100).unwrap();
+
+        let response: PledgesWithContextResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                test_env(),
+                QueryMsg::GetPledgesWithContext {},
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let contract_info = get_contract_info(deps.as_ref().storage).unwrap();
+        assert_eq!(response.originator, contract_info.facility.originator);
+        assert_eq!(response.warehouse, contract_info.facility.warehouse);
+        assert_eq!(
+            response.pledges,
+            vec![
+                get_pledge(deps.as_ref(), PLEDGE_ID_1),
+                get_pledge(deps.as_ref(), PLEDGE_ID_2),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn get_pledge_and_paydown_state() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1, ASSET_ID_2], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+
+        let pledge_state = |deps: Deps, id: &str| -> StdResult<PledgeStateResponse> {
+            from_binary(&query(
+                deps,
+                test_env(),
+                QueryMsg::GetPledgeState { id: id.into() },
+            )?)
+        };
+        assert_eq!(
+            pledge_state(deps.as_ref(), PLEDGE_ID_1).unwrap(),
+            PledgeStateResponse {
+                id: PLEDGE_ID_1.into(),
+                state: PledgeState::Accepted,
+            }
+        );
+        assert!(pledge_state(deps.as_ref(), PLEDGE_ID_2).is_err());
+
+        // the state follows the pledge through its execution and paydown
+        let steps = vec![
+            (
+                vec![],
+                ExecuteMsg::ExecutePledge {
+                    id: PLEDGE_ID_1.into(),
+                },
+            ),
+            (
+                coins(50, STABLECOIN_DENOM),
+                ExecuteMsg::ProposePaydown {
+                    id: PAYDOWN_ID_1.into(),
+                    assets: vec![ASSET_ID_1.into()],
+                    total_paydown: Uint128::new(50),
+                },
+            ),
+        ];
+        for (funds, msg) in steps {
+            execute(
+                deps.as_mut(),
+                test_env(),
+                mock_info(ORIGINATOR, &funds),
+                msg,
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            pledge_state(deps.as_ref(), PLEDGE_ID_1).unwrap().state,
+            PledgeState::Executed
+        );
+
+        let paydown_state: PaydownStateResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                test_env(),
+                QueryMsg::GetPaydownState {
+                    id: PAYDOWN_ID_1.into(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            paydown_state,
+            PaydownStateResponse {
+                id: PAYDOWN_ID_1.into(),
+                state: PaydownState::Proposed,
+            }
+        );
+    }
+
+    #[test]
+    pub fn pledge_timestamps_follow_block_time() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        execute(
+            deps.as_mut(),
+            test_env_at(1000),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ProposePledge {
+                id: PLEDGE_ID_1.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(PLEDGE_ID_1),
+                valid_until: None,
+                document_hash: None,
+            },
+        )
+        .unwrap();
+        let pledge = get_pledge(deps.as_ref(), PLEDGE_ID_1);
+        assert_eq!(pledge.proposed_at, Timestamp::from_seconds(1000));
+        assert_eq!(pledge.accepted_at, None);
+        assert_eq!(pledge.executed_at, None);
+
+        execute(
+            deps.as_mut(),
+            test_env_at(2000),
+            mock_info(WAREHOUSE, &coins(100, STABLECOIN_DENOM)),
+            ExecuteMsg::AcceptPledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            test_env_at(3000),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+        let pledge = get_pledge(deps.as_ref(), PLEDGE_ID_1);
+        assert_eq!(pledge.proposed_at, Timestamp::from_seconds(1000));
+        assert_eq!(pledge.accepted_at, Some(Timestamp::from_seconds(2000)));
+        assert_eq!(pledge.executed_at, Some(Timestamp::from_seconds(3000)));
+    }
+
+    #[test]
+    pub fn paydown_timestamps_follow_block_time() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.with_markers(vec![
+            marker(ESCROW_MARKER, ESCROW_DENOM),
+            marker("asset_marker_1", &asset_marker_denom(PLEDGE_ID_1)),
+        ]);
+        instantiate_test_contract(deps.as_mut());
+        propose_sale(deps.as_mut(), 200);
+
+        let paydown = get_paydown(deps.as_ref(), PAYDOWN_ID_1);
+        assert_eq!(paydown.proposed_at, test_env().block.time);
+        assert_eq!(paydown.accepted_at, None);
+
+        // the paydown is only accepted once both the warehouse and the buyer accept
+        execute(
+            deps.as_mut(),
+            test_env_at(4000),
+            mock_info(WAREHOUSE, &[]),
+            ExecuteMsg::AcceptPaydown {
+                id: PAYDOWN_ID_1.into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(get_paydown(deps.as_ref(), PAYDOWN_ID_1).accepted_at, None);
+
+        execute(
+            deps.as_mut(),
+            test_env_at(5000),
+            mock_info(BUYER, &coins(200, STABLECOIN_DENOM)),
+            ExecuteMsg::AcceptPaydown {
+                id: PAYDOWN_ID_1.into(),
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            test_env_at(6000),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePaydown {
+                id: PAYDOWN_ID_1.into(),
+            },
+        )
+        .unwrap();
+        let paydown = get_paydown(deps.as_ref(), PAYDOWN_ID_1);
+        assert_eq!(paydown.accepted_at, Some(Timestamp::from_seconds(5000)));
+        assert_eq!(paydown.executed_at, Some(Timestamp::from_seconds(6000)));
+    }
+
+    fn asset_state(deps: Deps, id: &str) -> Option<AssetState> {
+        let assets: Vec<Asset> =
+            from_binary(&query(deps, test_env(), QueryMsg::ListAssets {}).unwrap()).unwrap();
+        assets
+            .into_iter()
+            .find(|asset| asset.id == id)
+            .map(|asset| asset.state)
+    }
+
+    fn assets_by_state(deps: Deps, state: AssetState) -> Vec<String> {
+        let assets: Vec<Asset> =
+            from_binary(&query(deps, test_env(), QueryMsg::ListAssetsByState { state }).unwrap())
+                .unwrap();
+        assets.into_iter().map(|asset| asset.id).collect()
+    }
+
+    #[test]
+    pub fn list_assets_by_state() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        // one pledge proposed, and another executed with one of its assets proposed for paydown
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2, ASSET_ID_3], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_2, 100).unwrap();
+        let steps = vec![
+            (
+                vec![],
+                ExecuteMsg::ExecutePledge {
+                    id: PLEDGE_ID_2.into(),
+                },
+            ),
+            (
+                coins(50, STABLECOIN_DENOM),
+                ExecuteMsg::ProposePaydown {
+                    id: PAYDOWN_ID_1.into(),
+                    assets: vec![ASSET_ID_3.into()],
+                    total_paydown: Uint128::new(50),
+                },
+            ),
+        ];
+        for (funds, msg) in steps {
+            execute(
+                deps.as_mut(),
+                test_env(),
+                mock_info(ORIGINATOR, &funds),
+                msg,
+            )
+            .unwrap();
+        }
+
+        assert_eq!(
+            assets_by_state(deps.as_ref(), AssetState::PledgeProposed),
+            vec![ASSET_ID_1]
+        );
+        assert_eq!(
+            assets_by_state(deps.as_ref(), AssetState::Inventory),
+            vec![ASSET_ID_2]
+        );
+        assert_eq!(
+            assets_by_state(deps.as_ref(), AssetState::PaydownProposed),
+            vec![ASSET_ID_3]
+        );
+        assert!(assets_by_state(deps.as_ref(), AssetState::PaydownAccepted).is_empty());
+    }
+
+    fn all_scopes(deps: Deps) -> Vec<String> {
+        from_binary(&query(deps, test_env(), QueryMsg::ListAllScopes {}).unwrap()).unwrap()
+    }
+
+    #[test]
+    pub fn list_all_scopes_across_pledges() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        // a cancelled pledge of the first asset, given by its scope address
+        let asset_1 = scope_address(ASSET_ID_1);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[asset_1.as_str()], 100);
+        cancel_pledge(deps.as_mut(), ORIGINATOR).unwrap();
+
+        // live pledges of the same asset again and of the other assets
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_1, ASSET_ID_2], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_3, &[ASSET_ID_3], 100);
+
+        let mut expected = vec![
+            scope_address(ASSET_ID_1),
+            scope_address(ASSET_ID_2),
+            scope_address(ASSET_ID_3),
+        ];
+        expected.sort();
+        assert_eq!(all_scopes(deps.as_ref()), expected);
+    }
+
+    fn inventory(deps: Deps) -> Vec<String> {
+        from_binary(&query(deps, test_env(), QueryMsg::ListInventory {}).unwrap()).unwrap()
+    }
+
+    fn accept_sale_by_warehouse(deps: DepsMut) {
+        execute(
+            deps,
+            test_env(),
+            mock_info(WAREHOUSE, &[]),
+            ExecuteMsg::AcceptPaydown {
+                id: PAYDOWN_ID_1.into(),
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    pub fn asset_state_through_executed_paydown() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.with_markers(vec![
+            marker(ESCROW_MARKER, ESCROW_DENOM),
+            marker("asset_marker_1", &asset_marker_denom(PLEDGE_ID_1)),
+        ]);
+        instantiate_test_contract(deps.as_mut());
+
+        propose_sale(deps.as_mut(), 200);
+        assert_eq!(
+            asset_state(deps.as_ref(), ASSET_ID_1),
+            Some(AssetState::PaydownProposed)
+        );
+
+        // a partially accepted paydown leaves the asset proposed
+        accept_sale_by_warehouse(deps.as_mut());
+        assert_eq!(
+            asset_state(deps.as_ref(), ASSET_ID_1),
+            Some(AssetState::PaydownProposed)
+        );
+
+        accept_sale(deps.as_mut(), 200).unwrap();
+        assert_eq!(
+            asset_state(deps.as_ref(), ASSET_ID_1),
+            Some(AssetState::PaydownAccepted)
+        );
+        assert_eq!(inventory(deps.as_ref()), vec![ASSET_ID_1.to_string()]);
+
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePaydown {
+                id: PAYDOWN_ID_1.into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(asset_state(deps.as_ref(), ASSET_ID_1), None);
+        assert!(inventory(deps.as_ref()).is_empty());
+    }
+
+    #[test]
+    pub fn asset_state_through_cancelled_paydown() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        propose_sale(deps.as_mut(), 200);
+        accept_sale_by_warehouse(deps.as_mut());
+        accept_sale(deps.as_mut(), 200).unwrap();
+        assert_eq!(
+            asset_state(deps.as_ref(), ASSET_ID_1),
+            Some(AssetState::PaydownAccepted)
+        );
+
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::CancelPaydown {
+                id: PAYDOWN_ID_1.into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            asset_state(deps.as_ref(), ASSET_ID_1),
+            Some(AssetState::Inventory)
+        );
+        assert_eq!(inventory(deps.as_ref()), vec![ASSET_ID_1.to_string()]);
+    }
+
+    #[test]
+    pub fn instantiate_with_unrepresentable_advance_rate() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+
+        // parses and validates, but the marker supply for its scale can't be represented
+        let mut msg = instantiate_msg();
+        msg.facility.advance_rate = "0.0000000000000000000000000001".into();
+        match instantiate(deps.as_mut(), test_env(), mock_info(ADMIN, &[]), msg) {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new(
+                        "facility.advance_rate",
+                        "is too precise to represent as a facility marker supply"
+                    )]
+                );
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn calculate_marker_distribution_with_negative_advance_rate() {
+        let mut facility = instantiate_msg().facility;
+        facility.advance_rate = "-75.125".into();
+        match calculate_marker_distribution(&facility) {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new(
+                        "facility.advance_rate",
+                        "must be in (0,100]"
+                    )]
+                );
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn get_version_returns_contract_versions() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let version: ContractVersion =
+            from_binary(&query(deps.as_ref(), test_env(), QueryMsg::GetVersion {}).unwrap())
+                .unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+        assert_eq!(version.contract_version, CONTRACT_VERSION);
+        assert_eq!(version.contract_type, CONTRACT_TYPE);
+    }
+
+    fn facility_summary(deps: Deps) -> FacilitySummary {
+        from_binary(&query(deps, test_env(), QueryMsg::GetFacilitySummary {}).unwrap()).unwrap()
+    }
+
+    #[test]
+    pub fn facility_summary_through_pledge_lifecycle() {
+        let mut deps = instantiated_test_deps();
+
+        let summary = facility_summary(deps.as_ref());
+        assert_eq!(summary.facility, instantiate_msg().facility);
+        assert_eq!(summary.inventory_count, 0);
+        assert_eq!(summary.total_outstanding_advance, Uint128::zero());
+        assert_eq!(summary.open_proposal_count, 0);
+
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1, ASSET_ID_2], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_3], 50);
+        let summary = facility_summary(deps.as_ref());
+        assert_eq!(summary.inventory_count, 0);
+        assert_eq!(summary.open_proposal_count, 2);
+
+        // the accepted pledge's escrowed advance is outstanding before its assets are inventoried
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Accepted
+        );
+        let summary = facility_summary(deps.as_ref());
+        assert_eq!(summary.inventory_count, 0);
+        assert_eq!(summary.total_outstanding_advance, Uint128::from(100u128));
+        assert_eq!(summary.open_proposal_count, 1);
+
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+        let summary = facility_summary(deps.as_ref());
+        assert_eq!(summary.inventory_count, 2);
+        assert_eq!(summary.total_outstanding_advance, Uint128::from(100u128));
+        assert_eq!(summary.open_proposal_count, 1);
+
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &coins(100, STABLECOIN_DENOM)),
+            ExecuteMsg::ProposePaydown {
+                id: PAYDOWN_ID_1.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_paydown: Uint128::new(100),
+            },
+        )
+        .unwrap();
+        let summary = facility_summary(deps.as_ref());
+        assert_eq!(summary.inventory_count, 2);
+        assert_eq!(summary.open_proposal_count, 2);
+    }
+
+    #[test]
+    pub fn facility_summary_agrees_with_available_credit() {
+        let mut deps = instantiated_test_deps();
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 50);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+
+        // the advance escrowed for the accepted pledge is outstanding in both queries
+        let summary = facility_summary(deps.as_ref());
+        let credit = available_credit(deps.as_ref());
+        assert_eq!(summary.total_outstanding_advance, Uint128::new(100));
+        assert_eq!(summary.total_outstanding_advance, credit.outstanding);
+    }
+
+    #[test]
+    pub fn propose_paydown_with_assets_from_multiple_pledges() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        for (id, asset) in [(PLEDGE_ID_1, ASSET_ID_1), (PLEDGE_ID_2, ASSET_ID_2)] {
+            propose_test_pledge(deps.as_mut(), id, &[asset], 100);
+            accept_pledge(deps.as_mut(), id, 100).unwrap();
+            execute(
+                deps.as_mut(),
+                test_env(),
+                mock_info(ORIGINATOR, &[]),
+                ExecuteMsg::ExecutePledge { id: id.into() },
+            )
+            .unwrap();
+        }
+
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &coins(200, STABLECOIN_DENOM)),
+            ExecuteMsg::ProposePaydown {
+                id: PAYDOWN_ID_1.into(),
+                assets: vec![ASSET_ID_1.into(), ASSET_ID_2.into()],
+                total_paydown: Uint128::new(200),
+            },
+        );
+        match result {
+            Err(ContractError::PaydownSpansMultiplePledges {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn propose_paydown_with_assets_not_pledged() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        // an asset in the inventory that no executed pledge includes
+        set_assets_state(
+            deps.as_mut().storage,
+            AssetState::Inventory,
+            &[ASSET_ID_1.into()],
+            test_env().block.time,
+        )
+        .unwrap();
+
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &coins(100, STABLECOIN_DENOM)),
+            ExecuteMsg::ProposePaydown {
+                id: PAYDOWN_ID_1.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_paydown: Uint128::new(100),
+            },
+        );
+        match result {
+            Err(ContractError::PaydownAssetsNotPledged {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn asset_history_through_full_lifecycle() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.with_markers(vec![
+            marker(ESCROW_MARKER, ESCROW_DENOM),
+            marker("asset_marker_1", &asset_marker_denom(PLEDGE_ID_1)),
+        ]);
+        instantiate_test_contract(deps.as_mut());
+
+        let steps = vec![
+            (
+                1000,
+                ORIGINATOR,
+                vec![],
+                ExecuteMsg::ProposePledge {
+                    id: PLEDGE_ID_1.into(),
+                    assets: vec![ASSET_ID_1.into()],
+                    total_advance: Uint128::new(100),
+                    asset_marker_denom: asset_marker_denom(PLEDGE_ID_1),
+                    valid_until: None,
+                    document_hash: None,
+                },
+            ),
+            (
+                2000,
+                WAREHOUSE,
+                coins(100, STABLECOIN_DENOM),
+                ExecuteMsg::AcceptPledge {
+                    id: PLEDGE_ID_1.into(),
+                },
+            ),
+            (
+                3000,
+                ORIGINATOR,
+                vec![],
+                ExecuteMsg::ExecutePledge {
+                    id: PLEDGE_ID_1.into(),
+                },
+            ),
+            (
+                4000,
+                ORIGINATOR,
+                coins(100, STABLECOIN_DENOM),
+                ExecuteMsg::ProposePaydown {
+                    id: PAYDOWN_ID_1.into(),
+                    assets: vec![ASSET_ID_1.into()],
+                    total_paydown: Uint128::new(100),
+                },
+            ),
+            (
+                5000,
+                WAREHOUSE,
+                vec![],
+                ExecuteMsg::AcceptPaydown {
+                    id: PAYDOWN_ID_1.into(),
+                },
+            ),
+            (
+                6000,
+                ORIGINATOR,
+                vec![],
+                ExecuteMsg::ExecutePaydown {
+                    id: PAYDOWN_ID_1.into(),
+                },
+            ),
+        ];
+        for (seconds, sender, funds, msg) in steps {
+            execute(
+                deps.as_mut(),
+                test_env_at(seconds),
+                mock_info(sender, &funds),
+                msg,
+            )
+            .unwrap();
+        }
+
+        let history: Vec<AssetTransition> = from_binary(
+            &query(
+                deps.as_ref(),
+                test_env(),
+                QueryMsg::GetAssetHistory {
+                    id: ASSET_ID_1.into(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let transition = |from, to, seconds| AssetTransition {
+            from,
+            to,
+            at: Timestamp::from_seconds(seconds),
+        };
+        assert_eq!(
+            history,
+            vec![
+                transition(None, Some(AssetState::PledgeProposed), 1000),
+                transition(
+                    Some(AssetState::PledgeProposed),
+                    Some(AssetState::Inventory),
+                    3000
+                ),
+                transition(
+                    Some(AssetState::Inventory),
+                    Some(AssetState::PaydownProposed),
+                    4000
+                ),
+                transition(
+                    Some(AssetState::PaydownProposed),
+                    Some(AssetState::PaydownAccepted),
+                    5000
+                ),
+                transition(Some(AssetState::PaydownAccepted), None, 6000),
+            ]
+        );
+    }
+
+    fn cancel_pledge(
+        deps: DepsMut,
+        sender: &str,
+    ) -> Result<Response<ProvenanceMsg>, ContractError> {
+        execute(
+            deps,
+            test_env(),
+            mock_info(sender, &[]),
+            ExecuteMsg::CancelPledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+    }
+
+    #[test]
+    pub fn cancel_proposed_pledge_by_either_party() {
+        for sender in [ORIGINATOR, WAREHOUSE] {
+            let mut deps = mock_dependencies(&[]);
+            deps.querier
+                .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+            instantiate_test_contract(deps.as_mut());
+
+            // a missing pledge is reported as such to either party
+            match cancel_pledge(deps.as_mut(), sender) {
+                Err(ContractError::PledgeNotFound { id }) => assert_eq!(id, PLEDGE_ID_1),
+                result => panic!("unexpected result: {:?}", result),
+            }
+
+            propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+            cancel_pledge(deps.as_mut(), sender).unwrap();
+            assert_eq!(
+                get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+                PledgeState::Cancelled
+            );
+        }
+    }
+
+    #[test]
+    pub fn cancel_accepted_pledge_by_originator_only() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+
+        match cancel_pledge(deps.as_mut(), WAREHOUSE) {
+            Err(ContractError::Unauthorized {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match cancel_pledge(deps.as_mut(), BUYER) {
+            Err(ContractError::Unauthorized {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        cancel_pledge(deps.as_mut(), ORIGINATOR).unwrap();
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Cancelled
+        );
+    }
+
+    fn repropose_pledge(
+        deps: DepsMut,
+        assets: &[&str],
+        total_advance: u128,
+    ) -> Result<Response<ProvenanceMsg>, ContractError> {
+        execute(
+            deps,
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ReproposePledge {
+                id: PLEDGE_ID_1.into(),
+                assets: assets.iter().map(|asset| asset.to_string()).collect(),
+                total_advance: total_advance.into(),
+                asset_marker_denom: asset_marker_denom(PLEDGE_ID_1),
+                valid_until: None,
+                document_hash: None,
+            },
+        )
+    }
+
+    #[test]
+    pub fn repropose_cancelled_or_rejected_pledge() {
+        for sender in [ORIGINATOR, WAREHOUSE] {
+            let mut deps = mock_dependencies(&[]);
+            deps.querier.with_markers(vec![
+                marker(ESCROW_MARKER, ESCROW_DENOM),
+                marker("asset_marker_1", &asset_marker_denom(PLEDGE_ID_1)),
+            ]);
+            instantiate_test_contract(deps.as_mut());
+            propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+
+            // the originator cancels the pledge, or the warehouse rejects it
+            match sender {
+                ORIGINATOR => cancel_pledge(deps.as_mut(), sender).unwrap(),
+                _ => reject_pledge(deps.as_mut(), sender, None).unwrap(),
+            };
+
+            // the cancel or reject destroys the asset marker
+            deps.querier
+                .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+
+            let response = repropose_pledge(deps.as_mut(), &[ASSET_ID_1, ASSET_ID_2], 250).unwrap();
+            assert_eq!(
+                response.attributes,
+                vec![attr("action", "repropose_pledge")]
+            );
+            assert_eq!(response.messages.len(), 5);
+
+            let pledge = get_pledge(deps.as_ref(), PLEDGE_ID_1);
+            assert_eq!(pledge.state, PledgeState::Proposed);
+            assert_eq!(pledge.assets, vec![ASSET_ID_1, ASSET_ID_2]);
+            assert_eq!(pledge.total_advance, Uint128::new(250));
+            assert_eq!(
+                asset_state(deps.as_ref(), ASSET_ID_2),
+                Some(AssetState::PledgeProposed)
+            );
+        }
+    }
+
+    #[test]
+    pub fn repropose_pledge_with_deadline_and_document_hash() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        cancel_pledge(deps.as_mut(), ORIGINATOR).unwrap();
+
+        let valid_until = test_env().block.time.plus_seconds(60);
+        let hash = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        let response = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ReproposePledge {
+                id: PLEDGE_ID_1.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(PLEDGE_ID_1),
+                valid_until: Some(valid_until),
+                document_hash: Some(hash.into()),
+            },
+        )
+        .unwrap();
+        assert!(response.attributes.contains(&attr("document_hash", hash)));
+
+        let pledge = get_pledge(deps.as_ref(), PLEDGE_ID_1);
+        assert_eq!(pledge.state, PledgeState::Proposed);
+        assert_eq!(pledge.valid_until, Some(valid_until));
+        assert_eq!(pledge.document_hash, Some(hash.into()));
+    }
+
+    #[test]
+    pub fn repropose_pledge_before_asset_marker_destroyed() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.with_markers(vec![
+            marker(ESCROW_MARKER, ESCROW_DENOM),
+            marker("asset_marker_1", &asset_marker_denom(PLEDGE_ID_1)),
+        ]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        cancel_pledge(deps.as_mut(), ORIGINATOR).unwrap();
+
+        // the asset marker still exists (ex: the cancel only partially applied)
+        match repropose_pledge(deps.as_mut(), &[ASSET_ID_1], 100) {
+            Err(ContractError::AssetMarkerStillExists { denom }) => {
+                assert_eq!(denom, asset_marker_denom(PLEDGE_ID_1))
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Cancelled
+        );
+    }
+
+    #[test]
+    pub fn repropose_live_pledge() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        match repropose_pledge(deps.as_mut(), &[ASSET_ID_1], 100) {
+            Err(ContractError::PledgeNotFound { id }) => assert_eq!(id, PLEDGE_ID_1),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // proposed
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        match repropose_pledge(deps.as_mut(), &[ASSET_ID_1], 100) {
+            Err(ContractError::StateError { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // accepted
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        match repropose_pledge(deps.as_mut(), &[ASSET_ID_1], 100) {
+            Err(ContractError::StateError { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Accepted
+        );
+    }
+
+    fn amend_pledge(
+        deps: DepsMut,
+        sender: &str,
+        assets: Option<&[&str]>,
+        total_advance: Option<u128>,
+    ) -> Result<Response<ProvenanceMsg>, ContractError> {
+        execute(
+            deps,
+            test_env(),
+            mock_info(sender, &[]),
+            ExecuteMsg::AmendPledge {
+                id: PLEDGE_ID_1.into(),
+                assets: assets.map(|assets| assets.iter().map(|asset| asset.to_string()).collect()),
+                total_advance: total_advance.map(Uint128::new),
+            },
+        )
+    }
+
+    #[test]
+    pub fn amend_pledge_adds_and_removes_assets() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1, ASSET_ID_2], 100);
+
+        amend_pledge(
+            deps.as_mut(),
+            ORIGINATOR,
+            Some(&[ASSET_ID_3, ASSET_ID_2]),
+            Some(150),
+        )
+        .unwrap();
+
+        let pledge = get_pledge(deps.as_ref(), PLEDGE_ID_1);
+        assert_eq!(pledge.assets, vec![ASSET_ID_2, ASSET_ID_3]);
+        assert_eq!(pledge.total_advance, Uint128::new(150));
+        assert_eq!(pledge.state, PledgeState::Proposed);
+
+        // the removed asset is freed and the added asset is claimed
+        assert_eq!(asset_state(deps.as_ref(), ASSET_ID_1), None);
+        assert_eq!(
+            asset_state(deps.as_ref(), ASSET_ID_2),
+            Some(AssetState::PledgeProposed)
+        );
+        assert_eq!(
+            asset_state(deps.as_ref(), ASSET_ID_3),
+            Some(AssetState::PledgeProposed)
+        );
+
+        // the freed asset can be pledged again, and the amended advance is the one to accept
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_1], 100);
+        match accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100) {
+            Err(ContractError::InsufficientPledgeAdvanceFunds { need, .. }) => {
+                assert_eq!(need, 150)
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 150).unwrap();
+    }
+
+    #[test]
+    pub fn amend_pledge_with_assets_already_pledged() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 100);
+
+        match amend_pledge(
+            deps.as_mut(),
+            ORIGINATOR,
+            Some(&[ASSET_ID_1, ASSET_ID_2]),
+            None,
+        ) {
+            Err(ContractError::AssetsAlreadyPledged {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).assets,
+            vec![ASSET_ID_1]
+        );
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_2).assets,
+            vec![ASSET_ID_2]
+        );
+    }
+
+    #[test]
+    pub fn amend_pledge_only_while_proposed() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        match amend_pledge(deps.as_mut(), ORIGINATOR, None, Some(150)) {
+            Err(ContractError::PledgeNotFound { id }) => assert_eq!(id, PLEDGE_ID_1),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // proposed
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        match amend_pledge(deps.as_mut(), WAREHOUSE, None, Some(150)) {
+            Err(ContractError::Unauthorized {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        amend_pledge(deps.as_mut(), ORIGINATOR, None, Some(150)).unwrap();
+
+        // accepted
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 150).unwrap();
+        match amend_pledge(deps.as_mut(), ORIGINATOR, Some(&[ASSET_ID_2]), None) {
+            Err(ContractError::StateError { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        let pledge = get_pledge(deps.as_ref(), PLEDGE_ID_1);
+        assert_eq!(pledge.assets, vec![ASSET_ID_1]);
+        assert_eq!(pledge.total_advance, Uint128::new(150));
+        assert_eq!(asset_state(deps.as_ref(), ASSET_ID_2), None);
+    }
+
+    fn reject_pledge(
+        deps: DepsMut,
+        sender: &str,
+        reason: Option<&str>,
+    ) -> Result<Response<ProvenanceMsg>, ContractError> {
+        execute(
+            deps,
+            test_env(),
+            mock_info(sender, &[]),
+            ExecuteMsg::RejectPledge {
+                id: PLEDGE_ID_1.into(),
+                reason: reason.map(|reason| reason.into()),
+            },
+        )
+    }
+
+    #[test]
+    pub fn reject_proposed_pledge_by_warehouse_only() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.with_markers(vec![
+            marker(ESCROW_MARKER, ESCROW_DENOM),
+            marker("asset_marker_1", &asset_marker_denom(PLEDGE_ID_1)),
+        ]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+
+        match reject_pledge(deps.as_mut(), ORIGINATOR, None) {
+            Err(ContractError::Unauthorized {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let response = reject_pledge(deps.as_mut(), WAREHOUSE, Some("over concentration")).unwrap();
+        assert_eq!(response.messages.len(), 3);
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", "reject_pledge"),
+                attr("reason", "over concentration")
+            ]
+        );
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Rejected
+        );
+        assert_eq!(asset_state(deps.as_ref(), ASSET_ID_1), None);
+    }
+
+    #[test]
+    pub fn rejected_pledge_is_terminal() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        reject_pledge(deps.as_mut(), WAREHOUSE, None).unwrap();
+
+        match cancel_pledge(deps.as_mut(), ORIGINATOR) {
+            Err(ContractError::StateError { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let pledges: Vec<Pledge> = from_binary(
+            &query(
+                deps.as_ref(),
+                test_env(),
+                QueryMsg::ListPledgesByStates {
+                    states: vec![PledgeState::Rejected],
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(pledges.len(), 1);
+        assert_eq!(pledges[0].id, PLEDGE_ID_1);
+    }
+
+    #[test]
+    pub fn get_pledge_raw_matches_stored_bytes() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+
+        let raw: Binary = from_binary(
+            &query(
+                deps.as_ref(),
+                test_env(),
+                QueryMsg::GetPledgeRaw {
+                    id: PLEDGE_ID_1.into(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        // compose the storage key: 2-byte namespace length, namespace, pledge id
+        let mut key = vec![0u8, 7u8];
+        key.extend_from_slice(b"pledges");
+        key.extend_from_slice(PLEDGE_ID_1.as_bytes());
+        assert_eq!(raw.to_vec(), deps.storage.get(&key).unwrap());
+        assert_eq!(
+            from_binary::<Pledge>(&raw).unwrap(),
+            get_pledge(deps.as_ref(), PLEDGE_ID_1)
+        );
+    }
+
+    #[test]
+    pub fn get_pledge_raw_with_unknown_id() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let result = query(
+            deps.as_ref(),
+            test_env(),
+            QueryMsg::GetPledgeRaw {
+                id: PLEDGE_ID_1.into(),
+            },
+        );
+        match result {
+            Err(StdError::NotFound { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn propose_pledge_with_mismatched_marker_denom() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ProposePledge {
+                id: PLEDGE_ID_1.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(PLEDGE_ID_2),
+                valid_until: None,
+                document_hash: None,
+            },
+        );
+        match result {
+            Err(ContractError::InvalidAssetMarkerDenom { expected }) => {
+                assert_eq!(expected, format!("{}.pledge.{}", MARKER_DENOM, PLEDGE_ID_1))
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn propose_pledge_with_facility_marker_denom() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ProposePledge {
+                id: PLEDGE_ID_1.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: MARKER_DENOM.into(),
+                valid_until: None,
+                document_hash: None,
+            },
+        );
+        match result {
+            Err(ContractError::AssetMarkerDenomInUse { denom, used_by }) => {
+                assert_eq!(denom, MARKER_DENOM);
+                assert_eq!(used_by, "the facility marker");
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn propose_pledge_with_denom_of_existing_pledge() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ProposePledge {
+                id: PLEDGE_ID_2.into(),
+                assets: vec![ASSET_ID_2.into()],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(PLEDGE_ID_1),
+                valid_until: None,
+                document_hash: None,
+            },
+        );
+        match result {
+            Err(ContractError::DenomAlreadyUsed { denom }) => {
+                assert_eq!(denom, asset_marker_denom(PLEDGE_ID_1));
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn propose_pledge_with_denom_of_closed_pledge() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        // pledge the asset, then pay it down to close the pledge
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        let steps = vec![
+            (
+                ORIGINATOR,
+                vec![],
+                ExecuteMsg::ExecutePledge {
+                    id: PLEDGE_ID_1.into(),
+                },
+            ),
+            (
+                ORIGINATOR,
+                coins(100, STABLECOIN_DENOM),
+                ExecuteMsg::ProposePaydown {
+                    id: PAYDOWN_ID_1.into(),
+                    assets: vec![ASSET_ID_1.into()],
+                    total_paydown: Uint128::new(100),
+                },
+            ),
+            (
+                WAREHOUSE,
+                vec![],
+                ExecuteMsg::AcceptPaydown {
+                    id: PAYDOWN_ID_1.into(),
+                },
+            ),
+            (
+                ORIGINATOR,
+                vec![],
+                ExecuteMsg::ExecutePaydown {
+                    id: PAYDOWN_ID_1.into(),
+                },
+            ),
+        ];
+        for (sender, funds, msg) in steps {
+            execute(deps.as_mut(), test_env(), mock_info(sender, &funds), msg).unwrap();
+        }
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Closed
+        );
+
+        // the denom of the closed pledge can't be used by a new pledge
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ProposePledge {
+                id: PLEDGE_ID_2.into(),
+                assets: vec![ASSET_ID_2.into()],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(PLEDGE_ID_1),
+                valid_until: None,
+                document_hash: None,
+            },
+        );
+        match result {
+            Err(ContractError::DenomAlreadyUsed { denom }) => {
+                assert_eq!(denom, asset_marker_denom(PLEDGE_ID_1));
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn reject_accepted_pledge() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+
+        match reject_pledge(deps.as_mut(), WAREHOUSE, None) {
+            Err(ContractError::StateError { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Accepted
+        );
+    }
+
+    #[test]
+    pub fn require_funds_without_funds() {
+        let info = mock_info(WAREHOUSE, &[]);
+        match require_funds(&info, STABLECOIN_DENOM, 100, RequiredFunds::Purchase) {
+            Err(ContractError::MissingPurchaseFunds {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn require_funds_with_wrong_denom() {
+        let info = mock_info(WAREHOUSE, &coins(100, "other.denom"));
+        match require_funds(&info, STABLECOIN_DENOM, 100, RequiredFunds::PledgeAdvance) {
+            Err(ContractError::InsufficientPledgeAdvanceFunds {
+                need,
+                need_denom,
+                received,
+                received_denom,
+            }) => {
+                assert_eq!(need, 100);
+                assert_eq!(need_denom, STABLECOIN_DENOM);
+                assert_eq!(received, 100);
+                assert_eq!(received_denom, "other.denom");
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn require_funds_with_insufficient_amount() {
+        let info = mock_info(ORIGINATOR, &coins(99, STABLECOIN_DENOM));
+        match require_funds(&info, STABLECOIN_DENOM, 100, RequiredFunds::Paydown) {
+            Err(ContractError::InsufficientPaydownFunds { need, received, .. }) => {
+                assert_eq!(need, 100);
+                assert_eq!(received, 99);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn require_funds_returns_surplus() {
+        let info = mock_info(WAREHOUSE, &coins(100, STABLECOIN_DENOM));
+        assert_eq!(
+            require_funds(&info, STABLECOIN_DENOM, 100, RequiredFunds::PledgeAdvance).unwrap(),
+            0
+        );
+
+        // only funds in the required denom are counted
+        let info = mock_info(
+            WAREHOUSE,
+            &[
+                coin(60, STABLECOIN_DENOM),
+                coin(500, "other.denom"),
+                coin(70, STABLECOIN_DENOM),
+            ],
+        );
+        assert_eq!(
+            require_funds(&info, STABLECOIN_DENOM, 100, RequiredFunds::PledgeAdvance).unwrap(),
+            30
+        );
+    }
+
+    #[test]
+    pub fn refund_surplus_with_mixed_denoms() {
+        let info = mock_info(
+            WAREHOUSE,
+            &[
+                coin(150, STABLECOIN_DENOM),
+                coin(500, "other.denom"),
+                coin(20, "another.denom"),
+                coin(5, "other.denom"),
+            ],
+        );
+        let surplus =
+            require_funds(&info, STABLECOIN_DENOM, 100, RequiredFunds::PledgeAdvance).unwrap();
+
+        // the surplus is refunded along with the funds in other denoms, combined by denom
+        assert_eq!(
+            refund_surplus(&info, STABLECOIN_DENOM, surplus),
+            Some(BankMsg::Send {
+                to_address: WAREHOUSE.into(),
+                amount: vec![
+                    coin(20, "another.denom"),
+                    coin(505, "other.denom"),
+                    coin(50, STABLECOIN_DENOM),
+                ],
+            })
+        );
+
+        // nothing is refunded when exactly the required funds were sent
+        let info = mock_info(WAREHOUSE, &coins(100, STABLECOIN_DENOM));
+        assert_eq!(refund_surplus(&info, STABLECOIN_DENOM, 0), None);
+    }
+
+    fn try_propose_pledge(deps: DepsMut) -> Result<Response<ProvenanceMsg>, ContractError> {
+        execute(
+            deps,
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ProposePledge {
+                id: PLEDGE_ID_1.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(PLEDGE_ID_1),
+                valid_until: None,
+                document_hash: None,
+            },
+        )
+    }
+
+    #[test]
+    pub fn sudo_freeze_pauses_facility() {
+        let mut deps = instantiated_test_deps();
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 100);
+
+        let response = sudo(deps.as_mut(), test_env(), SudoMsg::Freeze {}).unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![attr("action", "sudo"), attr("frozen", "true")]
+        );
+
+        match try_propose_pledge(deps.as_mut()) {
+            Err(ContractError::Paused {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // the admin can neither resume nor re-pause the facility while it is frozen
+        for paused in [false, true] {
+            match set_paused(deps.as_mut(), ADMIN, paused) {
+                Err(ContractError::FacilityFrozen {}) => {}
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+        match try_propose_pledge(deps.as_mut()) {
+            Err(ContractError::Paused {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // the existing proposal can still be settled, and queries are still served
+        accept_pledge(deps.as_mut(), PLEDGE_ID_2, 100).unwrap();
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_2).state,
+            PledgeState::Accepted
+        );
+        assert_eq!(facility_summary(deps.as_ref()).inventory_count, 0);
+    }
+
+    #[test]
+    pub fn sudo_unfreeze_restores_execute() {
+        let mut deps = instantiated_test_deps();
+
+        sudo(deps.as_mut(), test_env(), SudoMsg::Freeze {}).unwrap();
+        sudo(deps.as_mut(), test_env(), SudoMsg::Unfreeze {}).unwrap();
+
+        try_propose_pledge(deps.as_mut()).unwrap();
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Proposed
+        );
+    }
+
+    fn set_paused(
+        deps: DepsMut,
+        sender: &str,
+        paused: bool,
+    ) -> Result<Response<ProvenanceMsg>, ContractError> {
+        execute(
+            deps,
+            test_env(),
+            mock_info(sender, &[]),
+            ExecuteMsg::SetPaused { paused },
+        )
+    }
+
+    #[test]
+    pub fn pause_blocks_new_proposals() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 100);
+
+        // only the admin can pause the facility
+        match set_paused(deps.as_mut(), ORIGINATOR, true) {
+            Err(ContractError::Unauthorized {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        let response = set_paused(deps.as_mut(), ADMIN, true).unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![attr("action", "set_paused"), attr("paused", "true")]
+        );
+
+        match try_propose_pledge(deps.as_mut()) {
+            Err(ContractError::Paused {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // nor can an existing proposal be amended to add assets or raise its advance
+        let result = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::AmendPledge {
+                id: PLEDGE_ID_2.into(),
+                assets: Some(vec![ASSET_ID_2.into(), ASSET_ID_3.into()]),
+                total_advance: Some(Uint128::new(200)),
+            },
+        );
+        match result {
+            Err(ContractError::Paused {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_2).total_advance,
+            Uint128::new(100)
+        );
+
+        // the existing proposal can still be settled, and queries are still served
+        accept_pledge(deps.as_mut(), PLEDGE_ID_2, 100).unwrap();
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_2).state,
+            PledgeState::Accepted
+        );
+        assert_eq!(facility_summary(deps.as_ref()).inventory_count, 0);
+
+        // resuming allows the proposal
+        set_paused(deps.as_mut(), ADMIN, false).unwrap();
+        try_propose_pledge(deps.as_mut()).unwrap();
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Proposed
+        );
+    }
+
+    #[test]
+    pub fn settle_cancelled_markers_that_still_exist() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        // unwind two pledges while their asset markers can't be found
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 100);
+        let response = cancel_pledge(deps.as_mut(), ORIGINATOR).unwrap();
+        assert!(response.messages.is_empty());
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(WAREHOUSE, &[]),
+            ExecuteMsg::RejectPledge {
+                id: PLEDGE_ID_2.into(),
+                reason: None,
+            },
+        )
+        .unwrap();
+
+        // the first pledge's asset marker still exists, the second one's is already gone
+        let asset_marker_denom_1 = asset_marker_denom(PLEDGE_ID_1);
+        deps.querier.with_markers(vec![
+            marker(ESCROW_MARKER, ESCROW_DENOM),
+            marker("asset_marker_1", &asset_marker_denom_1),
+        ]);
+
+        let settle = |deps: DepsMut, sender: &str| {
+            execute(
+                deps,
+                test_env(),
+                mock_info(sender, &[]),
+                ExecuteMsg::SettleCancelledMarkers {},
+            )
+        };
+        match settle(deps.as_mut(), ORIGINATOR) {
+            Err(ContractError::Unauthorized {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let response = settle(deps.as_mut(), ADMIN).unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", "settle_cancelled_markers"),
+                attr("settled_pledges", PLEDGE_ID_1),
+            ]
+        );
+        assert_eq!(
+            messages(response),
+            vec![
+                transfer_marker_coins(
+                    1,
+                    &asset_marker_denom_1,
+                    Addr::unchecked("asset_marker_1"),
+                    Addr::unchecked(ORIGINATOR),
+                )
+                .unwrap(),
+                cancel_marker(&asset_marker_denom_1).unwrap(),
+                destroy_marker(&asset_marker_denom_1).unwrap(),
+            ]
+        );
+    }
+
+    fn transfer_originator(
+        deps: DepsMut,
+        sender: &str,
+        new_originator: &str,
+    ) -> Result<Response<ProvenanceMsg>, ContractError> {
+        execute(
+            deps,
+            test_env(),
+            mock_info(sender, &[]),
+            ExecuteMsg::TransferOriginator {
+                new_originator: new_originator.into(),
+            },
+        )
+    }
+
+    fn record_repayment(
+        deps: DepsMut,
+        sender: &str,
+    ) -> Result<Response<ProvenanceMsg>, ContractError> {
+        execute(
+            deps,
+            test_env(),
+            mock_info(sender, &[]),
+            ExecuteMsg::RecordRepayment {
+                pledge_id: PLEDGE_ID_1.into(),
+                reference: "wire-1".into(),
+            },
+        )
+    }
+
+    #[test]
+    pub fn record_repayment_closes_executed_pledge() {
+        let mut deps = mock_dependencies(&[]);
+        let asset_marker_denom_1 = asset_marker_denom(PLEDGE_ID_1);
+        deps.querier.with_markers(vec![
+            marker(ESCROW_MARKER, ESCROW_DENOM),
+            marker("asset_marker_1", &asset_marker_denom_1),
+        ]);
+        instantiate_test_contract(deps.as_mut());
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1, ASSET_ID_2], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+
+        // only the warehouse can record the repayment of its advance
+        match record_repayment(deps.as_mut(), ORIGINATOR) {
+            Err(ContractError::Unauthorized {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let response = record_repayment(deps.as_mut(), WAREHOUSE).unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", "record_repayment"),
+                attr("pledge_id", PLEDGE_ID_1),
+                attr("reference", "wire-1"),
+            ]
+        );
+        assert_eq!(
+            messages(response),
+            vec![
+                transfer_marker_coins(
+                    1,
+                    &asset_marker_denom_1,
+                    Addr::unchecked("asset_marker_1"),
+                    Addr::unchecked(ORIGINATOR),
+                )
+                .unwrap(),
+                cancel_marker(&asset_marker_denom_1).unwrap(),
+                destroy_marker(&asset_marker_denom_1).unwrap(),
+            ]
+        );
+
+        // the pledge is closed with the reference, and its assets leave the inventory
+        let pledge = get_pledge(deps.as_ref(), PLEDGE_ID_1);
+        assert_eq!(pledge.state, PledgeState::Closed);
+        assert_eq!(pledge.repayment_reference, Some("wire-1".into()));
+        assert_eq!(asset_state(deps.as_ref(), ASSET_ID_1), None);
+        assert_eq!(asset_state(deps.as_ref(), ASSET_ID_2), None);
+
+        // a closed pledge can't be repaid again
+        match record_repayment(deps.as_mut(), WAREHOUSE) {
+            Err(ContractError::StateError { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn record_repayment_only_for_executed_pledge() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        // an accepted pledge hasn't disbursed an advance to repay
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1, ASSET_ID_2], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        match record_repayment(deps.as_mut(), WAREHOUSE) {
+            Err(ContractError::StateError { error }) => {
+                assert_eq!(
+                    error,
+                    "Unable to record repayment: Pledge is not in the 'executed' state."
+                );
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Accepted
+        );
+
+        // an executed pledge with an asset in a pending paydown can't be closed
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePledge {
+                id: PLEDGE_ID_1.into(),
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &coins(50, STABLECOIN_DENOM)),
+            ExecuteMsg::ProposePaydown {
+                id: PAYDOWN_ID_1.into(),
+                assets: vec![ASSET_ID_2.into()],
+                total_paydown: Uint128::new(50),
+            },
+        )
+        .unwrap();
+        match record_repayment(deps.as_mut(), WAREHOUSE) {
+            Err(ContractError::StateError { error }) => {
+                assert_eq!(
+                    error,
+                    "Unable to record repayment: Pledge assets are in a pending paydown."
+                );
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Executed
+        );
+    }
+
+    #[test]
+    pub fn record_repayment_leaves_assets_pledged_again() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.with_markers(vec![
+            marker(ESCROW_MARKER, ESCROW_DENOM),
+            marker("asset_marker_1", &asset_marker_denom(PLEDGE_ID_1)),
+        ]);
+        instantiate_test_contract(deps.as_mut());
+
+        // pay down the first asset of an executed pledge
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1, ASSET_ID_2], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_1, 100).unwrap();
+        let steps = vec![
+            (
+                ORIGINATOR,
+                vec![],
+                ExecuteMsg::ExecutePledge {
+                    id: PLEDGE_ID_1.into(),
+                },
+            ),
+            (
+                ORIGINATOR,
+                coins(50, STABLECOIN_DENOM),
+                ExecuteMsg::ProposePaydown {
+                    id: PAYDOWN_ID_1.into(),
+                    assets: vec![ASSET_ID_1.into()],
+                    total_paydown: Uint128::new(50),
+                },
+            ),
+            (
+                WAREHOUSE,
+                vec![],
+                ExecuteMsg::AcceptPaydown {
+                    id: PAYDOWN_ID_1.into(),
+                },
+            ),
+            (
+                ORIGINATOR,
+                vec![],
+                ExecuteMsg::ExecutePaydown {
+                    id: PAYDOWN_ID_1.into(),
+                },
+            ),
+        ];
+        for (sender, funds, msg) in steps {
+            execute(deps.as_mut(), test_env(), mock_info(sender, &funds), msg).unwrap();
+        }
+
+        // pledge the paid down asset again, then propose a paydown of it from the new pledge
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_1], 100);
+        accept_pledge(deps.as_mut(), PLEDGE_ID_2, 100).unwrap();
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ExecutePledge {
+                id: PLEDGE_ID_2.into(),
+            },
+        )
+        .unwrap();
+        let response = execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &coins(50, STABLECOIN_DENOM)),
+            ExecuteMsg::ProposePaydown {
+                id: PAYDOWN_ID_2.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_paydown: Uint128::new(50),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", "propose_paydown"),
+                attr("affected_pledges", PLEDGE_ID_2),
+            ]
+        );
+
+        // the new pledge's pending paydown doesn't block closing the old pledge, and closing the
+        // old pledge leaves the asset in the inventory for the new pledge
+        record_repayment(deps.as_mut(), WAREHOUSE).unwrap();
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Closed
+        );
+        assert_eq!(asset_state(deps.as_ref(), ASSET_ID_2), None);
+        assert_eq!(
+            asset_state(deps.as_ref(), ASSET_ID_1),
+            Some(AssetState::PaydownProposed)
+        );
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_2).state,
+            PledgeState::Executed
+        );
+    }
+
+    #[test]
+    pub fn transfer_originator_moves_authorization() {
+        let new_originator = "new_originator";
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        // only the admin or the originator can transfer the originator role
+        match transfer_originator(deps.as_mut(), WAREHOUSE, new_originator) {
+            Err(ContractError::Unauthorized {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match transfer_originator(deps.as_mut(), ORIGINATOR, "x") {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new("new_originator", "must be a valid address")]
+                );
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match transfer_originator(deps.as_mut(), ADMIN, WAREHOUSE) {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new(
+                        "new_originator",
+                        "must not be the warehouse"
+                    )]
+                );
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        let facility = get_contract_info(&deps.storage).unwrap().facility;
+        assert_eq!(facility.originator, Addr::unchecked(ORIGINATOR));
+
+        let response = transfer_originator(deps.as_mut(), ORIGINATOR, new_originator).unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", "transfer_originator"),
+                attr("previous_originator", ORIGINATOR),
+                attr("new_originator", new_originator),
+            ]
+        );
+        let facility = get_contract_info(&deps.storage).unwrap().facility;
+        assert_eq!(facility.originator, Addr::unchecked(new_originator));
+
+        // the previous originator can no longer propose
+        match try_propose_pledge(deps.as_mut()) {
+            Err(ContractError::Unauthorized {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // the new originator can propose
+        execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(new_originator, &[]),
+            ExecuteMsg::ProposePledge {
+                id: PLEDGE_ID_1.into(),
+                assets: vec![ASSET_ID_1.into()],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(PLEDGE_ID_1),
+                valid_until: None,
+                document_hash: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            get_pledge(deps.as_ref(), PLEDGE_ID_1).state,
+            PledgeState::Proposed
+        );
+
+        // and the admin can transfer the role again
+        transfer_originator(deps.as_mut(), ADMIN, ORIGINATOR).unwrap();
+        match transfer_originator(deps.as_mut(), new_originator, new_originator) {
+            Err(ContractError::Unauthorized {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    fn close_facility(
+        deps: DepsMut,
+        sender: &str,
+    ) -> Result<Response<ProvenanceMsg>, ContractError> {
+        execute(
+            deps,
+            test_env(),
+            mock_info(sender, &[]),
+            ExecuteMsg::CloseFacility {},
+        )
+    }
+
+    #[test]
+    pub fn close_facility_with_live_pledges() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_2, &[ASSET_ID_2], 100);
+        cancel_pledge(deps.as_mut(), ORIGINATOR).unwrap();
+
+        match close_facility(deps.as_mut(), ADMIN) {
+            Err(ContractError::StateError { error }) => {
+                assert!(error.contains(PLEDGE_ID_2));
+                assert!(!error.contains(PLEDGE_ID_1));
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // the facility is still open for activity
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_3, &[ASSET_ID_3], 100);
+    }
+
+    #[test]
+    pub fn close_facility_without_live_pledges() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        propose_test_pledge(deps.as_mut(), PLEDGE_ID_1, &[ASSET_ID_1], 100);
+        cancel_pledge(deps.as_mut(), ORIGINATOR).unwrap();
+
+        let response = close_facility(deps.as_mut(), ADMIN).unwrap();
+        assert_eq!(
+            response.attributes,
+            vec![
+                attr("action", "close_facility"),
+                attr("marker_denom", MARKER_DENOM)
+            ]
+        );
+        assert_eq!(
+            messages(response),
+            vec![
+                cancel_marker(MARKER_DENOM).unwrap(),
+                destroy_marker(MARKER_DENOM).unwrap(),
+            ]
+        );
+
+        // no further proposals are allowed
+        match execute(
+            deps.as_mut(),
+            test_env(),
+            mock_info(ORIGINATOR, &[]),
+            ExecuteMsg::ProposePledge {
+                id: PLEDGE_ID_2.into(),
+                assets: vec![ASSET_ID_2.into()],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: asset_marker_denom(PLEDGE_ID_2),
+                valid_until: None,
+                document_hash: None,
+            },
+        ) {
+            Err(ContractError::FacilityClosed {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // the facility can't be closed again
+        match close_facility(deps.as_mut(), ADMIN) {
+            Err(ContractError::FacilityClosed {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn close_facility_requires_admin() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+        instantiate_test_contract(deps.as_mut());
+
+        match close_facility(deps.as_mut(), ORIGINATOR) {
+            Err(ContractError::Unauthorized {}) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
 }
@@ -1,17 +1,31 @@
-use cosmwasm_std::{Addr, StdResult, Storage};
+use cosmwasm_std::{Addr, StdResult, Storage, Uint128};
 use cw_storage_plus::Item;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::error::ContractError;
-use crate::state::Facility;
+use crate::state::{ContractParty, Facility, RoundingMode};
 
 const NAMESPACE_CONTRACT_INFO: &str = "contract_info";
+const NAMESPACE_FROZEN: &str = "frozen";
+const NAMESPACE_PAUSED: &str = "paused";
+const NAMESPACE_CLOSED: &str = "closed";
 pub const CONTRACT_TYPE: &str = "figure:smart-contracts.warehouse-facility";
 pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub const CONTRACT_INFO: Item<ContractInfo> = Item::new(NAMESPACE_CONTRACT_INFO);
 
+// Set by chain governance (via sudo) while it holds the facility paused, so that the admin can't
+// resume it until governance lifts the freeze.
+const FROZEN: Item<bool> = Item::new(NAMESPACE_FROZEN);
+
+// Set by the admin to stop new proposals, while the existing pledges and paydowns can still be
+// settled.
+const PAUSED: Item<bool> = Item::new(NAMESPACE_PAUSED);
+
+// Set by the admin at the end of the facility's life to permanently halt all facility activity.
+const CLOSED: Item<bool> = Item::new(NAMESPACE_CLOSED);
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ContractInfo {
     pub admin: Addr,
@@ -41,13 +55,135 @@ impl ContractInfo {
             facility,
         }
     }
+
+    // Get the facility party (originator or warehouse) the address acts as, if any.
+    // NOTE: The originator and warehouse should be different addresses, but if they aren't, the
+    //       address is reported as the originator.
+    pub fn party(&self, address: &Addr) -> Option<ContractParty> {
+        if &self.facility.originator == address {
+            Some(ContractParty::Originator)
+        } else if &self.facility.warehouse == address {
+            Some(ContractParty::Warehouse)
+        } else {
+            None
+        }
+    }
+}
+
+// The publicly exposed facility terms and version info (omits the contract admin).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractConfig {
+    pub originator: Addr,
+    pub warehouse: Addr,
+    pub marker_denom: String,
+    pub stablecoin_denoms: Vec<String>,
+    pub advance_rate: String,
+    pub paydown_rate: String,
+    pub rounding: Option<RoundingMode>,
+    pub max_total_advance: Option<Uint128>,
+    pub version: String,
+}
+
+impl From<ContractInfo> for ContractConfig {
+    fn from(contract_info: ContractInfo) -> Self {
+        ContractConfig {
+            originator: contract_info.facility.originator,
+            warehouse: contract_info.facility.warehouse,
+            marker_denom: contract_info.facility.marker_denom,
+            stablecoin_denoms: contract_info.facility.stablecoin_denoms,
+            advance_rate: contract_info.facility.advance_rate,
+            paydown_rate: contract_info.facility.paydown_rate,
+            rounding: contract_info.facility.rounding,
+            max_total_advance: contract_info.facility.max_total_advance,
+            version: contract_info.version,
+        }
+    }
+}
+
+// The version info of the deployed contract, used by migration tooling.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractVersion {
+    pub version: String,
+    pub contract_version: String,
+    pub contract_type: String,
+}
+
+impl From<ContractInfo> for ContractVersion {
+    fn from(contract_info: ContractInfo) -> Self {
+        ContractVersion {
+            version: contract_info.version,
+            contract_version: contract_info.contract_version,
+            contract_type: contract_info.contract_type,
+        }
+    }
+}
+
+// The facility format stored before a facility could accept more than one stablecoin denom.
+#[derive(Serialize, Deserialize)]
+struct LegacyFacility {
+    originator: Addr,
+    warehouse: Addr,
+    escrow_marker: Addr,
+    marker_denom: String,
+    stablecoin_denom: String,
+    advance_rate: String,
+    paydown_rate: String,
+}
+
+// The contract info format stored with a legacy facility.
+#[derive(Serialize, Deserialize)]
+struct LegacyContractInfo {
+    admin: Addr,
+    bind_name: String,
+    contract_name: String,
+    version: String,
+    contract_type: String,
+    contract_version: String,
+    facility: LegacyFacility,
+}
+
+impl From<LegacyContractInfo> for ContractInfo {
+    fn from(legacy: LegacyContractInfo) -> Self {
+        ContractInfo {
+            admin: legacy.admin,
+            bind_name: legacy.bind_name,
+            contract_name: legacy.contract_name,
+            version: legacy.version,
+            contract_type: legacy.contract_type,
+            contract_version: legacy.contract_version,
+            facility: Facility {
+                originator: legacy.facility.originator,
+                warehouse: legacy.facility.warehouse,
+                escrow_marker: legacy.facility.escrow_marker,
+                marker_denom: legacy.facility.marker_denom,
+                stablecoin_denoms: vec![legacy.facility.stablecoin_denom],
+                advance_rate: legacy.facility.advance_rate,
+                paydown_rate: legacy.facility.paydown_rate,
+                rounding: None,
+                max_total_advance: None,
+            },
+        }
+    }
+}
+
+const LEGACY_CONTRACT_INFO: Item<LegacyContractInfo> = Item::new(NAMESPACE_CONTRACT_INFO);
+
+// Rewrite the contract info if stored in the legacy format, returning whether it was migrated.
+pub fn migrate_contract_info(store: &mut dyn Storage) -> StdResult<bool> {
+    if CONTRACT_INFO.load(store).is_ok() {
+        return Ok(false);
+    }
+
+    let legacy = LEGACY_CONTRACT_INFO.load(store)?;
+    CONTRACT_INFO.save(store, &legacy.into())?;
+    Ok(true)
 }
 
 pub fn set_contract_info(
     store: &mut dyn Storage,
     contract_info: &ContractInfo,
 ) -> Result<(), ContractError> {
-    let result = CONTRACT_INFO.save(store, &contract_info);
+    let result = CONTRACT_INFO.save(store, contract_info);
     result.map_err(ContractError::Std)
 }
 
@@ -55,15 +191,48 @@ pub fn get_contract_info(store: &dyn Storage) -> StdResult<ContractInfo> {
     CONTRACT_INFO.load(store)
 }
 
+pub fn get_contract_config(store: &dyn Storage) -> StdResult<ContractConfig> {
+    Ok(get_contract_info(store)?.into())
+}
+
+pub fn get_contract_version(store: &dyn Storage) -> StdResult<ContractVersion> {
+    Ok(get_contract_info(store)?.into())
+}
+
+pub fn is_frozen(store: &dyn Storage) -> StdResult<bool> {
+    Ok(FROZEN.may_load(store)?.unwrap_or(false))
+}
+
+pub fn set_frozen(store: &mut dyn Storage, frozen: bool) -> StdResult<()> {
+    FROZEN.save(store, &frozen)
+}
+
+pub fn is_paused(store: &dyn Storage) -> StdResult<bool> {
+    Ok(PAUSED.may_load(store)?.unwrap_or(false))
+}
+
+pub fn set_paused(store: &mut dyn Storage, paused: bool) -> StdResult<()> {
+    PAUSED.save(store, &paused)
+}
+
+pub fn is_closed(store: &dyn Storage) -> StdResult<bool> {
+    Ok(CLOSED.may_load(store)?.unwrap_or(false))
+}
+
+pub fn set_closed(store: &mut dyn Storage) -> StdResult<()> {
+    CLOSED.save(store, &true)
+}
+
 #[cfg(test)]
 mod tests {
     use provwasm_mocks::mock_dependencies;
 
     use crate::contract_info::{
-        get_contract_info, set_contract_info, ContractInfo, CONTRACT_TYPE, CONTRACT_VERSION,
+        get_contract_config, get_contract_info, migrate_contract_info, set_contract_info,
+        ContractInfo, CONTRACT_TYPE, CONTRACT_VERSION, LEGACY_CONTRACT_INFO,
     };
-    use crate::state::Facility;
-    use cosmwasm_std::Addr;
+    use crate::state::{ContractParty, Facility};
+    use cosmwasm_std::{Addr, Storage};
 
     #[test]
     pub fn set_contract_info_with_valid_data() {
@@ -80,9 +249,11 @@ mod tests {
                     warehouse: Addr::unchecked("contract_warehouse"),
                     escrow_marker: Addr::unchecked("escrow_marker"),
                     marker_denom: "test.denom.wf1".into(),
-                    stablecoin_denom: "test.denom.stable".into(),
+                    stablecoin_denoms: vec!["test.denom.stable".into()],
                     advance_rate: "75.125".into(),
                     paydown_rate: "102.25".into(),
+                    rounding: None,
+                    max_total_advance: None,
                 },
             ),
         );
@@ -113,11 +284,112 @@ mod tests {
                     Addr::unchecked("escrow_marker")
                 );
                 assert_eq!(contract_info.facility.marker_denom, "test.denom.wf1");
-                assert_eq!(contract_info.facility.stablecoin_denom, "test.denom.stable");
+                assert_eq!(
+                    contract_info.facility.stablecoin_denoms,
+                    vec!["test.denom.stable"]
+                );
                 assert_eq!(contract_info.facility.advance_rate, "75.125");
                 assert_eq!(contract_info.facility.paydown_rate, "102.25");
             }
             result => panic!("unexpected error: {:?}", result),
         }
     }
+
+    #[test]
+    pub fn party_of_address() {
+        let contract_info = ContractInfo::new(
+            Addr::unchecked("contract_admin"),
+            "contract_bind_name".into(),
+            "contract_name".into(),
+            "ver".to_string(),
+            Facility {
+                originator: Addr::unchecked("contract_originator"),
+                warehouse: Addr::unchecked("contract_warehouse"),
+                escrow_marker: Addr::unchecked("escrow_marker"),
+                marker_denom: "test.denom.wf1".into(),
+                stablecoin_denoms: vec!["test.denom.stable".into()],
+                advance_rate: "75.125".into(),
+                paydown_rate: "102.25".into(),
+                rounding: None,
+                max_total_advance: None,
+            },
+        );
+        assert_eq!(
+            contract_info.party(&Addr::unchecked("contract_originator")),
+            Some(ContractParty::Originator)
+        );
+        assert_eq!(
+            contract_info.party(&Addr::unchecked("contract_warehouse")),
+            Some(ContractParty::Warehouse)
+        );
+        assert_eq!(
+            contract_info.party(&Addr::unchecked("contract_admin")),
+            None
+        );
+    }
+
+    #[test]
+    pub fn get_contract_config_omits_admin() {
+        let mut deps = mock_dependencies(&[]);
+        set_contract_info(
+            &mut deps.storage,
+            &ContractInfo::new(
+                Addr::unchecked("contract_admin"),
+                "contract_bind_name".into(),
+                "contract_name".into(),
+                "ver".to_string(),
+                Facility {
+                    originator: Addr::unchecked("contract_originator"),
+                    warehouse: Addr::unchecked("contract_warehouse"),
+                    escrow_marker: Addr::unchecked("escrow_marker"),
+                    marker_denom: "test.denom.wf1".into(),
+                    stablecoin_denoms: vec!["test.denom.stable".into()],
+                    advance_rate: "75.125".into(),
+                    paydown_rate: "102.25".into(),
+                    rounding: None,
+                    max_total_advance: None,
+                },
+            ),
+        )
+        .unwrap();
+
+        let config = get_contract_config(&deps.storage);
+        match config {
+            Ok(config) => {
+                assert_eq!(config.originator, Addr::unchecked("contract_originator"));
+                assert_eq!(config.warehouse, Addr::unchecked("contract_warehouse"));
+                assert_eq!(config.marker_denom, "test.denom.wf1");
+                assert_eq!(config.stablecoin_denoms, vec!["test.denom.stable"]);
+                assert_eq!(config.advance_rate, "75.125");
+                assert_eq!(config.paydown_rate, "102.25");
+                assert_eq!(config.rounding, None);
+                assert_eq!(config.max_total_advance, None);
+                assert_eq!(config.version, "ver");
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn migrate_legacy_contract_info() {
+        let mut deps = mock_dependencies(&[]);
+        deps.storage.set(
+            LEGACY_CONTRACT_INFO.as_slice(),
+            br#"{"admin":"contract_admin","bind_name":"contract_bind_name","contract_name":"contract_name","version":"ver","contract_type":"figure:smart-contracts.warehouse-facility","contract_version":"0.1.0","facility":{"originator":"contract_originator","warehouse":"contract_warehouse","escrow_marker":"escrow_marker","marker_denom":"test.denom.wf1","stablecoin_denom":"test.denom.stable","advance_rate":"75.125","paydown_rate":"102.25"}}"#,
+        );
+
+        assert!(get_contract_info(&deps.storage).is_err());
+        assert!(migrate_contract_info(&mut deps.storage).unwrap());
+
+        let contract_info = get_contract_info(&deps.storage).unwrap();
+        assert_eq!(
+            contract_info.facility.stablecoin_denoms,
+            vec!["test.denom.stable"]
+        );
+        assert_eq!(contract_info.facility.marker_denom, "test.denom.wf1");
+        assert_eq!(contract_info.facility.advance_rate, "75.125");
+
+        // running the migration again is a no-op
+        assert!(!migrate_contract_info(&mut deps.storage).unwrap());
+    }
 }
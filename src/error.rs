@@ -1,7 +1,48 @@
+#[cfg(feature = "contract")]
 use crate::state::ContractParty;
+#[cfg(feature = "contract")]
 use cosmwasm_std::StdError;
+#[cfg(feature = "contract")]
+use std::fmt;
 use thiserror::Error;
 
+// A message field that failed validation, along with the reason it failed (for example:
+// "must be in (0,100]"), so that clients can render the reason alongside the field.
+#[cfg(feature = "contract")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+#[cfg(feature = "contract")]
+impl FieldError {
+    pub fn new<F: Into<String>, R: Into<String>>(field: F, reason: R) -> Self {
+        FieldError {
+            field: field.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+#[cfg(feature = "contract")]
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.field, self.reason)
+    }
+}
+
+// Join the field errors into a single readable message.
+#[cfg(feature = "contract")]
+fn join_field_errors(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|error| error.to_string())
+        .collect::<Vec<String>>()
+        .join("; ")
+}
+
+#[cfg(feature = "contract")]
 #[derive(Error, Debug)]
 pub enum ContractError {
     #[error("{0}")]
@@ -10,8 +51,17 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
-    #[error("Invalid fields: {fields:?}")]
-    InvalidFields { fields: Vec<String> },
+    #[error("Facility is frozen by governance")]
+    FacilityFrozen {},
+
+    #[error("Facility is closed")]
+    FacilityClosed {},
+
+    #[error("Facility is paused: New proposals are not allowed")]
+    Paused {},
+
+    #[error("Invalid fields: {}", join_field_errors(.errors))]
+    InvalidFields { errors: Vec<FieldError> },
 
     #[error("State error: {error:?}")]
     StateError { error: String },
@@ -19,14 +69,47 @@ pub enum ContractError {
     #[error("Pledge already exists: {id:?}")]
     PledgeAlreadyExists { id: String },
 
+    #[error("Pledge not found: {id:?}")]
+    PledgeNotFound { id: String },
+
     #[error(
         "Cannot propose pledge: One or more assets has already been pledged or is in the inventory"
     )]
     AssetsAlreadyPledged {},
 
+    #[error("Asset marker denom {denom:?} is already used by {used_by}")]
+    AssetMarkerDenomInUse { denom: String, used_by: String },
+
+    #[error("Asset marker denom {denom:?} has already been used by a pledge")]
+    DenomAlreadyUsed { denom: String },
+
+    #[error("Cannot re-propose pledge: Asset marker {denom:?} has not been destroyed")]
+    AssetMarkerStillExists { denom: String },
+
+    #[error("Invalid asset marker denom: expected {expected:?}")]
+    InvalidAssetMarkerDenom { expected: String },
+
+    #[error("Marker not found: {denom:?}")]
+    MarkerNotFound { denom: String },
+
+    #[error("Escrow marker not found: {address:?}")]
+    EscrowMarkerNotFound { address: String },
+
     #[error("Facility contract missing grants on escrow marker")]
     MissingEscrowMarkerGrant {},
 
+    #[error("Marker setup failed at step {step:?}: {error}")]
+    MarkerSetupFailed { step: String, error: String },
+
+    #[error("Cannot execute pledge: Escrow marker does not hold the pledge advance")]
+    InsufficientEscrow {},
+
+    #[error("Cannot accept pledge: Proposal {id:?} expired")]
+    ProposalExpired { id: String },
+
+    #[error("Cannot accept pledge: Facility limit {limit:?} exceeded, total advance would be {would_be:?}")]
+    FacilityLimitExceeded { limit: u128, would_be: u128 },
+
     #[error("Cannot accept pledge: Missing pledge advance funds")]
     MissingPledgeAdvanceFunds {},
 
@@ -52,9 +135,18 @@ pub enum ContractError {
     #[error("Paydown already exists: {id:?}")]
     PaydownAlreadyExists { id: String },
 
+    #[error("Paydown not found: {id:?}")]
+    PaydownNotFound { id: String },
+
     #[error("Cannot propose paydown: Assets not in inventory")]
     AssetsNotInInventory {},
 
+    #[error("Cannot propose paydown: Assets don't belong to an executed pledge")]
+    PaydownAssetsNotPledged {},
+
+    #[error("Cannot propose paydown: Assets belong to more than one pledge")]
+    PaydownSpansMultiplePledges {},
+
     #[error("Cannot accept paydown: Party {party:?} already accepted")]
     PaydownPartyAlreadyAccepted { party: ContractParty },
 
@@ -70,6 +162,50 @@ pub enum ContractError {
     },
 }
 
+#[derive(Error, Debug, PartialEq)]
+pub enum MetadataAddressError {
+    #[error("Invalid record name: cannot be empty or blank")]
+    BlankRecordName {},
+
+    #[error("Invalid record specification name: cannot be empty or blank")]
+    BlankRecordSpecificationName {},
+
+    #[error("Invalid data: cannot be empty")]
+    EmptyData {},
+
+    #[error("Invalid key: {key:?}")]
+    InvalidKey { key: u8 },
+
+    #[error(
+        "Incorrect data length for type {prefix:?}: Expected {expected:?}, Actual: {actual:?}"
+    )]
+    IncorrectLength {
+        prefix: String,
+        expected: usize,
+        actual: usize,
+    },
+
+    #[error("Unsupported address type: {prefix:?}")]
+    UnsupportedType { prefix: String },
+
+    #[error("Invalid bech32 address: {error}")]
+    InvalidBech32 { error: String },
+
+    #[error("Incorrect HRP: Expected {expected:?}, Actual: {actual:?}")]
+    IncorrectPrefix { expected: String, actual: String },
+}
+
+#[cfg(feature = "contract")]
+impl ContractError {
+    // Build an invalid fields error for a single field.
+    pub fn invalid_field<F: Into<String>, R: Into<String>>(field: F, reason: R) -> Self {
+        ContractError::InvalidFields {
+            errors: vec![FieldError::new(field, reason)],
+        }
+    }
+}
+
+#[cfg(feature = "contract")]
 impl From<ContractError> for StdError {
     fn from(error: ContractError) -> Self {
         StdError::GenericErr {
@@ -1,8 +1,16 @@
+#[cfg(feature = "contract")]
 extern crate cosmwasm_std;
 
+#[cfg(feature = "contract")]
 pub mod contract;
+#[cfg(feature = "contract")]
 pub mod contract_info;
 pub mod error;
+#[cfg(feature = "contract")]
 pub mod msg;
+#[cfg(feature = "contract")]
 pub mod state;
+#[cfg(all(test, feature = "contract"))]
+mod testing;
+#[cfg(feature = "metadata")]
 pub mod utils;
@@ -1,13 +1,95 @@
 use crate::contract_info::ContractInfo;
-use crate::error::ContractError;
-use crate::state::Facility;
-use cosmwasm_std::Addr;
-use rust_decimal::prelude::FromStr;
-use rust_decimal::Decimal;
+use crate::error::{ContractError, FieldError};
+use crate::state::{
+    AssetState, ContractParty, Facility, Paydown, PaydownState, Pledge, PledgeState, RoundingMode,
+};
+use crate::utils::normalize_asset_id;
+use cosmwasm_std::{Addr, Timestamp, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use uuid::Uuid;
 
+// The maximum number of assets that can be included in a single pledge or paydown, which bounds
+// the storage and gas used when the assets are iterated.
+pub const MAX_ASSETS_PER_PLEDGE: usize = 500;
+
+// Compare assets by their canonical scope UUID, so that a UUID and its scope address match.
+pub(crate) fn asset_key(asset: &str) -> String {
+    normalize_asset_id(asset).unwrap_or_else(|_| asset.into())
+}
+
+// The limits Provenance places on a name, which is made up of dot-separated segments.
+const MAX_NAME_SEGMENTS: usize = 16;
+const MIN_NAME_SEGMENT_LENGTH: usize = 2;
+const MAX_NAME_SEGMENT_LENGTH: usize = 32;
+
+// Check that a (non-empty) name can be bound on Provenance, returning the reason it can't if not.
+fn invalid_name_reason(name: &str) -> Option<String> {
+    let segments: Vec<&str> = name.split('.').collect();
+    let segment_lengths = MIN_NAME_SEGMENT_LENGTH..=MAX_NAME_SEGMENT_LENGTH;
+    let allowed = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.';
+    if segments.len() > MAX_NAME_SEGMENTS {
+        Some(format!(
+            "must not have more than {} segments",
+            MAX_NAME_SEGMENTS
+        ))
+    } else if !segments
+        .iter()
+        .all(|segment| segment_lengths.contains(&segment.len()))
+    {
+        Some(format!(
+            "segments must be {} to {} characters",
+            MIN_NAME_SEGMENT_LENGTH, MAX_NAME_SEGMENT_LENGTH
+        ))
+    } else if !name.chars().all(allowed) {
+        Some("must contain only lowercase letters, digits, hyphens and dots".into())
+    } else {
+        None
+    }
+}
+
+// Check that a document hash is a hex encoded SHA-256 hash.
+fn is_sha256_hex(hash: &str) -> bool {
+    hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn invalid_asset() -> FieldError {
+    FieldError::new("asset", "must be a UUID or a bech32 scope address")
+}
+
+fn too_many_assets() -> FieldError {
+    FieldError::new(
+        "assets",
+        format!(
+            "must not contain more than {} assets",
+            MAX_ASSETS_PER_PLEDGE
+        ),
+    )
+}
+
+// Check that the assets of a pledge or paydown are (at most MAX_ASSETS_PER_PLEDGE) valid and
+// distinct scope UUIDs or bech32 scope addresses.
+fn validate_assets(assets: &[String], invalid_fields: &mut Vec<FieldError>) {
+    if assets.is_empty() {
+        invalid_fields.push(FieldError::new("assets", "must not be empty"));
+    } else if assets.len() > MAX_ASSETS_PER_PLEDGE {
+        invalid_fields.push(too_many_assets());
+    }
+    for asset in assets {
+        if normalize_asset_id(asset).is_err() {
+            invalid_fields.push(invalid_asset());
+        }
+    }
+    let mut unique_assets = HashSet::new();
+    if !assets
+        .iter()
+        .all(|asset| unique_assets.insert(asset_key(asset)))
+    {
+        invalid_fields.push(FieldError::new("assets", "must not contain duplicates"));
+    }
+}
+
 pub trait Validate {
     fn validate(&self) -> Result<(), ContractError>;
 }
@@ -36,68 +118,239 @@ pub struct InstantiateMsg {
 /// ```
 impl Validate for InstantiateMsg {
     fn validate(&self) -> Result<(), ContractError> {
-        let mut invalid_fields: Vec<&str> = vec![];
+        let mut invalid_fields: Vec<FieldError> = vec![];
 
         // validate the bind name
         if self.bind_name.is_empty() {
-            invalid_fields.push("bind_name");
+            invalid_fields.push(FieldError::new("bind_name", "must not be empty"));
+        } else if let Some(reason) = invalid_name_reason(&self.bind_name) {
+            invalid_fields.push(FieldError::new("bind_name", reason));
         }
 
         // validate the contract name
         if self.contract_name.is_empty() {
-            invalid_fields.push("contract_name");
+            invalid_fields.push(FieldError::new("contract_name", "must not be empty"));
+        }
+
+        // validate the facility terms
+        if let Err(ContractError::InvalidFields { errors }) = self.facility.validate() {
+            invalid_fields.extend(errors);
+        }
+
+        match invalid_fields.len() {
+            0 => Ok(()),
+            _ => Err(ContractError::InvalidFields {
+                errors: invalid_fields,
+            }),
         }
+    }
+}
+
+// The facility terms are validated as part of the instantiate message, so their fields are reported
+// under "facility".
+impl Validate for Facility {
+    fn validate(&self) -> Result<(), ContractError> {
+        let mut invalid_fields: Vec<FieldError> = vec![];
 
         // validate the facility originator address
-        if self.facility.originator.as_str().is_empty() {
-            invalid_fields.push("facility.originator");
+        if self.originator.as_str().is_empty() {
+            invalid_fields.push(FieldError::new("facility.originator", "must not be empty"));
         }
 
         // validate the facility warehouse address
-        if self.facility.warehouse.as_str().is_empty() {
-            invalid_fields.push("facility.warehouse");
+        if self.warehouse.as_str().is_empty() {
+            invalid_fields.push(FieldError::new("facility.warehouse", "must not be empty"));
         }
 
         // validate the facility escrow marker address
-        if self.facility.escrow_marker.as_str().is_empty() {
-            invalid_fields.push("facility.escrow_marker");
+        if self.escrow_marker.as_str().is_empty() {
+            invalid_fields.push(FieldError::new(
+                "facility.escrow_marker",
+                "must not be empty",
+            ));
         }
 
         // validate the facility marker denom
-        if self.facility.marker_denom.is_empty() {
-            invalid_fields.push("facility.marker_denom");
+        if self.marker_denom.is_empty() {
+            invalid_fields.push(FieldError::new(
+                "facility.marker_denom",
+                "must not be empty",
+            ));
         }
 
-        // validate the stablecoin denom
-        if self.facility.stablecoin_denom.is_empty() {
-            invalid_fields.push("facility.stablecoin_denom");
+        // validate the stablecoin denoms: at least one is required, and each must be non-blank,
+        // unique and distinct from the facility marker denom
+        let denoms = &self.stablecoin_denoms;
+        let denoms_reason = if denoms.is_empty() {
+            Some("must include at least one denom")
+        } else if denoms.iter().any(|denom| denom.is_empty()) {
+            Some("must not include an empty denom")
+        } else if denoms.contains(&self.marker_denom) {
+            Some("must not include the facility marker denom")
+        } else if denoms
+            .iter()
+            .enumerate()
+            .any(|(i, denom)| denoms[..i].contains(denom))
+        {
+            Some("must not include duplicate denoms")
+        } else {
+            None
+        };
+        if let Some(reason) = denoms_reason {
+            invalid_fields.push(FieldError::new("facility.stablecoin_denoms", reason));
         }
 
         // validate the advance rate
-        let advance_rate = Decimal::from_str(&self.facility.advance_rate)
-            .map_err(|_| invalid_fields.push("facility.advance_rate"))
-            .unwrap();
-        if advance_rate <= Decimal::from(0) || advance_rate > Decimal::from(100) {
-            invalid_fields.push("facility.advance_rate");
+        if let Err(ContractError::InvalidFields { errors }) = self.advance_rate_decimal() {
+            invalid_fields.extend(errors);
         }
 
         // validate the paydown rate
-        let paydown_rate = Decimal::from_str(&self.facility.paydown_rate)
-            .map_err(|_| invalid_fields.push("facility.paydown_rate"))
-            .unwrap();
-        if paydown_rate <= Decimal::from(0) {
-            invalid_fields.push("facility.paydown_rate");
+        if let Err(ContractError::InvalidFields { errors }) = self.paydown_rate_decimal() {
+            invalid_fields.extend(errors);
         }
 
         match invalid_fields.len() {
             0 => Ok(()),
             _ => Err(ContractError::InvalidFields {
-                fields: invalid_fields.into_iter().map(|item| item.into()).collect(),
+                errors: invalid_fields,
             }),
         }
     }
 }
 
+impl InstantiateMsg {
+    // Start building an instantiate message (ex: for deployment scripts and integration tests).
+    pub fn builder() -> InstantiateMsgBuilder {
+        InstantiateMsgBuilder::default()
+    }
+}
+
+// Builds an instantiate message, which is validated when it is built.
+#[derive(Clone, Debug, Default)]
+pub struct InstantiateMsgBuilder {
+    bind_name: String,
+    contract_name: String,
+    facility: FacilityBuilder,
+}
+
+impl InstantiateMsgBuilder {
+    pub fn bind_name<N: Into<String>>(mut self, bind_name: N) -> Self {
+        self.bind_name = bind_name.into();
+        self
+    }
+
+    pub fn contract_name<N: Into<String>>(mut self, contract_name: N) -> Self {
+        self.contract_name = contract_name.into();
+        self
+    }
+
+    pub fn facility(mut self, facility: FacilityBuilder) -> Self {
+        self.facility = facility;
+        self
+    }
+
+    // Build the instantiate message, failing with the invalid fields if it doesn't validate.
+    pub fn build(self) -> Result<InstantiateMsg, ContractError> {
+        let msg = InstantiateMsg {
+            bind_name: self.bind_name,
+            contract_name: self.contract_name,
+            facility: self.facility.facility(),
+        };
+        msg.validate()?;
+        Ok(msg)
+    }
+}
+
+// Builds the facility terms, which are validated when they are built. The optional terms default to
+// no rounding mode (truncate) and no limit on the total advance.
+#[derive(Clone, Debug, Default)]
+pub struct FacilityBuilder {
+    originator: String,
+    warehouse: String,
+    escrow_marker: String,
+    marker_denom: String,
+    stablecoin_denoms: Vec<String>,
+    advance_rate: String,
+    paydown_rate: String,
+    rounding: Option<RoundingMode>,
+    max_total_advance: Option<Uint128>,
+}
+
+impl FacilityBuilder {
+    pub fn new() -> Self {
+        FacilityBuilder::default()
+    }
+
+    pub fn originator<A: Into<String>>(mut self, originator: A) -> Self {
+        self.originator = originator.into();
+        self
+    }
+
+    pub fn warehouse<A: Into<String>>(mut self, warehouse: A) -> Self {
+        self.warehouse = warehouse.into();
+        self
+    }
+
+    pub fn escrow_marker<A: Into<String>>(mut self, escrow_marker: A) -> Self {
+        self.escrow_marker = escrow_marker.into();
+        self
+    }
+
+    pub fn marker_denom<D: Into<String>>(mut self, marker_denom: D) -> Self {
+        self.marker_denom = marker_denom.into();
+        self
+    }
+
+    // Add a stablecoin denom accepted by the facility.
+    pub fn stablecoin_denom<D: Into<String>>(mut self, denom: D) -> Self {
+        self.stablecoin_denoms.push(denom.into());
+        self
+    }
+
+    pub fn advance_rate<R: Into<String>>(mut self, advance_rate: R) -> Self {
+        self.advance_rate = advance_rate.into();
+        self
+    }
+
+    pub fn paydown_rate<R: Into<String>>(mut self, paydown_rate: R) -> Self {
+        self.paydown_rate = paydown_rate.into();
+        self
+    }
+
+    pub fn rounding(mut self, rounding: RoundingMode) -> Self {
+        self.rounding = Some(rounding);
+        self
+    }
+
+    pub fn max_total_advance<A: Into<Uint128>>(mut self, max_total_advance: A) -> Self {
+        self.max_total_advance = Some(max_total_advance.into());
+        self
+    }
+
+    // Build the facility terms, failing with the invalid fields if they don't validate.
+    pub fn build(self) -> Result<Facility, ContractError> {
+        let facility = self.facility();
+        facility.validate()?;
+        Ok(facility)
+    }
+
+    // The facility terms as set, without validating them.
+    fn facility(self) -> Facility {
+        Facility {
+            originator: Addr::unchecked(self.originator),
+            warehouse: Addr::unchecked(self.warehouse),
+            escrow_marker: Addr::unchecked(self.escrow_marker),
+            marker_denom: self.marker_denom,
+            stablecoin_denoms: self.stablecoin_denoms,
+            advance_rate: self.advance_rate,
+            paydown_rate: self.paydown_rate,
+            rounding: self.rounding,
+            max_total_advance: self.max_total_advance,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
@@ -110,11 +363,55 @@ pub enum ExecuteMsg {
         assets: Vec<String>,
 
         // The total requested advance for the pledged assets.
-        total_advance: u64,
+        total_advance: Uint128,
+
+        // The marker denom to create representing the encumbered
+        // pool of pledged assets.
+        asset_marker_denom: String,
+
+        // The block time after which the warehouse can no longer accept the proposal, though the
+        // originator can still cancel it (None = no deadline).
+        valid_until: Option<Timestamp>,
+
+        // The hex SHA-256 hash of the off-chain pledge agreement to bind the proposal to
+        // (None = no document).
+        document_hash: Option<String>,
+    },
+
+    // Propose a cancelled or rejected pledge again, reusing its id (originator)
+    ReproposePledge {
+        // The unique identifier of the cancelled or rejected pledge.
+        id: String,
+
+        // A list of assets to include in the pledge.
+        assets: Vec<String>,
+
+        // The total requested advance for the pledged assets.
+        total_advance: Uint128,
 
         // The marker denom to create representing the encumbered
         // pool of pledged assets.
         asset_marker_denom: String,
+
+        // The block time after which the warehouse can no longer accept the proposal, though the
+        // originator can still cancel it (None = no deadline).
+        valid_until: Option<Timestamp>,
+
+        // The hex SHA-256 hash of the off-chain pledge agreement to bind the proposal to
+        // (None = no document).
+        document_hash: Option<String>,
+    },
+
+    // Amend the assets and/or advance of a pledge proposal before it is accepted (originator)
+    AmendPledge {
+        // The unique identifier of the proposed pledge.
+        id: String,
+
+        // The list of assets to include in the pledge instead (None = unchanged).
+        assets: Option<Vec<String>>,
+
+        // The total requested advance for the pledged assets instead (None = unchanged).
+        total_advance: Option<Uint128>,
     },
 
     // Accept a proposal to pledge assets to the warehouse facility (warehouse)
@@ -123,12 +420,27 @@ pub enum ExecuteMsg {
         id: String,
     },
 
+    // Accept multiple pledge proposals at once, escrowing their combined advance (warehouse)
+    AcceptPledges {
+        // The unique identifiers of the pledges.
+        ids: Vec<String>,
+    },
+
     // Cancel a proposal to pledge assets to the warehouse facility (originator)
     CancelPledge {
         // The unique identifier of the pledge.
         id: String,
     },
 
+    // Reject a proposal to pledge assets to the warehouse facility (warehouse)
+    RejectPledge {
+        // The unique identifier of the pledge.
+        id: String,
+
+        // The optional reason the pledge was rejected.
+        reason: Option<String>,
+    },
+
     // Executes a proposal to pledge assets to the warehouse facility (originator)
     ExecutePledge {
         // The unique identifier of the pledge.
@@ -144,7 +456,7 @@ pub enum ExecuteMsg {
         assets: Vec<String>,
 
         // The total proposed paydown for the pledged asset(s).
-        total_paydown: u64,
+        total_paydown: Uint128,
     },
 
     // Propose a paydown of a pledge to the warehouse facility, selling the asset(s) to a third-party investor (originator)
@@ -156,7 +468,7 @@ pub enum ExecuteMsg {
         assets: Vec<String>,
 
         // The total proposed paydown for the pledged asset(s).
-        total_paydown: u64,
+        total_paydown: Uint128,
 
         // The address of the buyer.
         buyer: Addr,
@@ -182,6 +494,62 @@ pub enum ExecuteMsg {
         // The unique identifier of the paydown.
         id: String,
     },
+
+    // Close an executed pledge whose advance was repaid outside of the facility, for example by
+    // wire transfer (warehouse)
+    RecordRepayment {
+        // The unique identifier of the pledge.
+        pledge_id: String,
+
+        // The external reference of the repayment (ex: the wire transfer id).
+        reference: String,
+    },
+
+    // Replace the name bound to the contract address (admin)
+    UpdateBinding {
+        // The new name to bind to the contract address.
+        bind_name: String,
+    },
+
+    // Pause or resume new pledge and paydown proposals, while the existing ones can still be
+    // settled (admin)
+    SetPaused {
+        // Whether new proposals are rejected.
+        paused: bool,
+    },
+
+    // Cancel and destroy the asset markers that still exist for cancelled or rejected pledges,
+    // for example when a marker couldn't be found while the pledge was being unwound (admin)
+    SettleCancelledMarkers {},
+
+    // Transfer the originator role to a new address, for example when the originator sells its
+    // loan book (admin or originator)
+    // NOTE: Only future authorization moves to the new originator. Asset markers already
+    //       withdrawn to the old originator are not transferred by the contract.
+    TransferOriginator {
+        // The address of the new originator.
+        new_originator: String,
+    },
+
+    // Close the facility at the end of its life, cancelling and destroying the facility marker
+    // and rejecting all further execute messages (admin)
+    CloseFacility {},
+}
+
+impl ExecuteMsg {
+    // Whether the message proposes a new pledge or paydown (which isn't allowed while the
+    // facility is paused). Amending a pledge proposal counts, since it can add assets or raise
+    // the advance.
+    pub fn is_proposal(&self) -> bool {
+        matches!(
+            self,
+            ExecuteMsg::ProposePledge { .. }
+                | ExecuteMsg::ReproposePledge { .. }
+                | ExecuteMsg::AmendPledge { .. }
+                | ExecuteMsg::ProposePaydown { .. }
+                | ExecuteMsg::ProposePaydownAndSell { .. }
+        )
+    }
 }
 
 /// Simple validation of ExecuteMsg data
@@ -197,132 +565,264 @@ pub enum ExecuteMsg {
 /// ```
 impl Validate for ExecuteMsg {
     fn validate(&self) -> Result<(), ContractError> {
-        let mut invalid_fields: Vec<&str> = vec![];
+        let mut invalid_fields: Vec<FieldError> = vec![];
 
         match self {
             ExecuteMsg::ProposePledge {
                 id,
                 assets,
-                total_advance: _,
+                total_advance,
                 asset_marker_denom,
+                valid_until: _,
+                document_hash,
             } => {
                 // validate the pledge id
                 if Uuid::parse_str(id).is_err() {
-                    invalid_fields.push("id");
+                    invalid_fields.push(FieldError::new("id", "must be a UUID"));
                 }
 
                 // validate the assets
-                if assets.is_empty() {
-                    invalid_fields.push("assets");
+                validate_assets(assets, &mut invalid_fields);
+
+                // validate the marker denom
+                if asset_marker_denom.is_empty() {
+                    invalid_fields.push(FieldError::new("asset_marker_denom", "must not be empty"));
                 }
-                for asset in assets {
-                    if Uuid::parse_str(&asset).is_err() {
-                        invalid_fields.push("asset");
+
+                // validate the total advance
+                if total_advance.is_zero() {
+                    invalid_fields.push(FieldError::new("total_advance", "must be greater than 0"));
+                }
+
+                // validate the document hash
+                if let Some(document_hash) = document_hash {
+                    if !is_sha256_hex(document_hash) {
+                        invalid_fields.push(FieldError::new(
+                            "document_hash",
+                            "must be a hex SHA-256 hash (64 hex characters)",
+                        ));
                     }
                 }
+            }
+
+            ExecuteMsg::ReproposePledge {
+                id,
+                assets,
+                total_advance,
+                asset_marker_denom,
+                valid_until: _,
+                document_hash,
+            } => {
+                // validate the pledge id
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push(FieldError::new("id", "must be a UUID"));
+                }
+
+                // validate the assets
+                validate_assets(assets, &mut invalid_fields);
 
                 // validate the marker denom
                 if asset_marker_denom.is_empty() {
-                    invalid_fields.push("asset_marker_denom");
+                    invalid_fields.push(FieldError::new("asset_marker_denom", "must not be empty"));
+                }
+
+                // validate the total advance
+                if total_advance.is_zero() {
+                    invalid_fields.push(FieldError::new("total_advance", "must be greater than 0"));
+                }
+
+                // validate the document hash
+                if let Some(document_hash) = document_hash {
+                    if !is_sha256_hex(document_hash) {
+                        invalid_fields.push(FieldError::new(
+                            "document_hash",
+                            "must be a hex SHA-256 hash (64 hex characters)",
+                        ));
+                    }
+                }
+            }
+
+            ExecuteMsg::AmendPledge {
+                id,
+                assets,
+                total_advance,
+            } => {
+                // validate the pledge id
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push(FieldError::new("id", "must be a UUID"));
+                }
+
+                // validate the amendment, which must change at least one of the terms
+                if assets.is_none() && total_advance.is_none() {
+                    invalid_fields.push(FieldError::new(
+                        "assets",
+                        "or total_advance must be specified",
+                    ));
+                }
+
+                // validate the assets
+                if let Some(assets) = assets {
+                    validate_assets(assets, &mut invalid_fields);
+                }
+
+                // validate the total advance
+                if matches!(total_advance, Some(total_advance) if total_advance.is_zero()) {
+                    invalid_fields.push(FieldError::new("total_advance", "must be greater than 0"));
                 }
             }
 
             ExecuteMsg::AcceptPledge { id } => {
                 // validate the pledge id
                 if Uuid::parse_str(id).is_err() {
-                    invalid_fields.push("id");
+                    invalid_fields.push(FieldError::new("id", "must be a UUID"));
+                }
+            }
+
+            ExecuteMsg::AcceptPledges { ids } => {
+                // validate the pledge ids
+                if ids.is_empty() {
+                    invalid_fields.push(FieldError::new("ids", "must not be empty"));
+                }
+                for id in ids {
+                    if Uuid::parse_str(id).is_err() {
+                        invalid_fields.push(FieldError::new("id", "must be a UUID"));
+                    }
+                }
+                let mut unique_ids = HashSet::new();
+                if !ids.iter().all(|id| unique_ids.insert(id)) {
+                    invalid_fields.push(FieldError::new("ids", "must not contain duplicates"));
                 }
             }
 
             ExecuteMsg::CancelPledge { id } => {
                 // validate the pledge id
                 if Uuid::parse_str(id).is_err() {
-                    invalid_fields.push("id");
+                    invalid_fields.push(FieldError::new("id", "must be a UUID"));
+                }
+            }
+
+            ExecuteMsg::RejectPledge { id, reason: _ } => {
+                // validate the pledge id
+                if Uuid::parse_str(id).is_err() {
+                    invalid_fields.push(FieldError::new("id", "must be a UUID"));
                 }
             }
 
             ExecuteMsg::ExecutePledge { id } => {
                 // validate the pledge id
                 if Uuid::parse_str(id).is_err() {
-                    invalid_fields.push("id");
+                    invalid_fields.push(FieldError::new("id", "must be a UUID"));
                 }
             }
 
             ExecuteMsg::ProposePaydown {
                 id,
                 assets,
-                total_paydown: _,
+                total_paydown,
             } => {
                 // validate the paydown id
                 if Uuid::parse_str(id).is_err() {
-                    invalid_fields.push("id");
+                    invalid_fields.push(FieldError::new("id", "must be a UUID"));
                 }
 
                 // validate the assets
-                if assets.is_empty() {
-                    invalid_fields.push("assets");
-                }
-                for asset in assets {
-                    if Uuid::parse_str(&asset).is_err() {
-                        invalid_fields.push("asset");
-                    }
+                validate_assets(assets, &mut invalid_fields);
+
+                // validate the total paydown
+                if total_paydown.is_zero() {
+                    invalid_fields.push(FieldError::new("total_paydown", "must be greater than 0"));
                 }
             }
 
             ExecuteMsg::ProposePaydownAndSell {
                 id,
                 assets,
-                total_paydown: _,
+                total_paydown,
                 buyer,
                 purchase_price: _,
             } => {
                 // validate the paydown id
                 if Uuid::parse_str(id).is_err() {
-                    invalid_fields.push("id");
+                    invalid_fields.push(FieldError::new("id", "must be a UUID"));
                 }
 
                 // validate the assets
-                if assets.is_empty() {
-                    invalid_fields.push("assets");
-                }
-                for asset in assets {
-                    if Uuid::parse_str(&asset).is_err() {
-                        invalid_fields.push("asset");
-                    }
+                validate_assets(assets, &mut invalid_fields);
+
+                // validate the total paydown
+                if total_paydown.is_zero() {
+                    invalid_fields.push(FieldError::new("total_paydown", "must be greater than 0"));
                 }
 
                 // validate the buyer address
                 if buyer.as_str().is_empty() {
-                    invalid_fields.push("buyer");
+                    invalid_fields.push(FieldError::new("buyer", "must not be empty"));
                 }
             }
 
             ExecuteMsg::AcceptPaydown { id } => {
                 // validate the paydown id
                 if Uuid::parse_str(id).is_err() {
-                    invalid_fields.push("id");
+                    invalid_fields.push(FieldError::new("id", "must be a UUID"));
                 }
             }
 
             ExecuteMsg::CancelPaydown { id } => {
                 // validate the paydown id
                 if Uuid::parse_str(id).is_err() {
-                    invalid_fields.push("id");
+                    invalid_fields.push(FieldError::new("id", "must be a UUID"));
                 }
             }
 
             ExecuteMsg::ExecutePaydown { id } => {
                 // validate the paydown id
                 if Uuid::parse_str(id).is_err() {
-                    invalid_fields.push("id");
+                    invalid_fields.push(FieldError::new("id", "must be a UUID"));
+                }
+            }
+
+            ExecuteMsg::UpdateBinding { bind_name } => {
+                // validate the name
+                if bind_name.trim().is_empty() {
+                    invalid_fields.push(FieldError::new("bind_name", "must not be blank"));
+                } else if let Some(reason) = invalid_name_reason(bind_name) {
+                    invalid_fields.push(FieldError::new("bind_name", reason));
                 }
             }
+
+            ExecuteMsg::RecordRepayment {
+                pledge_id,
+                reference,
+            } => {
+                // validate the pledge id
+                if Uuid::parse_str(pledge_id).is_err() {
+                    invalid_fields.push(FieldError::new("pledge_id", "must be a UUID"));
+                }
+
+                // validate the repayment reference
+                if reference.trim().is_empty() {
+                    invalid_fields.push(FieldError::new("reference", "must not be empty"));
+                }
+            }
+
+            ExecuteMsg::SetPaused { paused: _ } => {}
+
+            ExecuteMsg::SettleCancelledMarkers {} => {}
+
+            ExecuteMsg::TransferOriginator { new_originator } => {
+                // validate the new originator address
+                if new_originator.trim().is_empty() {
+                    invalid_fields.push(FieldError::new("new_originator", "must not be empty"));
+                }
+            }
+
+            ExecuteMsg::CloseFacility {} => {}
         }
 
         match invalid_fields.len() {
             0 => Ok(()),
             _ => Err(ContractError::InvalidFields {
-                fields: invalid_fields.into_iter().map(|item| item.into()).collect(),
+                errors: invalid_fields,
             }),
         }
     }
@@ -338,6 +838,8 @@ impl Authorize for ExecuteMsg {
                 assets: _,
                 total_advance: _,
                 asset_marker_denom: _,
+                valid_until: _,
+                document_hash: _,
             } => {
                 // only the originator in this facility can propose a pledge
                 if contract_info.facility.originator != sender {
@@ -345,6 +847,31 @@ impl Authorize for ExecuteMsg {
                 }
             }
 
+            ExecuteMsg::ReproposePledge {
+                id: _,
+                assets: _,
+                total_advance: _,
+                asset_marker_denom: _,
+                valid_until: _,
+                document_hash: _,
+            } => {
+                // only the originator in this facility can re-propose a pledge
+                if contract_info.facility.originator != sender {
+                    authorized = false;
+                }
+            }
+
+            ExecuteMsg::AmendPledge {
+                id: _,
+                assets: _,
+                total_advance: _,
+            } => {
+                // only the originator in this facility can amend a pledge
+                if contract_info.facility.originator != sender {
+                    authorized = false;
+                }
+            }
+
             ExecuteMsg::AcceptPledge { id: _ } => {
                 // only the warehouse in this facility can accept a pledge
                 if contract_info.facility.warehouse != sender {
@@ -352,9 +879,28 @@ impl Authorize for ExecuteMsg {
                 }
             }
 
+            ExecuteMsg::AcceptPledges { ids: _ } => {
+                // only the warehouse in this facility can accept pledges
+                if contract_info.facility.warehouse != sender {
+                    authorized = false;
+                }
+            }
+
             ExecuteMsg::CancelPledge { id: _ } => {
-                // only the originator in this facility can cancel a pledge
-                if contract_info.facility.originator != sender {
+                // only the originator or warehouse in this facility can cancel a pledge
+                // NOTE: The warehouse may only cancel a pledge that it hasn't accepted yet,
+                //       therefore the contract also authorizes the warehouse against the
+                //       stored pledge state before dispatching the message.
+                if contract_info.facility.originator != sender
+                    && contract_info.facility.warehouse != sender
+                {
+                    authorized = false;
+                }
+            }
+
+            ExecuteMsg::RejectPledge { id: _, reason: _ } => {
+                // only the warehouse in this facility can reject a pledge
+                if contract_info.facility.warehouse != sender {
                     authorized = false;
                 }
             }
@@ -408,6 +954,52 @@ impl Authorize for ExecuteMsg {
                     authorized = false;
                 }
             }
+
+            ExecuteMsg::RecordRepayment {
+                pledge_id: _,
+                reference: _,
+            } => {
+                // only the warehouse in this facility can record a repayment of its advance
+                if contract_info.facility.warehouse != sender {
+                    authorized = false;
+                }
+            }
+
+            ExecuteMsg::UpdateBinding { bind_name: _ } => {
+                // only the contract admin can update the name binding
+                if contract_info.admin != sender {
+                    authorized = false;
+                }
+            }
+
+            ExecuteMsg::SetPaused { paused: _ } => {
+                // only the contract admin can pause the facility
+                if contract_info.admin != sender {
+                    authorized = false;
+                }
+            }
+
+            ExecuteMsg::SettleCancelledMarkers {} => {
+                // only the contract admin can settle the asset markers of cancelled pledges
+                if contract_info.admin != sender {
+                    authorized = false;
+                }
+            }
+
+            ExecuteMsg::TransferOriginator { new_originator: _ } => {
+                // only the contract admin or the originator in this facility can transfer the
+                // originator role
+                if contract_info.admin != sender && contract_info.facility.originator != sender {
+                    authorized = false;
+                }
+            }
+
+            ExecuteMsg::CloseFacility {} => {
+                // only the contract admin can close the facility
+                if contract_info.admin != sender {
+                    authorized = false;
+                }
+            }
         }
 
         match authorized {
@@ -417,45 +1009,379 @@ impl Authorize for ExecuteMsg {
     }
 }
 
+// The response data for a pledge proposal.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum QueryMsg {
-    // Get the contract info.
-    GetContractInfo {},
+pub struct ProposePledgeResponse {
+    // The proposed pledge.
+    pub pledge: Pledge,
 
-    // Get the facility info.
-    GetFacilityInfo {},
+    // The address of the asset pool marker created for the pledge.
+    pub marker_address: Addr,
+}
 
-    // Get info about a pledge in the facility.
-    GetPledge { id: String },
+// The response data for the remaining credit of the facility.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AvailableCreditResponse {
+    // The most the warehouse will advance across all outstanding pledges (None = no limit).
+    pub limit: Option<Uint128>,
 
-    // List the ids of all pledges in the facility.
-    ListPledgeIds {},
+    // The total advance of the accepted and executed pledges.
+    pub outstanding: Uint128,
 
-    // List info about all pledges in the facility.
-    ListPledges {},
+    // The credit remaining under the limit (None = no limit).
+    pub available: Option<Uint128>,
+}
+
+// The response data for a summary of the facility terms and current activity.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FacilitySummary {
+    // The facility terms.
+    pub facility: Facility,
+
+    // The number of assets in the facility inventory.
+    pub inventory_count: u64,
+
+    // The total advance escrowed for accepted pledges or disbursed for executed pledges that have
+    // not been closed.
+    pub total_outstanding_advance: Uint128,
+
+    // The number of pledge and paydown proposals awaiting acceptance.
+    pub open_proposal_count: u64,
+}
+
+// The response data for simulating the advance on a hypothetical asset valuation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulatePledgeAdvanceResponse {
+    // The advance the warehouse would provide for the asset value at the facility advance rate.
+    pub total_advance: Uint128,
+
+    // The share of the asset value attributed to the warehouse (the advance).
+    pub marker_to_warehouse: Uint128,
+
+    // The share of the asset value retained by the originator.
+    pub marker_to_originator: Uint128,
+}
+
+// The response data for listing pledge or paydown ids.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ListIdsResponse {
+    // The listed ids.
+    pub ids: Vec<String>,
+
+    // The total number of ids in the facility.
+    pub total: u64,
+}
+
+// The response data for listing a page of pledges.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ListPledgesResponse {
+    // The listed pledges.
+    pub pledges: Vec<Pledge>,
+
+    // The id to list the next page of pledges after (None = no more pledges).
+    pub next_start_after: Option<String>,
+}
+
+// The response data for listing the pledges along with the facility parties.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PledgesWithContextResponse {
+    // The address of the originator.
+    pub originator: Addr,
+
+    // The address of the warehouse provider.
+    pub warehouse: Addr,
+
+    // All pledges in the facility.
+    pub pledges: Vec<Pledge>,
+}
+
+// A closed pledge along with the executed paydown(s) that closed it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClosedPledge {
+    // The closed pledge.
+    pub pledge: Pledge,
+
+    // The executed paydowns of the pledge's assets.
+    pub paydowns: Vec<Paydown>,
+}
+
+// The response data for listing a page of closed pledges.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ListClosedPledgesResponse {
+    // The listed closed pledges.
+    pub pledges: Vec<ClosedPledge>,
+
+    // The id to list the next page of closed pledges after (None = no more closed pledges).
+    pub next_start_after: Option<String>,
+}
+
+// The role an address plays in the facility.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PartyRole {
+    // The address is the originator.
+    Originator,
+
+    // The address is the warehouse provider.
+    Warehouse,
+
+    // The address is not a party to the facility.
+    None,
+}
+
+impl From<Option<ContractParty>> for PartyRole {
+    fn from(party: Option<ContractParty>) -> Self {
+        match party {
+            Some(ContractParty::Originator) => PartyRole::Originator,
+            Some(ContractParty::Warehouse) => PartyRole::Warehouse,
+            _ => PartyRole::None,
+        }
+    }
+}
+
+// The response data for the state of a pledge.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PledgeStateResponse {
+    pub id: String,
+    pub state: PledgeState,
+}
+
+// The response data for the state of a paydown.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PaydownStateResponse {
+    pub id: String,
+    pub state: PaydownState,
+}
+
+// The response data for the role an address plays in the facility.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PartyRoleResponse {
+    pub role: PartyRole,
+}
+
+// The response data for a dry-run validation of an instantiate message.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidateInstantiateResponse {
+    // The fields of the instantiate message that are invalid (empty when the message is valid).
+    pub invalid_fields: Vec<String>,
+}
+
+// The response data for listing the pledges with escrowed advances.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EscrowedPledgesResponse {
+    // The accepted pledges whose advance is escrowed but not yet disbursed.
+    pub pledges: Vec<Pledge>,
+
+    // The total advance held in escrow for the pledges.
+    pub total_escrowed: Uint128,
+}
+
+// The stablecoin held in escrow and the advance the pledge records imply it should hold.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EscrowBalance {
+    // The stablecoin denom.
+    pub denom: String,
+
+    // The balance of the denom held by the escrow marker account.
+    pub balance: Uint128,
+
+    // The total advance in the denom escrowed for accepted pledges.
+    pub total_escrowed: Uint128,
+}
+
+// The response data for the escrow balance held by the escrow marker account.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EscrowBalanceResponse {
+    // The escrow balance for each stablecoin denom of the facility.
+    pub balances: Vec<EscrowBalance>,
+}
+
+// The response data for an asset availability check.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CheckAssetsAvailableResponse {
+    // The assets that are free to be pledged.
+    pub available: Vec<String>,
+
+    // The assets that are already pledged, proposed or in the inventory.
+    pub unavailable: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    // Get the contract info.
+    GetContractInfo {},
+
+    // Get the public facility terms and version info (excludes the admin).
+    GetConfig {},
+
+    // Get the contract version info.
+    GetVersion {},
+
+    // Get the role (originator, warehouse or none) the address plays in the facility.
+    GetPartyRole {
+        address: String,
+    },
+
+    // Get the facility info.
+    GetFacilityInfo {},
+
+    // Get the facility info along with a summary of the inventory, advances and proposals.
+    GetFacilitySummary {},
+
+    // Get the facility credit limit, the advance outstanding against it and the credit remaining.
+    GetAvailableCredit {},
+
+    // Get the distribution of the facility marker supply between the warehouse and originator.
+    GetMarkerDistribution {},
+
+    // Simulate the advance and marker split for a hypothetical asset value at the facility rate.
+    SimulatePledgeAdvance {
+        asset_value: u64,
+    },
+
+    // Get info about a pledge in the facility.
+    GetPledge {
+        id: String,
+    },
+
+    // Get only the state of a pledge in the facility (ex: to poll the progress of a pledge).
+    GetPledgeState {
+        id: String,
+    },
+
+    // Get the raw bytes of a pledge as stored in contract state (for off-chain verification).
+    GetPledgeRaw {
+        id: String,
+    },
+
+    // Get the pledge that an asset (a scope UUID or bech32 scope address) is currently pledged
+    // under (proposed, accepted or executed).
+    GetPledgeByAsset {
+        asset: String,
+    },
+
+    // Find the pledges that include any of the assets, optionally only those in a state.
+    FindPledgesByAssets {
+        assets: Vec<String>,
+        state: Option<PledgeState>,
+    },
+
+    // List the ids of all pledges in the facility.
+    ListPledgeIds {
+        // List in descending order (default: ascending).
+        descending: Option<bool>,
+    },
+
+    // List info about the pledges in the facility, one page at a time.
+    ListPledges {
+        // List in descending order (default: ascending).
+        descending: Option<bool>,
+
+        // List the pledges after this pledge id (default: from the first pledge).
+        start_after: Option<String>,
+
+        // The max number of pledges to list (default: 30, capped at 100).
+        limit: Option<u32>,
+    },
 
     // List info about all open pledge proposals in the facility.
-    ListPledgeProposals {},
+    ListPledgeProposals {
+        // List in descending order (default: ascending).
+        descending: Option<bool>,
+    },
+
+    // List the closed pledges in the facility along with their paydowns, one page at a time.
+    ListClosedPledges {
+        // List in descending order (default: ascending).
+        descending: Option<bool>,
+
+        // List the closed pledges after this pledge id (default: from the first pledge).
+        start_after: Option<String>,
+
+        // The max number of closed pledges to list (default: 30, capped at 100).
+        limit: Option<u32>,
+    },
+
+    // List info about all pledges in the facility in any of the specified states.
+    ListPledgesByStates {
+        states: Vec<PledgeState>,
+    },
+
+    // List info about all pledges in the facility along with the facility parties, so that
+    // reporting tools get both in a single call.
+    GetPledgesWithContext {},
 
     // List the ids of all paydowns in the facility.
-    ListPaydownIds {},
+    ListPaydownIds {
+        // List in descending order (default: ascending).
+        descending: Option<bool>,
+    },
 
     // List info about all paydowns in the facility.
-    ListPaydowns {},
+    ListPaydowns {
+        // List in descending order (default: ascending).
+        descending: Option<bool>,
+    },
 
     // List info about all open paydown proposals in the facility.
-    ListPaydownProposals {},
+    ListPaydownProposals {
+        // List in descending order (default: ascending).
+        descending: Option<bool>,
+    },
 
     // Get info about a paydown in the facility.
-    GetPaydown { id: String },
+    GetPaydown {
+        id: String,
+    },
+
+    // Get only the state of a paydown in the facility (ex: to poll the progress of a paydown).
+    GetPaydownState {
+        id: String,
+    },
 
     // List the assets currently involved in the facility (whether
     // proposed for pledge/paydown or currently in the inventory).
     ListAssets {},
 
+    // List the assets currently in the specified state in the facility (ex: the assets tied up
+    // in pending paydowns).
+    ListAssetsByState {
+        state: AssetState,
+    },
+
+    // Get the state transitions of an asset in the facility (oldest first).
+    GetAssetHistory {
+        id: String,
+    },
+
     // List the assets currently in the facility inventory.
     ListInventory {},
+
+    // List the distinct scopes of all the assets the facility has ever tracked (including the
+    // assets of closed, cancelled and rejected pledges and paydowns) as bech32 scope addresses.
+    ListAllScopes {},
+
+    // Count the asset pool markers that are still live (one per pledge that
+    // has not been cancelled or closed).
+    CountActiveMarkers {},
+
+    // Check which of the specified assets are free to be pledged.
+    CheckAssetsAvailable {
+        assets: Vec<String>,
+    },
+
+    // List the accepted pledges whose advance is escrowed but not yet disbursed.
+    ListEscrowedPledges {},
+
+    // Get the stablecoin balance held by the escrow marker alongside the advance escrowed for
+    // accepted pledges, for each stablecoin denom of the facility.
+    GetEscrowBalance {},
+
+    // Validate a prospective instantiate message without instantiating a contract.
+    ValidateInstantiate {
+        msg: InstantiateMsg,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -463,3 +1389,884 @@ pub enum QueryMsg {
 pub enum MigrateMsg {
     Migrate {},
 }
+
+// The response data for a migration, so that upgrade tooling can confirm what it migrated.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateResponse {
+    // The contract version before the migration.
+    pub from_version: String,
+
+    // The contract version after the migration.
+    pub to_version: String,
+
+    // Whether any stored state was rewritten (including the version).
+    pub migrated: bool,
+}
+
+// Messages that can only be sent by the chain (ex: through a governance proposal).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SudoMsg {
+    // Freeze the facility, pausing it so that the admin can't resume it until unfrozen.
+    Freeze {},
+
+    // Lift a governance freeze of the facility, resuming it.
+    Unfreeze {},
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contract_info::ContractInfo;
+    use crate::error::{ContractError, FieldError};
+    use crate::msg::{
+        Authorize, ExecuteMsg, FacilityBuilder, InstantiateMsg, Validate, MAX_ASSETS_PER_PLEDGE,
+    };
+    use crate::state::{Facility, RoundingMode};
+    use crate::utils::MetadataAddress;
+    use cosmwasm_std::{Addr, Uint128};
+    use uuid::Uuid;
+
+    const PLEDGE_ID: &str = "0db2a8c5-3b6b-4b63-9a09-1c3a8a1b2b5e";
+    const ASSET_ID_1: &str = "7a6e2d4c-9d0e-4c3f-8e51-4f3b5d2c1a90";
+    const ASSET_ID_2: &str = "c3d1a6b8-2f4e-4a57-b0c9-8e7d6f5a4b32";
+
+    fn instantiate_msg(facility: Facility) -> InstantiateMsg {
+        InstantiateMsg {
+            bind_name: "contract-bind-name".into(),
+            contract_name: "contract_name".into(),
+            facility,
+        }
+    }
+
+    fn facility() -> Facility {
+        Facility {
+            originator: Addr::unchecked("contract_originator"),
+            warehouse: Addr::unchecked("contract_warehouse"),
+            escrow_marker: Addr::unchecked("escrow_marker"),
+            marker_denom: "test.denom.wf1".into(),
+            stablecoin_denoms: vec!["test.denom.stable".into()],
+            advance_rate: "75.125".into(),
+            paydown_rate: "102.25".into(),
+            rounding: None,
+            max_total_advance: None,
+        }
+    }
+
+    #[test]
+    pub fn validate_amend_pledge() {
+        let amend = |assets: Option<Vec<&str>>, total_advance: Option<u128>| {
+            ExecuteMsg::AmendPledge {
+                id: PLEDGE_ID.into(),
+                assets: assets.map(|assets| assets.iter().map(|a| a.to_string()).collect()),
+                total_advance: total_advance.map(Uint128::new),
+            }
+            .validate()
+        };
+
+        // either of the terms may be amended on its own
+        assert!(amend(Some(vec![ASSET_ID_1]), None).is_ok());
+        assert!(amend(None, Some(100)).is_ok());
+
+        let invalid_amendments = vec![
+            (None, None, "assets or total_advance must be specified"),
+            (Some(vec![]), Some(100), "assets must not be empty"),
+            (
+                Some(vec![ASSET_ID_1, ASSET_ID_1]),
+                None,
+                "assets must not contain duplicates",
+            ),
+        ];
+        for (assets, total_advance, error) in invalid_amendments {
+            match amend(assets, total_advance) {
+                Err(err @ ContractError::InvalidFields { .. }) => {
+                    assert_eq!(err.to_string(), format!("Invalid fields: {}", error))
+                }
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    pub fn validate_instantiate_with_valid_data() {
+        match instantiate_msg(facility()).validate() {
+            Ok(()) => {}
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    fn facility_builder() -> FacilityBuilder {
+        FacilityBuilder::new()
+            .originator("contract_originator")
+            .warehouse("contract_warehouse")
+            .escrow_marker("escrow_marker")
+            .marker_denom("test.denom.wf1")
+            .stablecoin_denom("test.denom.stable")
+            .advance_rate("75.125")
+            .paydown_rate("102.25")
+    }
+
+    #[test]
+    pub fn build_instantiate_msg() {
+        let msg = InstantiateMsg::builder()
+            .bind_name("contract-bind-name")
+            .contract_name("contract_name")
+            .facility(facility_builder())
+            .build()
+            .unwrap();
+        assert_eq!(msg, instantiate_msg(facility()));
+        assert!(msg.validate().is_ok());
+
+        // the optional terms can be set
+        let facility = facility_builder()
+            .rounding(RoundingMode::HalfEven)
+            .max_total_advance(1000u128)
+            .build()
+            .unwrap();
+        assert_eq!(facility.rounding, Some(RoundingMode::HalfEven));
+        assert_eq!(facility.max_total_advance, Some(Uint128::new(1000)));
+    }
+
+    #[test]
+    pub fn build_instantiate_msg_without_required_fields() {
+        match InstantiateMsg::builder()
+            .bind_name("contract-bind-name")
+            .facility(facility_builder())
+            .build()
+        {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new("contract_name", "must not be empty")]
+                );
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let facility = FacilityBuilder::new()
+            .originator("contract_originator")
+            .escrow_marker("escrow_marker")
+            .marker_denom("test.denom.wf1")
+            .stablecoin_denom("test.denom.stable")
+            .paydown_rate("102.25");
+        match facility.build() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![
+                        FieldError::new("facility.warehouse", "must not be empty"),
+                        FieldError::new("facility.advance_rate", "must be a decimal"),
+                    ]
+                );
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_instantiate_with_matching_denoms() {
+        let mut facility = facility();
+        facility
+            .stablecoin_denoms
+            .push(facility.marker_denom.clone());
+        match instantiate_msg(facility).validate() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new(
+                        "facility.stablecoin_denoms",
+                        "must not include the facility marker denom"
+                    )]
+                );
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_instantiate_with_multiple_stablecoin_denoms() {
+        let mut facility = facility();
+        facility.stablecoin_denoms.push("test.denom.stable2".into());
+        match instantiate_msg(facility).validate() {
+            Ok(()) => {}
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_instantiate_with_invalid_stablecoin_denoms() {
+        let invalid_denoms: Vec<(Vec<String>, &str)> = vec![
+            (vec![], "must include at least one denom"),
+            (vec!["".into()], "must not include an empty denom"),
+            (
+                vec!["test.denom.stable".into(), "test.denom.stable".into()],
+                "must not include duplicate denoms",
+            ),
+        ];
+        for (denoms, reason) in invalid_denoms {
+            let mut facility = facility();
+            facility.stablecoin_denoms = denoms;
+            match instantiate_msg(facility).validate() {
+                Err(ContractError::InvalidFields { errors }) => {
+                    assert_eq!(
+                        errors,
+                        vec![FieldError::new("facility.stablecoin_denoms", reason)]
+                    );
+                }
+                result => panic!("unexpected error: {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    pub fn validate_propose_pledge_with_unique_assets() {
+        let msg = ExecuteMsg::ProposePledge {
+            id: PLEDGE_ID.into(),
+            assets: vec![ASSET_ID_1.into(), ASSET_ID_2.into()],
+            total_advance: Uint128::new(100),
+            asset_marker_denom: "test.denom.pledge1".into(),
+            valid_until: None,
+            document_hash: None,
+        };
+        match msg.validate() {
+            Ok(()) => {}
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_duplicate_assets_in_every_message() {
+        let assets = vec![ASSET_ID_1.to_string(), ASSET_ID_1.to_string()];
+        let msgs = vec![
+            ExecuteMsg::ProposePledge {
+                id: PLEDGE_ID.into(),
+                assets: assets.clone(),
+                total_advance: Uint128::new(100),
+                asset_marker_denom: "test.denom.pledge1".into(),
+                valid_until: None,
+                document_hash: None,
+            },
+            ExecuteMsg::ReproposePledge {
+                id: PLEDGE_ID.into(),
+                assets: assets.clone(),
+                total_advance: Uint128::new(100),
+                asset_marker_denom: "test.denom.pledge1".into(),
+                valid_until: None,
+                document_hash: None,
+            },
+            ExecuteMsg::AmendPledge {
+                id: PLEDGE_ID.into(),
+                assets: Some(assets.clone()),
+                total_advance: None,
+            },
+            ExecuteMsg::ProposePaydown {
+                id: PLEDGE_ID.into(),
+                assets: assets.clone(),
+                total_paydown: Uint128::new(100),
+            },
+            ExecuteMsg::ProposePaydownAndSell {
+                id: PLEDGE_ID.into(),
+                assets,
+                total_paydown: Uint128::new(100),
+                buyer: Addr::unchecked("contract_buyer"),
+                purchase_price: 200,
+            },
+        ];
+        for msg in msgs {
+            match msg.validate() {
+                Err(ContractError::InvalidFields { errors }) => {
+                    assert_eq!(
+                        errors,
+                        vec![FieldError::new("assets", "must not contain duplicates")]
+                    );
+                }
+                result => panic!("unexpected result for {:?}: {:?}", msg, result),
+            }
+        }
+    }
+
+    #[test]
+    pub fn validate_repropose_pledge_with_document_hash() {
+        let repropose = |document_hash: &str| ExecuteMsg::ReproposePledge {
+            id: PLEDGE_ID.into(),
+            assets: vec![ASSET_ID_1.into()],
+            total_advance: Uint128::new(100),
+            asset_marker_denom: "test.denom.pledge1".into(),
+            valid_until: None,
+            document_hash: Some(document_hash.into()),
+        };
+
+        assert!(
+            repropose("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08")
+                .validate()
+                .is_ok()
+        );
+        match repropose("not-a-hash").validate() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new(
+                        "document_hash",
+                        "must be a hex SHA-256 hash (64 hex characters)"
+                    )]
+                );
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_propose_pledge_with_document_hash() {
+        let propose = |document_hash: Option<&str>| ExecuteMsg::ProposePledge {
+            id: PLEDGE_ID.into(),
+            assets: vec![ASSET_ID_1.into()],
+            total_advance: Uint128::new(100),
+            asset_marker_denom: "test.denom.pledge1".into(),
+            valid_until: None,
+            document_hash: document_hash.map(|hash| hash.into()),
+        };
+        let hash = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+        // a hash of 64 hex characters (in either case) is valid, as is no hash at all
+        assert!(propose(Some(hash)).validate().is_ok());
+        assert!(propose(Some(hash.to_uppercase().as_str()))
+            .validate()
+            .is_ok());
+        assert!(propose(None).validate().is_ok());
+
+        let invalid_hashes = vec![
+            hash[..63].to_string(),
+            format!("{}0", hash),
+            hash.replace('a', "g"),
+            "".to_string(),
+        ];
+        for invalid_hash in invalid_hashes {
+            match propose(Some(invalid_hash.as_str())).validate() {
+                Err(ContractError::InvalidFields { errors }) => {
+                    assert_eq!(
+                        errors,
+                        vec![FieldError::new(
+                            "document_hash",
+                            "must be a hex SHA-256 hash (64 hex characters)"
+                        )]
+                    );
+                }
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    pub fn validate_propose_pledge_with_duplicate_assets() {
+        let msg = ExecuteMsg::ProposePledge {
+            id: PLEDGE_ID.into(),
+            assets: vec![ASSET_ID_1.into(), ASSET_ID_2.into(), ASSET_ID_1.into()],
+            total_advance: Uint128::new(100),
+            asset_marker_denom: "test.denom.pledge1".into(),
+            valid_until: None,
+            document_hash: None,
+        };
+        match msg.validate() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new("assets", "must not contain duplicates")]
+                );
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_propose_paydown_with_duplicate_assets() {
+        let msg = ExecuteMsg::ProposePaydown {
+            id: PLEDGE_ID.into(),
+            assets: vec![ASSET_ID_2.into(), ASSET_ID_2.into()],
+            total_paydown: Uint128::new(100),
+        };
+        match msg.validate() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new("assets", "must not contain duplicates")]
+                );
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_propose_pledge_with_zero_advance() {
+        let propose = |total_advance: u128| ExecuteMsg::ProposePledge {
+            id: PLEDGE_ID.into(),
+            assets: vec![ASSET_ID_1.into()],
+            total_advance: Uint128::new(total_advance),
+            asset_marker_denom: "test.denom.pledge1".into(),
+            valid_until: None,
+            document_hash: None,
+        };
+        match propose(0).validate() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new("total_advance", "must be greater than 0")]
+                );
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+        assert!(propose(1).validate().is_ok());
+    }
+
+    #[test]
+    pub fn validate_propose_paydown_with_zero_paydown() {
+        let propose = |total_paydown: u128| ExecuteMsg::ProposePaydown {
+            id: PLEDGE_ID.into(),
+            assets: vec![ASSET_ID_1.into()],
+            total_paydown: Uint128::new(total_paydown),
+        };
+        match propose(0).validate() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new("total_paydown", "must be greater than 0")]
+                );
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+        assert!(propose(1).validate().is_ok());
+    }
+
+    fn assets(count: usize) -> Vec<String> {
+        (0..count)
+            .map(|i| Uuid::from_u128(i as u128 + 1).to_string())
+            .collect()
+    }
+
+    #[test]
+    pub fn validate_propose_pledge_with_max_assets() {
+        let msg = ExecuteMsg::ProposePledge {
+            id: PLEDGE_ID.into(),
+            assets: assets(MAX_ASSETS_PER_PLEDGE),
+            total_advance: Uint128::new(100),
+            asset_marker_denom: "test.denom.pledge1".into(),
+            valid_until: None,
+            document_hash: None,
+        };
+        match msg.validate() {
+            Ok(()) => {}
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_propose_pledge_with_too_many_assets() {
+        let msg = ExecuteMsg::ProposePledge {
+            id: PLEDGE_ID.into(),
+            assets: assets(MAX_ASSETS_PER_PLEDGE + 1),
+            total_advance: Uint128::new(100),
+            asset_marker_denom: "test.denom.pledge1".into(),
+            valid_until: None,
+            document_hash: None,
+        };
+        match msg.validate() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new(
+                        "assets",
+                        format!(
+                            "must not contain more than {} assets",
+                            MAX_ASSETS_PER_PLEDGE
+                        )
+                    )]
+                );
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_propose_paydown_with_max_assets() {
+        let msg = ExecuteMsg::ProposePaydown {
+            id: PLEDGE_ID.into(),
+            assets: assets(MAX_ASSETS_PER_PLEDGE),
+            total_paydown: Uint128::new(100),
+        };
+        match msg.validate() {
+            Ok(()) => {}
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_propose_paydown_with_too_many_assets() {
+        let msg = ExecuteMsg::ProposePaydown {
+            id: PLEDGE_ID.into(),
+            assets: assets(MAX_ASSETS_PER_PLEDGE + 1),
+            total_paydown: Uint128::new(100),
+        };
+        match msg.validate() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new(
+                        "assets",
+                        format!(
+                            "must not contain more than {} assets",
+                            MAX_ASSETS_PER_PLEDGE
+                        )
+                    )]
+                );
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_update_binding_with_blank_name() {
+        let msg = ExecuteMsg::UpdateBinding {
+            bind_name: " ".into(),
+        };
+        match msg.validate() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new("bind_name", "must not be blank")]
+                );
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_bind_names() {
+        let too_many_segments = vec!["wf"; 17].join(".");
+        let charset = "must contain only lowercase letters, digits, hyphens and dots";
+        let length = "segments must be 2 to 32 characters";
+        let invalid_names = vec![
+            ("Warehouse.sc.pb", charset),
+            ("warehouse_facility.sc.pb", charset),
+            ("warehouse..pb", length),
+            ("warehouse.sc.p", length),
+            (
+                too_many_segments.as_str(),
+                "must not have more than 16 segments",
+            ),
+        ];
+        for (name, reason) in invalid_names {
+            let mut msg = instantiate_msg(facility());
+            msg.bind_name = name.into();
+            match msg.validate() {
+                Err(ContractError::InvalidFields { errors }) => {
+                    assert_eq!(errors, vec![FieldError::new("bind_name", reason)]);
+                }
+                result => panic!("unexpected error: {:?}", result),
+            }
+
+            let msg = ExecuteMsg::UpdateBinding {
+                bind_name: name.into(),
+            };
+            match msg.validate() {
+                Err(ContractError::InvalidFields { errors }) => {
+                    assert_eq!(errors, vec![FieldError::new("bind_name", reason)]);
+                }
+                result => panic!("unexpected error: {:?}", result),
+            }
+        }
+
+        let mut msg = instantiate_msg(facility());
+        msg.bind_name = "warehouse-facility-2.sc.pb".into();
+        assert!(msg.validate().is_ok());
+    }
+
+    #[test]
+    pub fn validate_instantiate_reports_reasons_for_each_field() {
+        let mut facility = facility();
+        facility.advance_rate = "101".into();
+        facility.paydown_rate = "abc".into();
+        let mut msg = instantiate_msg(facility);
+        msg.bind_name = "".into();
+        match msg.validate() {
+            Err(error) => {
+                assert_eq!(
+                    error.to_string(),
+                    "Invalid fields: bind_name must not be empty; \
+                     facility.advance_rate must be in (0,100]; \
+                     facility.paydown_rate must be a decimal"
+                );
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_propose_pledge_with_empty_assets_and_invalid_ids() {
+        let msg = ExecuteMsg::ProposePledge {
+            id: "not-a-uuid".into(),
+            assets: vec![],
+            total_advance: Uint128::new(100),
+            asset_marker_denom: "".into(),
+            valid_until: None,
+            document_hash: None,
+        };
+        match msg.validate() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![
+                        FieldError::new("id", "must be a UUID"),
+                        FieldError::new("assets", "must not be empty"),
+                        FieldError::new("asset_marker_denom", "must not be empty"),
+                    ]
+                );
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    fn scope_address(asset: &str) -> String {
+        MetadataAddress::for_scope(Uuid::parse_str(asset).unwrap()).to_string()
+    }
+
+    #[test]
+    pub fn validate_propose_pledge_with_uuid_and_scope_address_assets() {
+        let msg = ExecuteMsg::ProposePledge {
+            id: PLEDGE_ID.into(),
+            assets: vec![ASSET_ID_1.into(), scope_address(ASSET_ID_2)],
+            total_advance: Uint128::new(100),
+            asset_marker_denom: "test.denom.pledge1".into(),
+            valid_until: None,
+            document_hash: None,
+        };
+        match msg.validate() {
+            Ok(()) => {}
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_propose_pledge_with_same_asset_as_uuid_and_scope_address() {
+        let msg = ExecuteMsg::ProposePledge {
+            id: PLEDGE_ID.into(),
+            assets: vec![ASSET_ID_1.into(), scope_address(ASSET_ID_1)],
+            total_advance: Uint128::new(100),
+            asset_marker_denom: "test.denom.pledge1".into(),
+            valid_until: None,
+            document_hash: None,
+        };
+        match msg.validate() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new("assets", "must not contain duplicates")]
+                );
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_propose_paydown_with_non_scope_address_asset() {
+        let session_address = MetadataAddress::for_session(
+            Uuid::parse_str(ASSET_ID_1).unwrap(),
+            Uuid::parse_str(ASSET_ID_2).unwrap(),
+        )
+        .to_string();
+        let msg = ExecuteMsg::ProposePaydown {
+            id: PLEDGE_ID.into(),
+            assets: vec![scope_address(ASSET_ID_1), session_address],
+            total_paydown: Uint128::new(100),
+        };
+        match msg.validate() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new(
+                        "asset",
+                        "must be a UUID or a bech32 scope address"
+                    )]
+                );
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_accept_pledges_with_duplicate_ids() {
+        let msg = ExecuteMsg::AcceptPledges {
+            ids: vec![PLEDGE_ID.into(), PLEDGE_ID.into()],
+        };
+        match msg.validate() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new("ids", "must not contain duplicates")]
+                );
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn validate_accept_pledges_without_ids() {
+        let msg = ExecuteMsg::AcceptPledges { ids: vec![] };
+        match msg.validate() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(errors, vec![FieldError::new("ids", "must not be empty")]);
+            }
+            result => panic!("unexpected error: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn authorize_each_message_to_its_parties() {
+        let admin = "contract_admin";
+        let originator = "contract_originator";
+        let warehouse = "contract_warehouse";
+        let contract_info = ContractInfo::new(
+            Addr::unchecked(admin),
+            "contract-bind-name".into(),
+            "contract_name".into(),
+            "ver".into(),
+            facility(),
+        );
+
+        // the parties allowed to send each message (any party may accept a paydown, the handler
+        // checks that the sender is the warehouse or the buyer)
+        let any_party = vec![admin, originator, warehouse, "third_party"];
+        let authorizations = vec![
+            (
+                ExecuteMsg::ProposePledge {
+                    id: PLEDGE_ID.into(),
+                    assets: vec![ASSET_ID_1.into()],
+                    total_advance: Uint128::new(100),
+                    asset_marker_denom: "test.denom.pledge1".into(),
+                    valid_until: None,
+                    document_hash: None,
+                },
+                vec![originator],
+            ),
+            (
+                ExecuteMsg::ReproposePledge {
+                    id: PLEDGE_ID.into(),
+                    assets: vec![ASSET_ID_1.into()],
+                    total_advance: Uint128::new(100),
+                    asset_marker_denom: "test.denom.pledge1".into(),
+                    valid_until: None,
+                    document_hash: None,
+                },
+                vec![originator],
+            ),
+            (
+                ExecuteMsg::AmendPledge {
+                    id: PLEDGE_ID.into(),
+                    assets: None,
+                    total_advance: Some(Uint128::new(100)),
+                },
+                vec![originator],
+            ),
+            (
+                ExecuteMsg::AcceptPledge {
+                    id: PLEDGE_ID.into(),
+                },
+                vec![warehouse],
+            ),
+            (
+                ExecuteMsg::AcceptPledges {
+                    ids: vec![PLEDGE_ID.into()],
+                },
+                vec![warehouse],
+            ),
+            (
+                ExecuteMsg::CancelPledge {
+                    id: PLEDGE_ID.into(),
+                },
+                vec![originator, warehouse],
+            ),
+            (
+                ExecuteMsg::RejectPledge {
+                    id: PLEDGE_ID.into(),
+                    reason: None,
+                },
+                vec![warehouse],
+            ),
+            (
+                ExecuteMsg::ExecutePledge {
+                    id: PLEDGE_ID.into(),
+                },
+                vec![originator],
+            ),
+            (
+                ExecuteMsg::ProposePaydown {
+                    id: PLEDGE_ID.into(),
+                    assets: vec![ASSET_ID_1.into()],
+                    total_paydown: Uint128::new(100),
+                },
+                vec![originator],
+            ),
+            (
+                ExecuteMsg::ProposePaydownAndSell {
+                    id: PLEDGE_ID.into(),
+                    assets: vec![ASSET_ID_1.into()],
+                    total_paydown: Uint128::new(100),
+                    buyer: Addr::unchecked("contract_buyer"),
+                    purchase_price: 200,
+                },
+                vec![originator],
+            ),
+            (
+                ExecuteMsg::AcceptPaydown {
+                    id: PLEDGE_ID.into(),
+                },
+                any_party.clone(),
+            ),
+            (
+                ExecuteMsg::CancelPaydown {
+                    id: PLEDGE_ID.into(),
+                },
+                vec![originator],
+            ),
+            (
+                ExecuteMsg::ExecutePaydown {
+                    id: PLEDGE_ID.into(),
+                },
+                vec![originator],
+            ),
+            (
+                ExecuteMsg::RecordRepayment {
+                    pledge_id: PLEDGE_ID.into(),
+                    reference: "wire-1".into(),
+                },
+                vec![warehouse],
+            ),
+            (
+                ExecuteMsg::UpdateBinding {
+                    bind_name: "new-bind-name".into(),
+                },
+                vec![admin],
+            ),
+            (ExecuteMsg::SetPaused { paused: true }, vec![admin]),
+            (ExecuteMsg::SettleCancelledMarkers {}, vec![admin]),
+            (
+                ExecuteMsg::TransferOriginator {
+                    new_originator: "new_originator".into(),
+                },
+                vec![admin, originator],
+            ),
+            (ExecuteMsg::CloseFacility {}, vec![admin]),
+        ];
+
+        for (msg, allowed) in authorizations {
+            for sender in &any_party {
+                let result = msg.authorize(contract_info.clone(), Addr::unchecked(*sender));
+                match result {
+                    Ok(()) if allowed.contains(sender) => {}
+                    Err(ContractError::Unauthorized {}) if !allowed.contains(sender) => {}
+                    result => panic!(
+                        "unexpected result for {:?} from {}: {:?}",
+                        msg, sender, result
+                    ),
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,12 @@
+use crate::error::ContractError;
 use crate::utils::vec_has_any;
-use cosmwasm_std::{Addr, Order, StdResult, Storage};
+use cosmwasm_std::{from_slice, Addr, Order, StdError, StdResult, Storage, Timestamp, Uint128};
 use cw_storage_plus::{Bound, Map};
+use rust_decimal::prelude::FromStr;
+use rust_decimal::{Decimal, RoundingStrategy};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::any::type_name;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Facility {
@@ -19,8 +23,9 @@ pub struct Facility {
     // ownership of assets in this facility.
     pub marker_denom: String,
 
-    // The stablecoin denom used for the advance from the warehouse.
-    pub stablecoin_denom: String,
+    // The stablecoin denoms accepted for the advance from the warehouse, paydowns and purchases
+    // (the first denom is used for pledges and paydowns that predate multiple denoms).
+    pub stablecoin_denoms: Vec<String>,
 
     // The advance rate of the facility agreement with the warehouse
     // as a percentage (for example: "75.125" = 75.125%).
@@ -29,6 +34,93 @@ pub struct Facility {
     // The paydown rate of the facility agreement with the warehouse
     // as a percentage of the UPB (for example: "77.25" = 77.25%).
     pub paydown_rate: String,
+
+    // How the warehouse share of a value split at the advance rate is rounded to a whole amount
+    // (None = truncate).
+    #[serde(default)]
+    pub rounding: Option<RoundingMode>,
+
+    // The most the warehouse will advance across all outstanding (accepted or executed) pledges
+    // (None = no limit).
+    #[serde(default)]
+    pub max_total_advance: Option<Uint128>,
+}
+
+// How a fractional warehouse share is rounded to a whole amount when splitting a value between the
+// warehouse and the originator at the advance rate. The originator receives the remainder, so
+// rounding the warehouse share up favors the warehouse and rounding it down favors the originator.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    // Round the warehouse share down (for example: 16666.5 -> 16666), which always favors the
+    // originator.
+    Truncate,
+
+    // Round the warehouse share to the nearest whole amount with halves rounded up (for example:
+    // 16666.5 -> 16667), which favors the warehouse on an exact half.
+    HalfUp,
+
+    // Round the warehouse share to the nearest whole amount with halves rounded to the even amount
+    // (banker's rounding, for example: 16666.5 -> 16666 and 49999.5 -> 50000), which favors
+    // neither party on average.
+    HalfEven,
+}
+
+impl RoundingMode {
+    fn strategy(&self) -> RoundingStrategy {
+        match self {
+            RoundingMode::Truncate => RoundingStrategy::ToZero,
+            RoundingMode::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            RoundingMode::HalfEven => RoundingStrategy::MidpointNearestEven,
+        }
+    }
+}
+
+impl Facility {
+    // Parse the advance rate (ignoring surrounding whitespace), which must be greater than 0% and
+    // at most 100%.
+    pub fn advance_rate_decimal(&self) -> Result<Decimal, ContractError> {
+        let field = "facility.advance_rate";
+        match Decimal::from_str(self.advance_rate.trim()) {
+            Ok(rate) if rate > Decimal::from(0) && rate <= Decimal::from(100) => Ok(rate),
+            Ok(_) => Err(ContractError::invalid_field(field, "must be in (0,100]")),
+            Err(_) => Err(ContractError::invalid_field(field, "must be a decimal")),
+        }
+    }
+
+    // Round the warehouse share of a split to a whole amount using the facility rounding mode.
+    pub fn round_warehouse_share(&self, share: Decimal) -> Decimal {
+        let mode = self.rounding.clone().unwrap_or(RoundingMode::Truncate);
+        share.round_dp_with_strategy(0, mode.strategy())
+    }
+
+    // Parse the paydown rate (ignoring surrounding whitespace), which must be greater than 0%.
+    pub fn paydown_rate_decimal(&self) -> Result<Decimal, ContractError> {
+        let field = "facility.paydown_rate";
+        match Decimal::from_str(self.paydown_rate.trim()) {
+            Ok(rate) if rate > Decimal::from(0) => Ok(rate),
+            Ok(_) => Err(ContractError::invalid_field(
+                field,
+                "must be greater than 0",
+            )),
+            Err(_) => Err(ContractError::invalid_field(field, "must be a decimal")),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarkerDistribution {
+    // The total supply of the facility marker.
+    pub total_supply: Uint128,
+
+    // The amount of the facility marker distributed to the warehouse.
+    pub to_warehouse: Uint128,
+
+    // The amount of the facility marker distributed to the originator.
+    pub to_originator: Uint128,
+
+    // The advance rate used to calculate the distribution.
+    pub advance_rate: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -43,6 +135,9 @@ pub enum PledgeState {
     // The originator has cancelled the pledge proposal.
     Cancelled,
 
+    // The warehouse has rejected the pledge proposal.
+    Rejected,
+
     // The originator has executed the pledge.
     Executed,
 
@@ -50,13 +145,64 @@ pub enum PledgeState {
     Closed,
 }
 
+impl PledgeState {
+    // The states that a pledge in this state may legally transition to.
+    pub fn allowed_next(&self) -> Vec<PledgeState> {
+        match self {
+            PledgeState::Proposed => vec![
+                PledgeState::Accepted,
+                PledgeState::Cancelled,
+                PledgeState::Rejected,
+            ],
+            PledgeState::Accepted => vec![PledgeState::Cancelled, PledgeState::Executed],
+            PledgeState::Cancelled => vec![],
+            PledgeState::Rejected => vec![],
+            PledgeState::Executed => vec![PledgeState::Closed],
+            PledgeState::Closed => vec![],
+        }
+    }
+
+    // Check if a pledge in this state may transition to the specified state.
+    pub fn can_transition_to(&self, next: &PledgeState) -> bool {
+        self.allowed_next().contains(next)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Pledge {
     pub id: String,
     pub assets: Vec<String>,
-    pub total_advance: u64,
+    pub total_advance: Uint128,
     pub asset_marker_denom: String,
     pub state: PledgeState,
+
+    // The block time at which the pledge was proposed.
+    pub proposed_at: Timestamp,
+
+    // The block time at which the pledge was accepted by the warehouse.
+    pub accepted_at: Option<Timestamp>,
+
+    // The block time at which the pledge was executed by the originator.
+    pub executed_at: Option<Timestamp>,
+
+    // The stablecoin denom the warehouse sent the advance in (None = not yet accepted).
+    #[serde(default)]
+    pub advance_denom: Option<String>,
+
+    // The block time after which the warehouse can no longer accept the proposal (None = no
+    // deadline).
+    #[serde(default)]
+    pub valid_until: Option<Timestamp>,
+
+    // The hex SHA-256 hash of the off-chain pledge agreement the proposal is bound to (None = no
+    // document).
+    #[serde(default)]
+    pub document_hash: Option<String>,
+
+    // The external reference (ex: a wire transfer id) of a repayment of the advance made outside of
+    // the facility, recorded when the warehouse closes the pledge (None = not repaid externally).
+    #[serde(default)]
+    pub repayment_reference: Option<String>,
 }
 
 pub const NAMESPACE_PLEDGES: &str = "pledges";
@@ -66,24 +212,109 @@ pub fn load_pledge(storage: &dyn Storage, key: &[u8]) -> StdResult<Pledge> {
     PLEDGES.load(storage, key)
 }
 
+pub fn may_load_pledge(storage: &dyn Storage, key: &[u8]) -> StdResult<Option<Pledge>> {
+    PLEDGES.may_load(storage, key)
+}
+
+// The raw storage key of the pledge with the specified id, for reading contract state directly.
+// NOTE: The storage key is the length of the namespace as a 2-byte big-endian integer, followed
+//       by the namespace and the pledge id: [0x00, 0x07] + b"pledges" + id.as_bytes()
+pub fn pledge_storage_key(id: &str) -> Vec<u8> {
+    PLEDGES.key(id.as_bytes()).to_vec()
+}
+
+// Load the serialized pledge exactly as it is stored in contract state.
+pub fn load_pledge_raw(storage: &dyn Storage, key: &[u8]) -> StdResult<Vec<u8>> {
+    storage
+        .get(&PLEDGES.key(key))
+        .ok_or_else(|| StdError::not_found(type_name::<Pledge>()))
+}
+
+// The state of a stored pledge or paydown, read without deserializing the rest of the record.
+#[derive(Deserialize)]
+struct StoredState<S> {
+    state: S,
+}
+
+// Load only the state of a pledge (ex: for clients polling the progress of a pledge).
+pub fn load_pledge_state(storage: &dyn Storage, key: &[u8]) -> StdResult<PledgeState> {
+    let raw = load_pledge_raw(storage, key)?;
+    Ok(from_slice::<StoredState<PledgeState>>(&raw)?.state)
+}
+
 pub fn save_pledge(storage: &mut dyn Storage, key: &[u8], pledge: &Pledge) -> StdResult<()> {
     PLEDGES.save(storage, key, pledge)
 }
 
+// The pledge format stored before the total advance was widened to a Uint128.
+#[derive(Serialize, Deserialize)]
+struct LegacyPledge {
+    id: String,
+    assets: Vec<String>,
+    total_advance: u64,
+    asset_marker_denom: String,
+    state: PledgeState,
+    #[serde(default)]
+    proposed_at: Timestamp,
+    #[serde(default)]
+    accepted_at: Option<Timestamp>,
+    #[serde(default)]
+    executed_at: Option<Timestamp>,
+}
+
+impl From<LegacyPledge> for Pledge {
+    fn from(legacy: LegacyPledge) -> Self {
+        Pledge {
+            id: legacy.id,
+            assets: legacy.assets,
+            total_advance: Uint128::from(legacy.total_advance),
+            asset_marker_denom: legacy.asset_marker_denom,
+            state: legacy.state,
+            proposed_at: legacy.proposed_at,
+            accepted_at: legacy.accepted_at,
+            executed_at: legacy.executed_at,
+            advance_denom: None,
+            valid_until: None,
+            document_hash: None,
+            repayment_reference: None,
+        }
+    }
+}
+
+const LEGACY_PLEDGES: Map<&[u8], LegacyPledge> = Map::new(NAMESPACE_PLEDGES);
+
+// Rewrite any pledges stored in the legacy format, returning how many were migrated.
+pub fn migrate_pledges(storage: &mut dyn Storage) -> StdResult<u64> {
+    let keys = PLEDGES
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<Vec<Vec<u8>>>();
+
+    let mut migrated = 0;
+    for key in keys {
+        if PLEDGES.load(storage, &key).is_ok() {
+            continue;
+        }
+
+        let legacy = LEGACY_PLEDGES.load(storage, &key)?;
+        PLEDGES.save(storage, &key, &legacy.into())?;
+        migrated += 1;
+    }
+
+    Ok(migrated)
+}
+
 pub fn get_pledge_ids(
     storage: &dyn Storage,
     state: Option<PledgeState>,
     min: Option<Bound>,
     max: Option<Bound>,
+    order: Order,
 ) -> StdResult<Vec<String>> {
     Ok(PLEDGES
-        .keys(storage, min, max, Order::Ascending)
-        .filter(|id| {
-            if state.is_none() {
-                true
-            } else {
-                return &load_pledge(storage, id).unwrap().state == state.as_ref().unwrap();
-            }
+        .keys(storage, min, max, order)
+        .filter(|id| match &state {
+            None => true,
+            Some(state) => &load_pledge(storage, id).unwrap().state == state,
         })
         .map(|id| String::from_utf8(id).unwrap())
         .collect::<Vec<String>>())
@@ -94,26 +325,128 @@ pub fn get_pledges(
     state: Option<PledgeState>,
     min: Option<Bound>,
     max: Option<Bound>,
+    order: Order,
 ) -> StdResult<Vec<Pledge>> {
-    Ok(get_pledge_ids(storage, state, min, max)?
+    Ok(get_pledge_ids(storage, state, min, max, order)?
         .iter()
         .map(|id| load_pledge(storage, id.as_bytes()).unwrap())
         .collect::<Vec<Pledge>>())
 }
 
+// Load up to `limit` pledges (optionally in a state) after the start id, along with the id to
+// continue from when more pledges remain. One extra pledge is taken from the iterator to detect
+// whether more exist.
+pub fn get_pledges_page(
+    storage: &dyn Storage,
+    state: Option<PledgeState>,
+    start_after: Option<String>,
+    limit: usize,
+    order: Order,
+) -> StdResult<(Vec<Pledge>, Option<String>)> {
+    let start = start_after.map(|id| Bound::exclusive(id.into_bytes()));
+    let (min, max) = match order {
+        Order::Ascending => (start, None),
+        Order::Descending => (None, start),
+    };
+    let mut pledges = PLEDGES
+        .range(storage, min, max, order)
+        .map(|item| item.map(|(_, pledge)| pledge))
+        .filter(|item| match (&state, item) {
+            (Some(state), Ok(pledge)) => &pledge.state == state,
+            _ => true,
+        })
+        .take(limit + 1)
+        .collect::<StdResult<Vec<Pledge>>>()?;
+    let next_start_after = match pledges.len() > limit {
+        true => {
+            pledges.truncate(limit);
+            pledges.last().map(|pledge| pledge.id.clone())
+        }
+        false => None,
+    };
+    Ok((pledges, next_start_after))
+}
+
+pub fn get_pledge_ids_by_states(
+    storage: &dyn Storage,
+    states: Vec<PledgeState>,
+    min: Option<Bound>,
+    max: Option<Bound>,
+) -> StdResult<Vec<String>> {
+    Ok(PLEDGES
+        .keys(storage, min, max, Order::Ascending)
+        .filter(|id| states.contains(&load_pledge(storage, id).unwrap().state))
+        .map(|id| String::from_utf8(id).unwrap())
+        .collect::<Vec<String>>())
+}
+
+pub fn get_pledges_by_states(
+    storage: &dyn Storage,
+    states: Vec<PledgeState>,
+    min: Option<Bound>,
+    max: Option<Bound>,
+) -> StdResult<Vec<Pledge>> {
+    Ok(get_pledge_ids_by_states(storage, states, min, max)?
+        .iter()
+        .map(|id| load_pledge(storage, id.as_bytes()).unwrap())
+        .collect::<Vec<Pledge>>())
+}
+
+pub const NAMESPACE_ASSET_MARKER_DENOMS: &str = "asset_marker_denoms";
+const ASSET_MARKER_DENOMS: Map<&[u8], ()> = Map::new(NAMESPACE_ASSET_MARKER_DENOMS);
+
+// Whether the denom has been used for the asset pool marker of any pledge (in any state).
+// NOTE: Pledges are never removed from the facility, so a denom stays used once it is registered.
+pub fn is_asset_marker_denom_used(storage: &dyn Storage, denom: &str) -> StdResult<bool> {
+    Ok(ASSET_MARKER_DENOMS
+        .may_load(storage, denom.as_bytes())?
+        .is_some())
+}
+
+pub fn register_asset_marker_denom(storage: &mut dyn Storage, denom: &str) -> StdResult<()> {
+    ASSET_MARKER_DENOMS.save(storage, denom.as_bytes(), &())
+}
+
+// Register the asset marker denoms of pledges proposed before the registry existed, returning how
+// many were registered.
+pub fn migrate_asset_marker_denoms(storage: &mut dyn Storage) -> StdResult<u64> {
+    let denoms = PLEDGES
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, pledge)| pledge.asset_marker_denom))
+        .collect::<StdResult<Vec<String>>>()?;
+
+    let mut migrated = 0;
+    for denom in denoms {
+        if is_asset_marker_denom_used(storage, &denom)? {
+            continue;
+        }
+
+        register_asset_marker_denom(storage, &denom)?;
+        migrated += 1;
+    }
+
+    Ok(migrated)
+}
+
+// Find the pledges (optionally in the specified state) that include any of the assets.
+// NOTE: No pledge matches an empty list of assets, so the result is empty rather than every pledge.
 pub fn find_pledge_ids_with_assets(
     storage: &dyn Storage,
-    assets: Vec<String>,
+    assets: &[String],
     state: Option<PledgeState>,
     min: Option<Bound>,
     max: Option<Bound>,
 ) -> StdResult<Vec<String>> {
+    if assets.is_empty() {
+        return Ok(vec![]);
+    }
+
     Ok(PLEDGES
         .keys(storage, min, max, Order::Ascending)
         .filter(|id| {
             let pledge = load_pledge(storage, id).unwrap();
             if state.is_none() || &pledge.state == state.as_ref().unwrap() {
-                vec_has_any(&pledge.assets, &assets)
+                vec_has_any(&pledge.assets, assets)
             } else {
                 false
             }
@@ -124,7 +457,7 @@ pub fn find_pledge_ids_with_assets(
 
 pub fn find_pledges_with_assets(
     storage: &dyn Storage,
-    assets: Vec<String>,
+    assets: &[String],
     state: Option<PledgeState>,
     min: Option<Bound>,
     max: Option<Bound>,
@@ -148,6 +481,9 @@ pub enum AssetState {
 
     // A paydown proposal exists for this asset.
     PaydownProposed,
+
+    // The paydown proposal for this asset has been accepted by all required parties.
+    PaydownAccepted,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -159,10 +495,19 @@ pub struct Asset {
 pub const NAMESPACE_ASSETS: &str = "assets";
 const ASSETS: Map<&[u8], Asset> = Map::new(NAMESPACE_ASSETS);
 
+// The raw storage key of the asset with the specified id: [0x00, 0x06] + b"assets" + id.
+pub fn asset_storage_key(id: &str) -> Vec<u8> {
+    ASSETS.key(id.as_bytes()).to_vec()
+}
+
 pub fn load_asset(storage: &dyn Storage, key: &[u8]) -> StdResult<Asset> {
     ASSETS.load(storage, key)
 }
 
+pub fn may_load_asset(storage: &dyn Storage, key: &[u8]) -> StdResult<Option<Asset>> {
+    ASSETS.may_load(storage, key)
+}
+
 pub fn save_asset(storage: &mut dyn Storage, key: &[u8], asset: &Asset) -> StdResult<()> {
     ASSETS.save(storage, key, asset)
 }
@@ -172,13 +517,51 @@ pub fn remove_asset(storage: &mut dyn Storage, key: &[u8]) -> StdResult<()> {
     Ok(())
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetTransition {
+    // The state of the asset before the transition (None = not in the facility).
+    pub from: Option<AssetState>,
+
+    // The state of the asset after the transition (None = removed from the facility).
+    pub to: Option<AssetState>,
+
+    // The block time of the transition.
+    pub at: Timestamp,
+}
+
+// The maximum number of transitions kept for an asset (the oldest are dropped first).
+pub const MAX_ASSET_HISTORY: usize = 32;
+
+pub const NAMESPACE_ASSET_HISTORY: &str = "asset_history";
+const ASSET_HISTORY: Map<&[u8], Vec<AssetTransition>> = Map::new(NAMESPACE_ASSET_HISTORY);
+
+pub fn load_asset_history(storage: &dyn Storage, key: &[u8]) -> StdResult<Vec<AssetTransition>> {
+    Ok(ASSET_HISTORY.may_load(storage, key)?.unwrap_or_default())
+}
+
+// Append a transition to the history of an asset, keeping at most MAX_ASSET_HISTORY entries.
+pub fn append_asset_history(
+    storage: &mut dyn Storage,
+    key: &[u8],
+    transition: AssetTransition,
+) -> StdResult<()> {
+    let mut history = load_asset_history(storage, key)?;
+    history.push(transition);
+    if history.len() > MAX_ASSET_HISTORY {
+        history.drain(..history.len() - MAX_ASSET_HISTORY);
+    }
+    ASSET_HISTORY.save(storage, key, &history)
+}
+
 // Set the assets to the specified state in the inventory.
 pub fn set_assets_state(
     storage: &mut dyn Storage,
     state: AssetState,
     ids: &[String],
+    at: Timestamp,
 ) -> StdResult<()> {
     for id in ids {
+        let from = may_load_asset(storage, id.as_bytes())?.map(|asset| asset.state);
         save_asset(
             storage,
             id.as_bytes(),
@@ -187,14 +570,36 @@ pub fn set_assets_state(
                 state: state.clone(),
             },
         )?;
+        if from.as_ref() != Some(&state) {
+            append_asset_history(
+                storage,
+                id.as_bytes(),
+                AssetTransition {
+                    from,
+                    to: Some(state.clone()),
+                    at,
+                },
+            )?;
+        }
     }
     Ok(())
 }
 
 // Remove assets from the inventory.
-pub fn remove_assets(storage: &mut dyn Storage, ids: &[String]) -> StdResult<()> {
+pub fn remove_assets(storage: &mut dyn Storage, ids: &[String], at: Timestamp) -> StdResult<()> {
     for id in ids {
-        remove_asset(storage, id.as_bytes())?;
+        if let Some(asset) = may_load_asset(storage, id.as_bytes())? {
+            remove_asset(storage, id.as_bytes())?;
+            append_asset_history(
+                storage,
+                id.as_bytes(),
+                AssetTransition {
+                    from: Some(asset.state),
+                    to: None,
+                    at,
+                },
+            )?;
+        }
     }
     Ok(())
 }
@@ -207,12 +612,9 @@ pub fn get_asset_ids(
 ) -> StdResult<Vec<String>> {
     Ok(ASSETS
         .keys(storage, min, max, Order::Ascending)
-        .filter(|id| {
-            if state.is_none() {
-                true
-            } else {
-                return &load_asset(storage, id).unwrap().state == state.as_ref().unwrap();
-            }
+        .filter(|id| match &state {
+            None => true,
+            Some(state) => &load_asset(storage, id).unwrap().state == state,
         })
         .map(|id| String::from_utf8(id).unwrap())
         .collect::<Vec<String>>())
@@ -271,6 +673,23 @@ pub enum PaydownState {
     Executed,
 }
 
+impl PaydownState {
+    // The states that a paydown in this state may legally transition to.
+    pub fn allowed_next(&self) -> Vec<PaydownState> {
+        match self {
+            PaydownState::Proposed => vec![PaydownState::Accepted, PaydownState::Cancelled],
+            PaydownState::Accepted => vec![PaydownState::Cancelled, PaydownState::Executed],
+            PaydownState::Cancelled => vec![],
+            PaydownState::Executed => vec![],
+        }
+    }
+
+    // Check if a paydown in this state may transition to the specified state.
+    pub fn can_transition_to(&self, next: &PaydownState) -> bool {
+        self.allowed_next().contains(next)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum PaydownKind {
@@ -304,20 +723,119 @@ pub struct PaydownSaleInfo {
 pub struct Paydown {
     pub id: String,
     pub assets: Vec<String>,
-    pub total_paydown: u64,
+    pub total_paydown: Uint128,
     pub kind: PaydownKind,
     pub state: PaydownState,
     pub parties_accepted: Vec<ContractParty>,
     pub sale_info: Option<PaydownSaleInfo>,
+
+    // The block time at which the paydown was proposed.
+    pub proposed_at: Timestamp,
+
+    // The block time at which all required parties had accepted the paydown.
+    pub accepted_at: Option<Timestamp>,
+
+    // The block time at which the paydown was executed by the originator.
+    pub executed_at: Option<Timestamp>,
+
+    // The stablecoin denom the originator sent the paydown in.
+    #[serde(default)]
+    pub paydown_denom: Option<String>,
+
+    // The stablecoin denom the buyer sent the purchase price in (None = not yet paid).
+    #[serde(default)]
+    pub purchase_denom: Option<String>,
+
+    // The id of the pledge the paid down assets belong to (None = proposed before paydowns were
+    // linked to their pledge).
+    #[serde(default)]
+    pub pledge_id: Option<String>,
 }
 
 pub const NAMESPACE_PAYDOWNS: &str = "paydowns";
 const PAYDOWNS: Map<&[u8], Paydown> = Map::new(NAMESPACE_PAYDOWNS);
 
+// The raw storage key of the paydown with the specified id: [0x00, 0x08] + b"paydowns" + id.
+pub fn paydown_storage_key(id: &str) -> Vec<u8> {
+    PAYDOWNS.key(id.as_bytes()).to_vec()
+}
+
+// The paydown format stored before the total paydown was widened to a Uint128.
+#[derive(Serialize, Deserialize)]
+struct LegacyPaydown {
+    id: String,
+    assets: Vec<String>,
+    total_paydown: u64,
+    kind: PaydownKind,
+    state: PaydownState,
+    parties_accepted: Vec<ContractParty>,
+    sale_info: Option<PaydownSaleInfo>,
+    #[serde(default)]
+    proposed_at: Timestamp,
+    #[serde(default)]
+    accepted_at: Option<Timestamp>,
+    #[serde(default)]
+    executed_at: Option<Timestamp>,
+}
+
+impl From<LegacyPaydown> for Paydown {
+    fn from(legacy: LegacyPaydown) -> Self {
+        Paydown {
+            id: legacy.id,
+            assets: legacy.assets,
+            total_paydown: Uint128::from(legacy.total_paydown),
+            kind: legacy.kind,
+            state: legacy.state,
+            parties_accepted: legacy.parties_accepted,
+            sale_info: legacy.sale_info,
+            proposed_at: legacy.proposed_at,
+            accepted_at: legacy.accepted_at,
+            executed_at: legacy.executed_at,
+            paydown_denom: None,
+            purchase_denom: None,
+            pledge_id: None,
+        }
+    }
+}
+
+const LEGACY_PAYDOWNS: Map<&[u8], LegacyPaydown> = Map::new(NAMESPACE_PAYDOWNS);
+
+// Rewrite any paydowns stored in the legacy format, returning how many were migrated.
+pub fn migrate_paydowns(storage: &mut dyn Storage) -> StdResult<u64> {
+    let keys = PAYDOWNS
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<Vec<Vec<u8>>>();
+
+    let mut migrated = 0;
+    for key in keys {
+        if PAYDOWNS.load(storage, &key).is_ok() {
+            continue;
+        }
+
+        let legacy = LEGACY_PAYDOWNS.load(storage, &key)?;
+        PAYDOWNS.save(storage, &key, &legacy.into())?;
+        migrated += 1;
+    }
+
+    Ok(migrated)
+}
+
 pub fn load_paydown(storage: &dyn Storage, key: &[u8]) -> StdResult<Paydown> {
     PAYDOWNS.load(storage, key)
 }
 
+// Load only the state of a paydown (ex: for clients polling the progress of a paydown).
+pub fn load_paydown_state(storage: &dyn Storage, key: &[u8]) -> StdResult<PaydownState> {
+    let raw = storage
+        .get(&PAYDOWNS.key(key))
+        .ok_or_else(|| StdError::not_found(type_name::<Paydown>()))?;
+    Ok(from_slice::<StoredState<PaydownState>>(&raw)?.state)
+}
+
+pub fn may_load_paydown(storage: &dyn Storage, key: &[u8]) -> StdResult<Option<Paydown>> {
+    PAYDOWNS.may_load(storage, key)
+}
+
 pub fn save_paydown(storage: &mut dyn Storage, key: &[u8], paydown: &Paydown) -> StdResult<()> {
     PAYDOWNS.save(storage, key, paydown)
 }
@@ -327,15 +845,13 @@ pub fn get_paydown_ids(
     state: Option<PaydownState>,
     min: Option<Bound>,
     max: Option<Bound>,
+    order: Order,
 ) -> StdResult<Vec<String>> {
     Ok(PAYDOWNS
-        .keys(storage, min, max, Order::Ascending)
-        .filter(|id| {
-            if state.is_none() {
-                true
-            } else {
-                return &load_paydown(storage, id).unwrap().state == state.as_ref().unwrap();
-            }
+        .keys(storage, min, max, order)
+        .filter(|id| match &state {
+            None => true,
+            Some(state) => &load_paydown(storage, id).unwrap().state == state,
         })
         .map(|id| String::from_utf8(id).unwrap())
         .collect::<Vec<String>>())
@@ -346,9 +862,582 @@ pub fn get_paydowns(
     state: Option<PaydownState>,
     min: Option<Bound>,
     max: Option<Bound>,
+    order: Order,
 ) -> StdResult<Vec<Paydown>> {
-    Ok(get_paydown_ids(storage, state, min, max)?
+    Ok(get_paydown_ids(storage, state, min, max, order)?
         .iter()
         .map(|id| load_paydown(storage, id.as_bytes()).unwrap())
         .collect::<Vec<Paydown>>())
 }
+
+// Find the paydowns (optionally in a state) of the pledge's assets. Paydowns linked to a pledge are
+// matched on the link, and paydowns proposed before the link existed are matched on their assets.
+pub fn find_paydowns_for_pledge(
+    storage: &dyn Storage,
+    pledge: &Pledge,
+    state: Option<PaydownState>,
+) -> StdResult<Vec<Paydown>> {
+    Ok(get_paydowns(storage, state, None, None, Order::Ascending)?
+        .into_iter()
+        .filter(|paydown| match &paydown.pledge_id {
+            Some(pledge_id) => pledge_id == &pledge.id,
+            None => vec_has_any(&pledge.assets, &paydown.assets),
+        })
+        .collect::<Vec<Paydown>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{ContractError, FieldError};
+    use crate::state::Facility;
+    use crate::state::{
+        asset_storage_key, find_pledge_ids_with_assets, find_pledges_with_assets, get_pledge_ids,
+        get_pledges_by_states, load_asset, load_asset_history, load_paydown, load_paydown_state,
+        load_pledge, load_pledge_state, migrate_paydowns, migrate_pledges, paydown_storage_key,
+        pledge_storage_key, save_paydown, save_pledge, set_assets_state, Asset, AssetState,
+        MarkerDistribution, Paydown, PaydownKind, PaydownState, Pledge, PledgeState,
+        LEGACY_PAYDOWNS, LEGACY_PLEDGES, MAX_ASSET_HISTORY,
+    };
+    use cosmwasm_schema::schema_for;
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Storage;
+    use cosmwasm_std::{from_slice, Addr, Order, StdError, Timestamp, Uint128};
+    use rust_decimal::prelude::FromStr;
+    use rust_decimal::Decimal;
+
+    fn facility_with_rates(advance_rate: &str, paydown_rate: &str) -> Facility {
+        Facility {
+            originator: Addr::unchecked("originator"),
+            warehouse: Addr::unchecked("warehouse"),
+            escrow_marker: Addr::unchecked("escrow_marker"),
+            marker_denom: "test.denom.wf1".into(),
+            stablecoin_denoms: vec!["test.denom.stable".into()],
+            advance_rate: advance_rate.into(),
+            paydown_rate: paydown_rate.into(),
+            rounding: None,
+            max_total_advance: None,
+        }
+    }
+
+    #[test]
+    pub fn facility_rates_parse_as_decimals() {
+        let facility = facility_with_rates("75.125", "102.25");
+        assert_eq!(
+            facility.advance_rate_decimal().unwrap(),
+            Decimal::from_str("75.125").unwrap()
+        );
+        assert_eq!(
+            facility.paydown_rate_decimal().unwrap(),
+            Decimal::from_str("102.25").unwrap()
+        );
+    }
+
+    #[test]
+    pub fn facility_rates_with_surrounding_whitespace() {
+        let facility = facility_with_rates(" 75.125 ", "\t102.25\n");
+        assert_eq!(
+            facility.advance_rate_decimal().unwrap(),
+            Decimal::from_str("75.125").unwrap()
+        );
+        assert_eq!(
+            facility.paydown_rate_decimal().unwrap(),
+            Decimal::from_str("102.25").unwrap()
+        );
+
+        // whitespace within the rate is still malformed
+        for rate in ["75 .125", " ", "7 5"].iter() {
+            let facility = facility_with_rates(rate, rate);
+            match facility.advance_rate_decimal() {
+                Err(ContractError::InvalidFields { errors }) => {
+                    assert_eq!(errors[0].reason, "must be a decimal", "rate {:?}", rate);
+                }
+                result => panic!("unexpected result for rate {:?}: {:?}", rate, result),
+            }
+            match facility.paydown_rate_decimal() {
+                Err(ContractError::InvalidFields { errors }) => {
+                    assert_eq!(errors[0].reason, "must be a decimal", "rate {:?}", rate);
+                }
+                result => panic!("unexpected result for rate {:?}: {:?}", rate, result),
+            }
+        }
+    }
+
+    #[test]
+    pub fn facility_rates_with_parse_failures() {
+        let facility = facility_with_rates("abc", "");
+        match facility.advance_rate_decimal() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new(
+                        "facility.advance_rate",
+                        "must be a decimal"
+                    )]
+                );
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match facility.paydown_rate_decimal() {
+            Err(ContractError::InvalidFields { errors }) => {
+                assert_eq!(
+                    errors,
+                    vec![FieldError::new(
+                        "facility.paydown_rate",
+                        "must be a decimal"
+                    )]
+                );
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn facility_advance_rate_range() {
+        for rate in ["0.001", "50", "100"].iter() {
+            let facility = facility_with_rates(rate, "100");
+            assert!(facility.advance_rate_decimal().is_ok(), "rate {}", rate);
+        }
+        for rate in ["0", "-1", "100.001", "101"].iter() {
+            let facility = facility_with_rates(rate, "100");
+            match facility.advance_rate_decimal() {
+                Err(ContractError::InvalidFields { errors }) => {
+                    assert_eq!(errors[0].reason, "must be in (0,100]", "rate {}", rate);
+                }
+                result => panic!("unexpected result for rate {}: {:?}", rate, result),
+            }
+        }
+    }
+
+    #[test]
+    pub fn facility_paydown_rate_range() {
+        // the paydown rate can exceed 100% of the UPB
+        for rate in ["0.001", "100", "150.5"].iter() {
+            let facility = facility_with_rates("75", rate);
+            assert!(facility.paydown_rate_decimal().is_ok(), "rate {}", rate);
+        }
+        for rate in ["0", "-0.5"].iter() {
+            let facility = facility_with_rates("75", rate);
+            match facility.paydown_rate_decimal() {
+                Err(ContractError::InvalidFields { errors }) => {
+                    assert_eq!(errors[0].reason, "must be greater than 0", "rate {}", rate);
+                }
+                result => panic!("unexpected result for rate {}: {:?}", rate, result),
+            }
+        }
+    }
+
+    fn save_test_pledge(storage: &mut MockStorage, id: &str, state: PledgeState) {
+        save_pledge(
+            storage,
+            id.as_bytes(),
+            &Pledge {
+                id: id.into(),
+                assets: vec![format!("asset-{}", id)],
+                total_advance: Uint128::new(100),
+                asset_marker_denom: format!("test.denom.pledge.{}", id),
+                state,
+                proposed_at: Timestamp::from_seconds(0),
+                accepted_at: None,
+                executed_at: None,
+                advance_denom: None,
+                valid_until: None,
+                document_hash: None,
+                repayment_reference: None,
+            },
+        )
+        .unwrap();
+    }
+
+    fn mixed_state_pledges() -> MockStorage {
+        let mut storage = MockStorage::new();
+        save_test_pledge(&mut storage, "pledge-1", PledgeState::Proposed);
+        save_test_pledge(&mut storage, "pledge-2", PledgeState::Accepted);
+        save_test_pledge(&mut storage, "pledge-3", PledgeState::Cancelled);
+        save_test_pledge(&mut storage, "pledge-4", PledgeState::Executed);
+        save_test_pledge(&mut storage, "pledge-5", PledgeState::Closed);
+        storage
+    }
+
+    #[test]
+    pub fn get_pledges_by_states_excludes_terminal_states() {
+        let storage = mixed_state_pledges();
+        let pledges = get_pledges_by_states(
+            &storage,
+            vec![
+                PledgeState::Proposed,
+                PledgeState::Accepted,
+                PledgeState::Executed,
+            ],
+            None,
+            None,
+        )
+        .unwrap();
+        let ids: Vec<String> = pledges.into_iter().map(|pledge| pledge.id).collect();
+        assert_eq!(ids, vec!["pledge-1", "pledge-2", "pledge-4"]);
+    }
+
+    #[test]
+    pub fn get_pledges_by_states_with_single_state() {
+        let storage = mixed_state_pledges();
+        let pledges =
+            get_pledges_by_states(&storage, vec![PledgeState::Cancelled], None, None).unwrap();
+        let ids: Vec<String> = pledges.into_iter().map(|pledge| pledge.id).collect();
+        assert_eq!(ids, vec!["pledge-3"]);
+    }
+
+    #[test]
+    pub fn get_pledges_by_states_with_rejected_state() {
+        let mut storage = mixed_state_pledges();
+        save_test_pledge(&mut storage, "pledge-6", PledgeState::Rejected);
+        let pledges = get_pledges_by_states(
+            &storage,
+            vec![PledgeState::Cancelled, PledgeState::Rejected],
+            None,
+            None,
+        )
+        .unwrap();
+        let ids: Vec<String> = pledges.into_iter().map(|pledge| pledge.id).collect();
+        assert_eq!(ids, vec!["pledge-3", "pledge-6"]);
+    }
+
+    #[test]
+    pub fn get_pledges_by_states_with_no_states() {
+        let storage = mixed_state_pledges();
+        let pledges = get_pledges_by_states(&storage, vec![], None, None).unwrap();
+        assert!(pledges.is_empty());
+    }
+
+    #[test]
+    pub fn pledge_state_proposed_allowed_next() {
+        let allowed = PledgeState::Proposed.allowed_next();
+        assert!(allowed.contains(&PledgeState::Accepted));
+        assert!(allowed.contains(&PledgeState::Cancelled));
+        assert!(allowed.contains(&PledgeState::Rejected));
+        assert!(!allowed.contains(&PledgeState::Executed));
+        assert!(!allowed.contains(&PledgeState::Closed));
+    }
+
+    #[test]
+    pub fn pledge_state_terminal_states_have_no_successors() {
+        assert!(PledgeState::Cancelled.allowed_next().is_empty());
+        assert!(PledgeState::Rejected.allowed_next().is_empty());
+        assert!(PledgeState::Closed.allowed_next().is_empty());
+    }
+
+    #[test]
+    pub fn pledge_state_can_transition_to() {
+        assert!(PledgeState::Accepted.can_transition_to(&PledgeState::Executed));
+        assert!(PledgeState::Executed.can_transition_to(&PledgeState::Closed));
+        assert!(!PledgeState::Executed.can_transition_to(&PledgeState::Cancelled));
+    }
+
+    #[test]
+    pub fn pledge_state_transition_matrix() {
+        let states = [
+            PledgeState::Proposed,
+            PledgeState::Accepted,
+            PledgeState::Cancelled,
+            PledgeState::Rejected,
+            PledgeState::Executed,
+            PledgeState::Closed,
+        ];
+        let legal = [
+            (PledgeState::Proposed, PledgeState::Accepted),
+            (PledgeState::Proposed, PledgeState::Cancelled),
+            (PledgeState::Proposed, PledgeState::Rejected),
+            (PledgeState::Accepted, PledgeState::Cancelled),
+            (PledgeState::Accepted, PledgeState::Executed),
+            (PledgeState::Executed, PledgeState::Closed),
+        ];
+        for from in &states {
+            for to in &states {
+                let expected = legal.contains(&(from.clone(), to.clone()));
+                assert_eq!(
+                    from.can_transition_to(to),
+                    expected,
+                    "{:?} -> {:?}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn paydown_state_transition_matrix() {
+        let states = [
+            PaydownState::Proposed,
+            PaydownState::Accepted,
+            PaydownState::Cancelled,
+            PaydownState::Executed,
+        ];
+        let legal = [
+            (PaydownState::Proposed, PaydownState::Accepted),
+            (PaydownState::Proposed, PaydownState::Cancelled),
+            (PaydownState::Accepted, PaydownState::Cancelled),
+            (PaydownState::Accepted, PaydownState::Executed),
+        ];
+        for from in &states {
+            for to in &states {
+                let expected = legal.contains(&(from.clone(), to.clone()));
+                assert_eq!(
+                    from.can_transition_to(to),
+                    expected,
+                    "{:?} -> {:?}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn get_pledge_ids_in_descending_order() {
+        let storage = mixed_state_pledges();
+        let ascending = get_pledge_ids(&storage, None, None, None, Order::Ascending).unwrap();
+        assert_eq!(
+            ascending,
+            vec!["pledge-1", "pledge-2", "pledge-3", "pledge-4", "pledge-5"]
+        );
+
+        let descending = get_pledge_ids(&storage, None, None, None, Order::Descending).unwrap();
+        assert_eq!(
+            descending,
+            vec!["pledge-5", "pledge-4", "pledge-3", "pledge-2", "pledge-1"]
+        );
+    }
+
+    #[test]
+    fn exported_schemas_are_not_empty() {
+        let schemas = vec![
+            schema_for!(Asset),
+            schema_for!(MarkerDistribution),
+            schema_for!(Paydown),
+        ];
+        for schema in schemas {
+            let object = schema.schema.object.expect("schema should be an object");
+            assert!(!object.properties.is_empty());
+        }
+    }
+
+    #[test]
+    pub fn find_pledges_with_borrowed_assets() {
+        let storage = mixed_state_pledges();
+        let assets: Vec<String> = vec![
+            "asset-pledge-2".into(),
+            "asset-pledge-4".into(),
+            "asset-unknown".into(),
+        ];
+
+        let ids = find_pledge_ids_with_assets(&storage, &assets[..2], None, None, None).unwrap();
+        assert_eq!(ids, vec!["pledge-2", "pledge-4"]);
+
+        let pledges =
+            find_pledges_with_assets(&storage, &assets, Some(PledgeState::Executed), None, None)
+                .unwrap();
+        assert_eq!(pledges.len(), 1);
+        assert_eq!(pledges[0].id, "pledge-4");
+
+        // the caller still owns the assets
+        assert_eq!(assets.len(), 3);
+    }
+
+    #[test]
+    pub fn find_pledges_with_no_assets() {
+        let storage = mixed_state_pledges();
+        let ids = find_pledge_ids_with_assets(&storage, &[], None, None, None).unwrap();
+        assert!(ids.is_empty());
+
+        let pledges =
+            find_pledges_with_assets(&storage, &[], Some(PledgeState::Executed), None, None)
+                .unwrap();
+        assert!(pledges.is_empty());
+    }
+
+    #[test]
+    pub fn asset_history_is_bounded() {
+        let mut storage = MockStorage::new();
+        let ids = vec!["asset-1".to_string()];
+        let states = [AssetState::Inventory, AssetState::PaydownProposed];
+        for i in 0..(MAX_ASSET_HISTORY + 10) {
+            set_assets_state(
+                &mut storage,
+                states[i % 2].clone(),
+                &ids,
+                Timestamp::from_seconds(i as u64),
+            )
+            .unwrap();
+        }
+
+        let history = load_asset_history(&storage, b"asset-1").unwrap();
+        assert_eq!(history.len(), MAX_ASSET_HISTORY);
+        assert_eq!(history[0].at, Timestamp::from_seconds(10));
+        assert_eq!(
+            history.last().unwrap().at,
+            Timestamp::from_seconds((MAX_ASSET_HISTORY + 9) as u64)
+        );
+    }
+
+    #[test]
+    pub fn asset_history_skips_unchanged_state() {
+        let mut storage = MockStorage::new();
+        let ids = vec!["asset-1".to_string()];
+        set_assets_state(
+            &mut storage,
+            AssetState::Inventory,
+            &ids,
+            Timestamp::from_seconds(1),
+        )
+        .unwrap();
+        set_assets_state(
+            &mut storage,
+            AssetState::Inventory,
+            &ids,
+            Timestamp::from_seconds(2),
+        )
+        .unwrap();
+        assert_eq!(load_asset_history(&storage, b"asset-1").unwrap().len(), 1);
+        assert!(load_asset_history(&storage, b"asset-2").unwrap().is_empty());
+    }
+
+    #[test]
+    pub fn pledge_storage_key_reads_saved_pledge() {
+        let mut storage = MockStorage::new();
+        save_test_pledge(&mut storage, "pledge-1", PledgeState::Proposed);
+
+        let key = pledge_storage_key("pledge-1");
+        assert_eq!(key, b"\x00\x07pledgespledge-1".to_vec());
+
+        let raw = storage.get(&key).unwrap();
+        let pledge: Pledge = from_slice(&raw).unwrap();
+        assert_eq!(pledge, load_pledge(&storage, b"pledge-1").unwrap());
+    }
+
+    #[test]
+    pub fn load_pledge_state_only() {
+        let mut storage = MockStorage::new();
+        save_test_pledge(&mut storage, "pledge-1", PledgeState::Accepted);
+        assert_eq!(
+            load_pledge_state(&storage, b"pledge-1").unwrap(),
+            PledgeState::Accepted
+        );
+        match load_pledge_state(&storage, b"pledge-2") {
+            Err(StdError::NotFound { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match load_paydown_state(&storage, b"paydown-1") {
+            Err(StdError::NotFound { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn paydown_storage_key_reads_saved_paydown() {
+        let mut storage = MockStorage::new();
+        let paydown = Paydown {
+            id: "paydown-1".into(),
+            assets: vec!["asset-1".into()],
+            total_paydown: Uint128::new(250),
+            kind: PaydownKind::PaydownOnly,
+            state: PaydownState::Proposed,
+            parties_accepted: vec![],
+            sale_info: None,
+            proposed_at: Timestamp::from_seconds(0),
+            accepted_at: None,
+            executed_at: None,
+            paydown_denom: None,
+            purchase_denom: None,
+            pledge_id: None,
+        };
+        save_paydown(&mut storage, b"paydown-1", &paydown).unwrap();
+
+        let key = paydown_storage_key("paydown-1");
+        assert_eq!(key, b"\x00\x08paydownspaydown-1".to_vec());
+
+        let raw = storage.get(&key).unwrap();
+        assert_eq!(from_slice::<Paydown>(&raw).unwrap(), paydown);
+    }
+
+    #[test]
+    pub fn asset_storage_key_reads_saved_asset() {
+        let mut storage = MockStorage::new();
+        let ids = vec!["asset-1".to_string()];
+        set_assets_state(
+            &mut storage,
+            AssetState::Inventory,
+            &ids,
+            Timestamp::from_seconds(1),
+        )
+        .unwrap();
+
+        let key = asset_storage_key("asset-1");
+        assert_eq!(key, b"\x00\x06assetsasset-1".to_vec());
+
+        let raw = storage.get(&key).unwrap();
+        let asset: Asset = from_slice(&raw).unwrap();
+        assert_eq!(asset, load_asset(&storage, b"asset-1").unwrap());
+        assert_eq!(asset.state, AssetState::Inventory);
+    }
+
+    #[test]
+    pub fn migrate_legacy_pledges() {
+        let mut storage = MockStorage::new();
+        storage.set(
+            &LEGACY_PLEDGES.key(b"pledge-1"),
+            br#"{"id":"pledge-1","assets":["asset-1"],"total_advance":18446744073709551615,"asset_marker_denom":"test.denom.pledge.pledge-1","state":"executed"}"#,
+        );
+        save_test_pledge(&mut storage, "pledge-2", PledgeState::Proposed);
+
+        assert!(load_pledge(&storage, b"pledge-1").is_err());
+        assert_eq!(migrate_pledges(&mut storage).unwrap(), 1);
+
+        let pledge = load_pledge(&storage, b"pledge-1").unwrap();
+        assert_eq!(pledge.total_advance, Uint128::from(u64::MAX));
+        assert_eq!(pledge.state, PledgeState::Executed);
+        assert_eq!(pledge.proposed_at, Timestamp::default());
+        assert_eq!(pledge.accepted_at, None);
+        assert_eq!(
+            load_pledge(&storage, b"pledge-2").unwrap().total_advance,
+            Uint128::new(100)
+        );
+
+        // running the migration again is a no-op
+        assert_eq!(migrate_pledges(&mut storage).unwrap(), 0);
+    }
+
+    #[test]
+    pub fn migrate_registers_existing_asset_marker_denoms() {
+        let mut storage = MockStorage::new();
+        save_test_pledge(&mut storage, "pledge-1", PledgeState::Closed);
+        save_test_pledge(&mut storage, "pledge-2", PledgeState::Proposed);
+        register_asset_marker_denom(&mut storage, "test.denom.pledge.pledge-2").unwrap();
+
+        assert!(!is_asset_marker_denom_used(&storage, "test.denom.pledge.pledge-1").unwrap());
+        assert_eq!(migrate_asset_marker_denoms(&mut storage).unwrap(), 1);
+        assert!(is_asset_marker_denom_used(&storage, "test.denom.pledge.pledge-1").unwrap());
+        assert!(is_asset_marker_denom_used(&storage, "test.denom.pledge.pledge-2").unwrap());
+        assert!(!is_asset_marker_denom_used(&storage, "test.denom.pledge.pledge-3").unwrap());
+
+        // running the migration again is a no-op
+        assert_eq!(migrate_asset_marker_denoms(&mut storage).unwrap(), 0);
+    }
+
+    #[test]
+    pub fn migrate_legacy_paydowns() {
+        let mut storage = MockStorage::new();
+        storage.set(
+            &LEGACY_PAYDOWNS.key(b"paydown-1"),
+            br#"{"id":"paydown-1","assets":["asset-1"],"total_paydown":250,"kind":"paydown_only","state":"proposed","parties_accepted":[],"sale_info":null}"#,
+        );
+
+        assert!(load_paydown(&storage, b"paydown-1").is_err());
+        assert_eq!(migrate_paydowns(&mut storage).unwrap(), 1);
+
+        let paydown = load_paydown(&storage, b"paydown-1").unwrap();
+        assert_eq!(paydown.total_paydown, Uint128::new(250));
+        assert_eq!(paydown.state, PaydownState::Proposed);
+        assert_eq!(paydown.executed_at, None);
+
+        assert_eq!(migrate_paydowns(&mut storage).unwrap(), 0);
+    }
+}
@@ -0,0 +1,124 @@
+// Fixtures shared by the contract tests, so that tests for the pledge and paydown handlers don't
+// need to build a facility from scratch.
+use crate::contract::{execute, instantiate};
+use crate::msg::{ExecuteMsg, InstantiateMsg};
+use crate::state::Facility;
+use crate::utils::pledge_marker_denom;
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage};
+use cosmwasm_std::{coin, Addr, Decimal, DepsMut, Env, OwnedDeps, Timestamp};
+use provwasm_mocks::{mock_dependencies, ProvenanceMockQuerier};
+use provwasm_std::{AccessGrant, Marker, MarkerAccess, MarkerStatus, MarkerType};
+
+pub const CONTRACT_ADDRESS: &str = "tp1ejpjr43ht3y56pplm5pxpusmcrk9rkkvnyjxy5";
+pub const ADMIN: &str = "contract_admin";
+pub const ORIGINATOR: &str = "contract_originator";
+pub const WAREHOUSE: &str = "contract_warehouse";
+pub const BUYER: &str = "contract_buyer";
+pub const ESCROW_MARKER: &str = "escrow_marker";
+pub const ESCROW_DENOM: &str = "test.denom.escrow";
+pub const MARKER_DENOM: &str = "test.denom.wf1";
+pub const STABLECOIN_DENOM: &str = "test.denom.stable";
+pub const STABLECOIN_DENOM_2: &str = "test.denom.stable2";
+
+pub const PLEDGE_ID_1: &str = "5fd5ea7c-2a36-4d1e-8dbd-8c7b2a2c9b11";
+pub const PLEDGE_ID_2: &str = "9a4e9c2b-63b2-4c4b-9e3a-2f6d1b7c8e22";
+pub const PLEDGE_ID_3: &str = "b8f2d6e4-5c1a-4f3b-a7e9-6d0c2b4a8f55";
+pub const PAYDOWN_ID_1: &str = "3e1c8f5a-7b2d-4e6a-9c0f-5d4b3a2e1f33";
+pub const PAYDOWN_ID_2: &str = "6b9d2e7f-4c8a-4d1b-a3e5-7f0c9b8d6a24";
+pub const ASSET_ID_1: &str = "7a6e2d4c-9d0e-4c3f-8e51-4f3b5d2c1a90";
+pub const ASSET_ID_2: &str = "c3d1a6b8-2f4e-4a57-b0c9-8e7d6f5a4b32";
+pub const ASSET_ID_3: &str = "e2b4c6d8-1a3f-4e5b-9c7d-0f2e4a6b8c41";
+
+pub fn test_env() -> Env {
+    let mut env = mock_env();
+    env.contract.address = Addr::unchecked(CONTRACT_ADDRESS);
+    env
+}
+
+pub fn test_env_at(seconds: u64) -> Env {
+    let mut env = test_env();
+    env.block.time = Timestamp::from_seconds(seconds);
+    env
+}
+
+// An active marker that grants the contract transfer and withdraw access, and holds stablecoin.
+pub fn marker(address: &str, denom: &str) -> Marker {
+    Marker {
+        address: Addr::unchecked(address),
+        coins: vec![
+            coin(1_000_000, STABLECOIN_DENOM),
+            coin(1_000_000, STABLECOIN_DENOM_2),
+        ],
+        account_number: 0,
+        sequence: 0,
+        manager: "".into(),
+        permissions: vec![AccessGrant {
+            address: Addr::unchecked(CONTRACT_ADDRESS),
+            permissions: vec![MarkerAccess::Transfer, MarkerAccess::Withdraw],
+        }],
+        status: MarkerStatus::Active,
+        denom: denom.into(),
+        total_supply: Decimal::zero(),
+        marker_type: MarkerType::Restricted,
+        supply_fixed: true,
+    }
+}
+
+pub fn asset_marker_denom(pledge_id: &str) -> String {
+    pledge_marker_denom(MARKER_DENOM, pledge_id)
+}
+
+pub fn default_facility() -> Facility {
+    Facility {
+        originator: Addr::unchecked(ORIGINATOR),
+        warehouse: Addr::unchecked(WAREHOUSE),
+        escrow_marker: Addr::unchecked(ESCROW_MARKER),
+        marker_denom: MARKER_DENOM.into(),
+        stablecoin_denoms: vec![STABLECOIN_DENOM.into()],
+        advance_rate: "75".into(),
+        paydown_rate: "100".into(),
+        rounding: None,
+        max_total_advance: None,
+    }
+}
+
+pub fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        bind_name: "contract-bind-name".into(),
+        contract_name: "contract_name".into(),
+        facility: default_facility(),
+    }
+}
+
+// Instantiate the default facility.
+// NOTE: The escrow marker must already be registered with the querier, ex:
+//       deps.querier.with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)])
+pub fn instantiate_test_contract(deps: DepsMut) {
+    instantiate(deps, test_env(), mock_info(ADMIN, &[]), instantiate_msg()).unwrap();
+}
+
+// Instantiate the default facility against mock dependencies that know only the escrow marker.
+pub fn instantiated_test_deps() -> OwnedDeps<MockStorage, MockApi, ProvenanceMockQuerier> {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_markers(vec![marker(ESCROW_MARKER, ESCROW_DENOM)]);
+    instantiate_test_contract(deps.as_mut());
+    deps
+}
+
+pub fn propose_test_pledge(deps: DepsMut, id: &str, assets: &[&str], total_advance: u128) {
+    execute(
+        deps,
+        test_env(),
+        mock_info(ORIGINATOR, &[]),
+        ExecuteMsg::ProposePledge {
+            id: id.into(),
+            assets: assets.iter().map(|asset| asset.to_string()).collect(),
+            total_advance: total_advance.into(),
+            asset_marker_denom: asset_marker_denom(id),
+            valid_until: None,
+            document_hash: None,
+        },
+    )
+    .unwrap();
+}
@@ -8,10 +8,55 @@ pub fn vec_has_any<T: PartialEq>(a: &[T], b: &[T]) -> bool {
     matching > 0
 }
 
+// Derive a stable UUID (v5) from a namespace and an external key (ex: a loan identifier),
+// so the same key always maps to the same scope/asset id.
+// NOTE: This is intended for off-chain key derivation only, not as a source of on-chain randomness.
+pub fn deterministic_uuid(namespace: Uuid, name: &str) -> Uuid {
+    Uuid::new_v5(&namespace, name.as_bytes())
+}
+
+// Derive the asset pool marker denom for a pledge, scoped under the facility marker denom so it
+// can't collide with the facility marker or the marker of any other pledge.
+pub fn pledge_marker_denom(facility_denom: &str, pledge_id: &str) -> String {
+    format!("{}.pledge.{}", facility_denom, pledge_id)
+}
+
+// Normalize an asset identifier, which may be a bare scope UUID or a bech32 scope address
+// (ex: "scope1..."), to its canonical form: the lowercase hyphenated scope UUID.
+pub fn normalize_asset_id(asset: &str) -> Result<String, MetadataAddressError> {
+    if let Ok(uuid) = Uuid::parse_str(asset) {
+        return Ok(uuid.to_hyphenated().to_string());
+    }
+    let address = MetadataAddress::try_from_bech32(asset)?;
+    if address.get_key() != KEY_SCOPE {
+        return Err(MetadataAddressError::UnsupportedType { prefix: address.get_prefix() });
+    }
+    Ok(address.get_primary_uuid()?.to_hyphenated().to_string())
+}
+
+// Derive the bech32 address of a marker account from its denom (the first 20 bytes of the
+// SHA-256 hash of the denom), using the specified address prefix (ex: "tp" or "pb").
+pub fn marker_address_for_denom(hrp: &str, denom: &str) -> Result<String, bech32::Error> {
+    let mut hasher = Sha256::new();
+    hasher.update(denom.as_bytes());
+    let mut hashed_bytes = hasher.finalize().to_vec();
+    hashed_bytes.truncate(20);
+    bech32::encode(hrp, hashed_bytes.to_base32(), Variant::Bech32)
+}
+
+use crate::error::MetadataAddressError;
 use std::convert::TryInto;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use uuid::Uuid;
 use bech32::{ self, FromBase32, ToBase32, Variant };
 use sha2::{ Digest, Sha256 };
+#[cfg(feature = "contract")]
+use schemars::JsonSchema;
+#[cfg(feature = "contract")]
+use schemars::gen::SchemaGenerator;
+#[cfg(feature = "contract")]
+use schemars::schema::{ InstanceType, Schema, SchemaObject };
 
 const PREFIX_SCOPE: &str = "scope";
 const PREFIX_SESSION: &str  = "session";
@@ -27,8 +72,11 @@ const KEY_SCOPE_SPECIFICATION: u8 = 0x04; // Note that this is not in numerical
 const KEY_CONTRACT_SPECIFICATION: u8 = 0x03;
 const KEY_RECORD_SPECIFICATION: u8 = 0x05;
 
+#[derive(Clone, Debug)]
 pub struct MetadataAddress {
-    bytes: Vec<u8>
+    bytes: Vec<u8>,
+    // The bech32 variant used to encode the address (defaults to Bech32).
+    variant: Variant
 }
 
 impl MetadataAddress {
@@ -38,7 +86,8 @@ impl MetadataAddress {
         data.push(KEY_SCOPE);
         data.extend(MetadataAddress::uuid_as_byte_array(scope_uuid));
         MetadataAddress {
-            bytes: data
+            bytes: data,
+            variant: Variant::Bech32
         }
     }
 
@@ -48,23 +97,23 @@ impl MetadataAddress {
         data.extend(MetadataAddress::uuid_as_byte_array(scope_uuid));
         data.extend(MetadataAddress::uuid_as_byte_array(session_uuid));
         MetadataAddress {
-            bytes: data
+            bytes: data,
+            variant: Variant::Bech32
         }
     }
 
-    pub fn for_record(scope_uuid: Uuid, record_name: String) -> Self {
-        /* TODO
-        if (recordName.isBlank()) {
-            throw IllegalArgumentException("Invalid recordName: cannot be empty or blank.")
+    pub fn for_record(scope_uuid: Uuid, record_name: &str) -> Result<Self, MetadataAddressError> {
+        if record_name.trim().is_empty() {
+            return Err(MetadataAddressError::BlankRecordName {});
         }
-        */
         let mut data: Vec<u8> = Vec::new();
         data.push(KEY_RECORD);
         data.extend(MetadataAddress::uuid_as_byte_array(scope_uuid));
         data.extend(MetadataAddress::as_hashed_bytes(record_name));
-        MetadataAddress {
-            bytes: data
-        }
+        Ok(MetadataAddress {
+            bytes: data,
+            variant: Variant::Bech32
+        })
     }
 
     pub fn for_scope_specification(scope_spec_uuid: Uuid) -> Self {
@@ -72,7 +121,8 @@ impl MetadataAddress {
         data.push(KEY_SCOPE_SPECIFICATION);
         data.extend(MetadataAddress::uuid_as_byte_array(scope_spec_uuid));
         MetadataAddress {
-            bytes: data
+            bytes: data,
+            variant: Variant::Bech32
         }
     }
 
@@ -81,95 +131,127 @@ impl MetadataAddress {
         data.push(KEY_CONTRACT_SPECIFICATION);
         data.extend(MetadataAddress::uuid_as_byte_array(contract_spec_uuid));
         MetadataAddress {
-            bytes: data
+            bytes: data,
+            variant: Variant::Bech32
         }
     }
 
-    pub fn for_record_specification(contract_spec_uuid: Uuid, record_spec_name: String) -> Self {
-        /* TODO
-        if (recordSpecName.isBlank()) {
-            throw IllegalArgumentException("Invalid recordSpecName: cannot be empty or blank.")
+    pub fn for_record_specification(contract_spec_uuid: Uuid, record_spec_name: &str) -> Result<Self, MetadataAddressError> {
+        if record_spec_name.trim().is_empty() {
+            return Err(MetadataAddressError::BlankRecordSpecificationName {});
         }
-        */
         let mut data: Vec<u8> = Vec::new();
         data.push(KEY_RECORD_SPECIFICATION);
         data.extend(MetadataAddress::uuid_as_byte_array(contract_spec_uuid));
         data.extend(MetadataAddress::as_hashed_bytes(record_spec_name));
-        MetadataAddress {
-            bytes: data
-        }
+        Ok(MetadataAddress {
+            bytes: data,
+            variant: Variant::Bech32
+        })
     }
 
+    // Parse a bech32 metadata address, panicking when the value is not a valid metadata address or
+    // its HRP doesn't match the address type (see try_from_bech32).
     pub fn from_bech32(bech32_value: String) -> Self {
-        let (hrp, data5, _variant) = bech32::decode(&*bech32_value).unwrap();
-        let data = Vec::<u8>::from_base32(&data5).unwrap();
-        MetadataAddress::validate_bytes(&data);
+        MetadataAddress::try_from_bech32(&bech32_value).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Parse a bech32 metadata address, returning an error rather than panicking when the value
+    // is not a valid metadata address or its HRP doesn't match the address type.
+    pub fn try_from_bech32(bech32_value: &str) -> Result<Self, MetadataAddressError> {
+        let (hrp, data5, variant) = bech32::decode(bech32_value)
+            .map_err(|e| MetadataAddressError::InvalidBech32 { error: e.to_string() })?;
+        let data = Vec::<u8>::from_base32(&data5)
+            .map_err(|e| MetadataAddressError::InvalidBech32 { error: e.to_string() })?;
+        MetadataAddress::validate_bytes(&data)?;
         let prefix = MetadataAddress::get_prefix_from_key(data[0]);
         if hrp != prefix {
-            /* TODO
-            throw IllegalArgumentException("Incorrect HRP: Expected ${prefix}, Actual: ${hrp}.")
-            */
-        }
-        MetadataAddress {
-            bytes: data
+            return Err(MetadataAddressError::IncorrectPrefix { expected: prefix, actual: hrp });
         }
+        Ok(MetadataAddress {
+            bytes: data,
+            variant
+        })
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, MetadataAddressError> {
+        MetadataAddress::validate_bytes(&bytes)?;
+        Ok(MetadataAddress {
+            bytes,
+            variant: Variant::Bech32
+        })
     }
 
     fn uuid_as_byte_array(uuid: Uuid) -> Vec<u8> {
         uuid.as_bytes().to_vec()
     }
 
-    fn byte_array_as_uuid(data: Vec<u8>) -> Uuid {
-        Uuid::from_bytes(data.try_into().unwrap())
-        // TODO: .unwrap_or_else(|v: Vec<T>| panic!("Expected a Vec of length {} but it was {}", N, v.len()))
+    fn byte_array_as_uuid(data: &[u8]) -> Result<Uuid, MetadataAddressError> {
+        let bytes: [u8; 16] = data.try_into().map_err(|_| MetadataAddressError::IncorrectLength {
+            prefix: "uuid".to_string(),
+            expected: 16,
+            actual: data.len(),
+        })?;
+        Ok(Uuid::from_bytes(bytes))
     }
 
-    fn as_hashed_bytes(string: String) -> Vec<u8> {
+    fn as_hashed_bytes(string: &str) -> Vec<u8> {
         let mut hasher = Sha256::new();
-        hasher.update(string.to_lowercase().as_bytes().to_vec());
+        hasher.update(string.to_lowercase().as_bytes());
         let mut hashed_bytes = hasher.finalize().to_vec();
         hashed_bytes.truncate(16);
         hashed_bytes
     }
 
     fn get_prefix_from_key(key: u8) -> String {
+        MetadataAddress::prefix_str_from_key(key).to_string()
+    }
+
+    fn prefix_str_from_key(key: u8) -> &'static str {
         match key {
-            KEY_SCOPE => PREFIX_SCOPE.to_string(),
-            KEY_SESSION => PREFIX_SESSION.to_string(),
-            KEY_RECORD => PREFIX_RECORD.to_string(),
-            KEY_SCOPE_SPECIFICATION => PREFIX_SCOPE_SPECIFICATION.to_string(),
-            KEY_CONTRACT_SPECIFICATION => PREFIX_CONTRACT_SPECIFICATION.to_string(),
-            KEY_RECORD_SPECIFICATION => PREFIX_RECORD_SPECIFICATION.to_string(),
+            KEY_SCOPE => PREFIX_SCOPE,
+            KEY_SESSION => PREFIX_SESSION,
+            KEY_RECORD => PREFIX_RECORD,
+            KEY_SCOPE_SPECIFICATION => PREFIX_SCOPE_SPECIFICATION,
+            KEY_CONTRACT_SPECIFICATION => PREFIX_CONTRACT_SPECIFICATION,
+            KEY_RECORD_SPECIFICATION => PREFIX_RECORD_SPECIFICATION,
             _ => {
                 /* TODO
                 throw IllegalArgumentException("Invalid key: $key")
                 */
-                "".to_string()
+                ""
             }
         }
     }
 
-    fn validate_bytes(bytes: &Vec<u8>) {
-        let expected_length = match bytes[0] {
+    fn validate_bytes(bytes: &[u8]) -> Result<(), MetadataAddressError> {
+        let key = *bytes.first().ok_or(MetadataAddressError::EmptyData {})?;
+        let expected_length = match key {
             KEY_SCOPE => 17,
             KEY_SESSION => 33,
             KEY_RECORD => 33,
             KEY_SCOPE_SPECIFICATION => 17,
             KEY_CONTRACT_SPECIFICATION => 17,
             KEY_RECORD_SPECIFICATION => 33,
-            _ => {
-                /* TODO
-                throw IllegalArgumentException("Invalid key: ${bytes[0]}")
-                */
-                0
-            }
+            _ => return Err(MetadataAddressError::InvalidKey { key }),
         };
 
         if expected_length != bytes.len() {
-            /* TODO
-            throw IllegalArgumentException("Incorrect data length for type ${getPrefixFromKey(bytes[0])}: Expected ${expectedLength}, Actual: ${bytes.size}.")
-            */
+            return Err(MetadataAddressError::IncorrectLength {
+                prefix: MetadataAddress::get_prefix_from_key(key),
+                expected: expected_length,
+                actual: bytes.len(),
+            });
         }
+        Ok(())
+    }
+
+    pub fn get_variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
     }
 
     pub fn get_key(&self) -> u8 {
@@ -180,8 +262,40 @@ impl MetadataAddress {
         MetadataAddress::get_prefix_from_key(self.get_key())
     }
 
-    pub fn get_primary_uuid(&self) -> Uuid {
-        MetadataAddress::byte_array_as_uuid(self.bytes.get(1..17).unwrap().to_vec())
+    // The same as get_prefix, without allocating a string.
+    pub fn prefix_str(&self) -> &'static str {
+        MetadataAddress::prefix_str_from_key(self.get_key())
+    }
+
+    pub fn get_primary_uuid(&self) -> Result<Uuid, MetadataAddressError> {
+        let data = self.bytes.get(1..17).ok_or(MetadataAddressError::IncorrectLength {
+            prefix: self.get_prefix(),
+            expected: 17,
+            actual: self.bytes.len(),
+        })?;
+        MetadataAddress::byte_array_as_uuid(data)
+    }
+
+    // Check if the specified name hashes to the secondary bytes of a record or record
+    // specification address. The comparison is performed in constant time.
+    pub fn record_name_matches(&self, name: &str) -> bool {
+        match self.get_key() {
+            KEY_RECORD | KEY_RECORD_SPECIFICATION => {
+                let expected = MetadataAddress::as_hashed_bytes(name);
+                let actual = &self.bytes[17..];
+                expected.len() == actual.len()
+                    && expected.iter().zip(actual.iter()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+            }
+            _ => false,
+        }
+    }
+
+    // Get the parent contract specification address of a record specification address.
+    pub fn get_contract_specification(&self) -> Result<MetadataAddress, MetadataAddressError> {
+        match self.get_key() {
+            KEY_RECORD_SPECIFICATION => Ok(MetadataAddress::for_contract_specification(self.get_primary_uuid()?)),
+            _ => Err(MetadataAddressError::UnsupportedType { prefix: self.get_prefix() }),
+        }
     }
 
     pub fn get_secondary_bytes(&self) -> Vec<u8> {
@@ -192,25 +306,75 @@ impl MetadataAddress {
         }
     }
 
+    // The same as get_secondary_bytes, borrowing the bytes rather than copying them.
+    pub fn secondary_bytes_ref(&self) -> &[u8] {
+        self.bytes.get(17..).unwrap_or(&[])
+    }
+}
+
+// Equality and hashing are based on the underlying bytes only, so the same address
+// encoded with different bech32 variants is considered equal.
+impl PartialEq for MetadataAddress {
+
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+
 }
 
-impl ToString for MetadataAddress {
+impl Eq for MetadataAddress {}
 
-    fn to_string(&self) -> String {
-        bech32::encode(&*self.get_prefix(), self.bytes.to_base32(), Variant::Bech32).unwrap()
+impl Hash for MetadataAddress {
+
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bytes.hash(state);
+    }
+
+}
+
+impl fmt::Display for MetadataAddress {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let encoded = bech32::encode(&self.get_prefix(), self.bytes.to_base32(), self.variant).unwrap();
+        write!(f, "{}", encoded)
+    }
+
+}
+
+// Metadata addresses are exchanged with clients as their bech32 string encoding.
+#[cfg(feature = "contract")]
+impl JsonSchema for MetadataAddress {
+
+    fn schema_name() -> String {
+        "MetadataAddress".to_string()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            format: Some("bech32".to_string()),
+            ..Default::default()
+        }.into()
     }
 
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
     use std::convert::TryInto;
-    use crate::utils::MetadataAddress;
-    use crate::utils::vec_contains;
+    use crate::error::MetadataAddressError;
+    use crate::utils::{deterministic_uuid, normalize_asset_id, pledge_marker_denom, MetadataAddress};
+    use bech32::Variant;
+    #[cfg(feature = "contract")]
+    use cosmwasm_schema::schema_for;
+    #[cfg(feature = "contract")]
+    use schemars::schema::InstanceType;
     use uuid::Uuid;
 
     const SCOPE_UUID: &str = "d1f0a3a5-c1c2-4f8e-a8c1-416e102d0520";
     const SCOPE_BECH32: &str = "scope1qrglpga9c8pylr4gc9qkuypdq5sqph649l";
+    const SCOPE_BECH32M: &str = "scope1qrglpga9c8pylr4gc9qkuypdq5sq5t2eqa";
 
     const SESSION_UUID: &str = "73b477e1-dfeb-4709-88cf-b0eb80830d3c";
     const SESSION_BECH32: &str = "session1q8glpga9c8pylr4gc9qkuypdq5s88drhu807k3cf3r8mp6uqsvxnckjeje7";
@@ -253,7 +417,7 @@ mod tests {
 
     #[test]
     pub fn metadata_address_for_record() {
-        let record_addr = MetadataAddress::for_record(Uuid::parse_str(RECORD_UUID).unwrap(), RECORD_NAME.to_string());
+        let record_addr = MetadataAddress::for_record(Uuid::parse_str(RECORD_UUID).unwrap(), RECORD_NAME).unwrap();
         let result = record_addr.to_string();
         match &*result {
             RECORD_BECH32 => {}
@@ -283,7 +447,7 @@ mod tests {
 
     #[test]
     pub fn metadata_address_for_record_specification() {
-        let record_spec_addr = MetadataAddress::for_record_specification(Uuid::parse_str(RECORD_SPEC_UUID).unwrap(), RECORD_SPEC_NAME.to_string());
+        let record_spec_addr = MetadataAddress::for_record_specification(Uuid::parse_str(RECORD_SPEC_UUID).unwrap(), RECORD_SPEC_NAME).unwrap();
         let result = record_spec_addr.to_string();
         match &*result {
             RECORD_SPEC_BECH32 => {}
@@ -294,7 +458,7 @@ mod tests {
     #[test]
     pub fn metadata_address_for_scope_from_bech32() {
         let scope_addr = MetadataAddress::from_bech32(SCOPE_BECH32.to_string());
-        let scope_uuid = scope_addr.get_primary_uuid().to_hyphenated().to_string().to_lowercase();
+        let scope_uuid = scope_addr.get_primary_uuid().unwrap().to_hyphenated().to_string().to_lowercase();
         match &*scope_uuid {
             SCOPE_UUID => {}
             _ => panic!("unexpected error: expected {:?} got {:?}", SCOPE_UUID, scope_uuid),
@@ -304,7 +468,7 @@ mod tests {
     #[test]
     pub fn metadata_address_for_session_from_bech32() {
         let session_addr = MetadataAddress::from_bech32(SESSION_BECH32.to_string());
-        let scope_uuid = session_addr.get_primary_uuid().to_hyphenated().to_string().to_lowercase();
+        let scope_uuid = session_addr.get_primary_uuid().unwrap().to_hyphenated().to_string().to_lowercase();
         match &*scope_uuid {
             SCOPE_UUID => {}
             _ => panic!("unexpected error: expected {:?} got {:?}", SCOPE_UUID, scope_uuid),
@@ -319,21 +483,24 @@ mod tests {
     #[test]
     pub fn metadata_address_for_record_from_bech32() {
         let record_addr = MetadataAddress::from_bech32(RECORD_BECH32.to_string());
-        let record_uuid = record_addr.get_primary_uuid().to_hyphenated().to_string().to_lowercase();
+        let record_uuid = record_addr.get_primary_uuid().unwrap().to_hyphenated().to_string().to_lowercase();
         match &*record_uuid {
             RECORD_UUID => {}
             _ => panic!("unexpected error: expected {:?} got {:?}", RECORD_UUID, record_uuid),
         }
         let record_name_sha256 = record_addr.get_secondary_bytes();
-        if !vec_contains(&*record_name_sha256, &RECORD_NAME_SHA256) {
+        if record_name_sha256 != RECORD_NAME_SHA256 {
             panic!("unexpected error: expected {:?} got {:?}", RECORD_NAME_SHA256, record_name_sha256)
         }
+        if !record_addr.record_name_matches(RECORD_NAME) {
+            panic!("unexpected error: expected record name {:?} to match", RECORD_NAME)
+        }
     }
 
     #[test]
     pub fn metadata_address_for_scope_specification_from_bech32() {
         let scope_spec_addr = MetadataAddress::from_bech32(SCOPE_SPEC_BECH32.to_string());
-        let scope_spec_uuid = scope_spec_addr.get_primary_uuid().to_hyphenated().to_string().to_lowercase();
+        let scope_spec_uuid = scope_spec_addr.get_primary_uuid().unwrap().to_hyphenated().to_string().to_lowercase();
         match &*scope_spec_uuid {
             SCOPE_SPEC_UUID => {}
             _ => panic!("unexpected error: expected {:?} got {:?}", SCOPE_SPEC_UUID, scope_spec_uuid),
@@ -343,7 +510,7 @@ mod tests {
     #[test]
     pub fn metadata_address_for_contract_specification_from_bech32() {
         let contract_spec_addr = MetadataAddress::from_bech32(CONTRACT_SPEC_BECH32.to_string());
-        let contract_spec_uuid = contract_spec_addr.get_primary_uuid().to_hyphenated().to_string().to_lowercase();
+        let contract_spec_uuid = contract_spec_addr.get_primary_uuid().unwrap().to_hyphenated().to_string().to_lowercase();
         match &*contract_spec_uuid {
             CONTRACT_SPEC_UUID => {}
             _ => panic!("unexpected error: expected {:?} got {:?}", CONTRACT_SPEC_UUID, contract_spec_uuid),
@@ -353,14 +520,306 @@ mod tests {
     #[test]
     pub fn metadata_address_for_record_specification_from_bech32() {
         let record_spec_addr = MetadataAddress::from_bech32(RECORD_SPEC_BECH32.to_string());
-        let record_spec_uuid = record_spec_addr.get_primary_uuid().to_hyphenated().to_string().to_lowercase();
+        let record_spec_uuid = record_spec_addr.get_primary_uuid().unwrap().to_hyphenated().to_string().to_lowercase();
         match &*record_spec_uuid {
             RECORD_SPEC_UUID => {}
             _ => panic!("unexpected error: expected {:?} got {:?}", RECORD_SPEC_UUID, record_spec_uuid),
         }
         let record_spec_name_sha256 = record_spec_addr.get_secondary_bytes();
-        if !vec_contains(&*record_spec_name_sha256, &RECORD_SPEC_NAME_SHA256) {
+        if record_spec_name_sha256 != RECORD_SPEC_NAME_SHA256 {
             panic!("unexpected error: expected {:?} got {:?}", RECORD_SPEC_NAME_SHA256, record_spec_name_sha256)
         }
+        if !record_spec_addr.record_name_matches(RECORD_SPEC_NAME) {
+            panic!("unexpected error: expected record spec name {:?} to match", RECORD_SPEC_NAME)
+        }
+    }
+
+    #[test]
+    pub fn metadata_address_round_trip_preserves_bech32_variant() {
+        let scope_addr = MetadataAddress::from_bech32(SCOPE_BECH32.to_string());
+        assert_eq!(scope_addr.get_variant(), Variant::Bech32);
+        let result = scope_addr.to_string();
+        match &*result {
+            SCOPE_BECH32 => {}
+            _ => panic!("unexpected error: expected {:?} got {:?}", SCOPE_BECH32, result),
+        }
+    }
+
+    #[test]
+    pub fn metadata_address_round_trip_preserves_bech32m_variant() {
+        let scope_addr = MetadataAddress::from_bech32(SCOPE_BECH32M.to_string());
+        assert_eq!(scope_addr.get_variant(), Variant::Bech32m);
+        let scope_uuid = scope_addr.get_primary_uuid().unwrap().to_hyphenated().to_string().to_lowercase();
+        match &*scope_uuid {
+            SCOPE_UUID => {}
+            _ => panic!("unexpected error: expected {:?} got {:?}", SCOPE_UUID, scope_uuid),
+        }
+        let result = scope_addr.to_string();
+        match &*result {
+            SCOPE_BECH32M => {}
+            _ => panic!("unexpected error: expected {:?} got {:?}", SCOPE_BECH32M, result),
+        }
+    }
+
+    #[test]
+    pub fn metadata_address_for_record_with_empty_name() {
+        let result = MetadataAddress::for_record(Uuid::parse_str(RECORD_UUID).unwrap(), "");
+        match result {
+            Err(MetadataAddressError::BlankRecordName {}) => {}
+            _ => panic!("unexpected result: expected blank record name error"),
+        }
+    }
+
+    #[test]
+    pub fn metadata_address_for_record_with_blank_name() {
+        let result = MetadataAddress::for_record(Uuid::parse_str(RECORD_UUID).unwrap(), "   ");
+        match result {
+            Err(MetadataAddressError::BlankRecordName {}) => {}
+            _ => panic!("unexpected result: expected blank record name error"),
+        }
+    }
+
+    #[test]
+    pub fn metadata_address_for_record_specification_with_empty_name() {
+        let result = MetadataAddress::for_record_specification(Uuid::parse_str(RECORD_SPEC_UUID).unwrap(), "");
+        match result {
+            Err(MetadataAddressError::BlankRecordSpecificationName {}) => {}
+            _ => panic!("unexpected result: expected blank record specification name error"),
+        }
+    }
+
+    #[test]
+    pub fn metadata_address_for_record_specification_with_blank_name() {
+        let result = MetadataAddress::for_record_specification(Uuid::parse_str(RECORD_SPEC_UUID).unwrap(), "   ");
+        match result {
+            Err(MetadataAddressError::BlankRecordSpecificationName {}) => {}
+            _ => panic!("unexpected result: expected blank record specification name error"),
+        }
+    }
+
+    #[test]
+    pub fn metadata_address_equality_and_hashing() {
+        let parsed = MetadataAddress::from_bech32(SCOPE_BECH32.to_string());
+        let constructed = MetadataAddress::for_scope(Uuid::parse_str(SCOPE_UUID).unwrap());
+        assert_eq!(parsed, constructed);
+
+        let mut addresses = HashSet::new();
+        addresses.insert(parsed);
+        addresses.insert(constructed);
+        assert_eq!(addresses.len(), 1);
+    }
+
+    #[test]
+    pub fn metadata_address_borrowed_accessors_match_owned() {
+        let addresses = vec![
+            SCOPE_BECH32,
+            SESSION_BECH32,
+            RECORD_BECH32,
+            SCOPE_SPEC_BECH32,
+            CONTRACT_SPEC_BECH32,
+            RECORD_SPEC_BECH32,
+        ];
+        for address in addresses {
+            let address = MetadataAddress::from_bech32(address.to_string());
+            assert_eq!(address.prefix_str(), address.get_prefix());
+            assert_eq!(
+                address.secondary_bytes_ref(),
+                address.get_secondary_bytes().as_slice()
+            );
+        }
+    }
+
+    #[test]
+    pub fn metadata_address_from_bytes() {
+        let scope_addr = MetadataAddress::for_scope(Uuid::parse_str(SCOPE_UUID).unwrap());
+        let result = MetadataAddress::from_bytes(scope_addr.as_bytes().to_vec()).unwrap().to_string();
+        match &*result {
+            SCOPE_BECH32 => {}
+            _ => panic!("unexpected error: expected {:?} got {:?}", SCOPE_BECH32, result),
+        }
+    }
+
+    #[test]
+    pub fn metadata_address_from_truncated_bytes() {
+        let scope_addr = MetadataAddress::for_scope(Uuid::parse_str(SCOPE_UUID).unwrap());
+        let result = MetadataAddress::from_bytes(scope_addr.as_bytes()[..10].to_vec());
+        match result {
+            Err(MetadataAddressError::IncorrectLength { prefix, expected, actual }) => {
+                assert_eq!(prefix, "scope");
+                assert_eq!(expected, 17);
+                assert_eq!(actual, 10);
+            }
+            _ => panic!("unexpected result: expected incorrect length error"),
+        }
+    }
+
+    #[test]
+    pub fn metadata_address_from_bytes_with_invalid_key() {
+        let result = MetadataAddress::from_bytes(vec![0x09; 17]);
+        match result {
+            Err(MetadataAddressError::InvalidKey { key: 0x09 }) => {}
+            _ => panic!("unexpected result: expected invalid key error"),
+        }
+    }
+
+    #[test]
+    pub fn metadata_address_record_name_matches() {
+        let record_addr = MetadataAddress::from_bech32(RECORD_BECH32.to_string());
+        assert!(record_addr.record_name_matches(RECORD_NAME));
+        assert!(record_addr.record_name_matches(&RECORD_NAME.to_uppercase()));
+        assert!(!record_addr.record_name_matches(RECORD_SPEC_NAME));
+
+        let scope_addr = MetadataAddress::from_bech32(SCOPE_BECH32.to_string());
+        assert!(!scope_addr.record_name_matches(RECORD_NAME));
+    }
+
+    #[test]
+    pub fn deterministic_uuid_is_stable() {
+        let namespace = Uuid::parse_str(SCOPE_SPEC_UUID).unwrap();
+        let first = deterministic_uuid(namespace, "loan-12345");
+        let second = deterministic_uuid(namespace, "loan-12345");
+        assert_eq!(first, second);
+        assert_eq!(first.get_version_num(), 5);
+    }
+
+    #[test]
+    pub fn deterministic_uuid_diverges_for_different_names() {
+        let namespace = Uuid::parse_str(SCOPE_SPEC_UUID).unwrap();
+        assert_ne!(deterministic_uuid(namespace, "loan-12345"), deterministic_uuid(namespace, "loan-12346"));
+
+        let other_namespace = Uuid::parse_str(CONTRACT_SPEC_UUID).unwrap();
+        assert_ne!(deterministic_uuid(namespace, "loan-12345"), deterministic_uuid(other_namespace, "loan-12345"));
+    }
+
+    #[test]
+    pub fn pledge_marker_denom_format() {
+        assert_eq!(pledge_marker_denom("pm.facility.wf1", SCOPE_UUID), format!("pm.facility.wf1.pledge.{}", SCOPE_UUID));
+    }
+
+    #[test]
+    pub fn pledge_marker_denom_is_unique() {
+        let first = pledge_marker_denom("pm.facility.wf1", SCOPE_UUID);
+        assert_ne!(first, pledge_marker_denom("pm.facility.wf1", SESSION_UUID));
+        assert_ne!(first, pledge_marker_denom("pm.facility.wf2", SCOPE_UUID));
+        assert_ne!(first, "pm.facility.wf1");
+    }
+
+    #[test]
+    pub fn metadata_address_from_short_session_bytes() {
+        let session_addr = MetadataAddress::for_session(Uuid::parse_str(SCOPE_UUID).unwrap(), Uuid::parse_str(SESSION_UUID).unwrap());
+        let result = MetadataAddress::from_bytes(session_addr.as_bytes()[..17].to_vec());
+        match result {
+            Err(MetadataAddressError::IncorrectLength { prefix, expected, actual }) => {
+                assert_eq!(prefix, "session");
+                assert_eq!(expected, 33);
+                assert_eq!(actual, 17);
+            }
+            _ => panic!("unexpected result: expected incorrect length error"),
+        }
+        let scope_uuid = session_addr.get_primary_uuid();
+        match scope_uuid {
+            Ok(scope_uuid) => assert_eq!(scope_uuid, Uuid::parse_str(SCOPE_UUID).unwrap()),
+            _ => panic!("unexpected error: expected {:?} got {:?}", SCOPE_UUID, scope_uuid),
+        }
+    }
+
+    #[test]
+    pub fn metadata_address_get_contract_specification() {
+        let contract_spec_addr = MetadataAddress::for_contract_specification(Uuid::parse_str(CONTRACT_SPEC_UUID).unwrap());
+        let record_spec_addr = MetadataAddress::for_record_specification(Uuid::parse_str(CONTRACT_SPEC_UUID).unwrap(), RECORD_SPEC_NAME).unwrap();
+        let result = record_spec_addr.get_contract_specification().unwrap();
+        assert_eq!(result, contract_spec_addr);
+        let result = result.to_string();
+        match &*result {
+            CONTRACT_SPEC_BECH32 => {}
+            _ => panic!("unexpected error: expected {:?} got {:?}", CONTRACT_SPEC_BECH32, result),
+        }
+    }
+
+    #[test]
+    pub fn metadata_address_get_contract_specification_from_bech32() {
+        let record_spec_addr = MetadataAddress::from_bech32(RECORD_SPEC_BECH32.to_string());
+        let contract_spec_addr = record_spec_addr.get_contract_specification().unwrap();
+        assert_eq!(contract_spec_addr.get_prefix(), "contractspec");
+        assert_eq!(contract_spec_addr.get_primary_uuid().unwrap(), Uuid::parse_str(RECORD_SPEC_UUID).unwrap());
+    }
+
+    #[test]
+    pub fn metadata_address_get_contract_specification_for_other_types() {
+        let scope_spec_addr = MetadataAddress::from_bech32(SCOPE_SPEC_BECH32.to_string());
+        match scope_spec_addr.get_contract_specification() {
+            Err(MetadataAddressError::UnsupportedType { prefix }) => assert_eq!(prefix, "scopespec"),
+            _ => panic!("unexpected result: expected unsupported type error"),
+        }
+    }
+
+    #[cfg(feature = "contract")]
+    #[test]
+    pub fn metadata_address_json_schema_is_string() {
+        let schema = schema_for!(MetadataAddress).schema;
+        assert_eq!(schema.instance_type, Some(InstanceType::String.into()));
+        assert_eq!(schema.format, Some("bech32".to_string()));
+    }
+
+    #[test]
+    pub fn metadata_address_try_from_bech32() {
+        let scope_addr = MetadataAddress::try_from_bech32(SCOPE_BECH32).unwrap();
+        assert_eq!(scope_addr, MetadataAddress::for_scope(Uuid::parse_str(SCOPE_UUID).unwrap()));
+        match MetadataAddress::try_from_bech32("not-an-address") {
+            Err(MetadataAddressError::InvalidBech32 { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    pub fn metadata_address_try_from_bech32_with_incorrect_prefix() {
+        let scope_addr = MetadataAddress::for_scope(Uuid::parse_str(SCOPE_UUID).unwrap());
+        let session_hrp = bech32::encode(
+            "session",
+            bech32::ToBase32::to_base32(&scope_addr.as_bytes()),
+            Variant::Bech32,
+        )
+        .unwrap();
+        match MetadataAddress::try_from_bech32(&session_hrp) {
+            Err(MetadataAddressError::IncorrectPrefix { expected, actual }) => {
+                assert_eq!(expected, "scope");
+                assert_eq!(actual, "session");
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Incorrect HRP: Expected \"scope\", Actual: \"session\"")]
+    pub fn metadata_address_from_bech32_with_incorrect_prefix() {
+        let scope_addr = MetadataAddress::for_scope(Uuid::parse_str(SCOPE_UUID).unwrap());
+        let session_hrp = bech32::encode(
+            "session",
+            bech32::ToBase32::to_base32(&scope_addr.as_bytes()),
+            Variant::Bech32,
+        )
+        .unwrap();
+        MetadataAddress::from_bech32(session_hrp);
+    }
+
+    #[test]
+    pub fn normalize_asset_id_for_uuids_and_scope_addresses() {
+        assert_eq!(normalize_asset_id(SCOPE_UUID).unwrap(), SCOPE_UUID);
+        assert_eq!(normalize_asset_id(&SCOPE_UUID.to_uppercase()).unwrap(), SCOPE_UUID);
+        assert_eq!(normalize_asset_id(&SCOPE_UUID.replace("-", "")).unwrap(), SCOPE_UUID);
+        assert_eq!(normalize_asset_id(SCOPE_BECH32).unwrap(), SCOPE_UUID);
+        assert_eq!(normalize_asset_id(SCOPE_BECH32M).unwrap(), SCOPE_UUID);
+    }
+
+    #[test]
+    pub fn normalize_asset_id_for_other_addresses() {
+        match normalize_asset_id(SESSION_BECH32) {
+            Err(MetadataAddressError::UnsupportedType { prefix }) => assert_eq!(prefix, "session"),
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match normalize_asset_id("scope1notvalid") {
+            Err(MetadataAddressError::InvalidBech32 { .. }) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
     }
 }